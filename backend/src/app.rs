@@ -0,0 +1,52 @@
+use axum::{routing::get, Extension, Router};
+use http::header::{ACCEPT, AUTHORIZATION};
+use http::Method;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::config::{api_get_public_config, Config};
+use crate::database_ctl::directory::{
+    api_get_child_directories, api_get_node, api_get_node_assets, api_get_root_directories,
+    api_get_subtree, api_search_nodes,
+};
+use crate::database_ctl::repository::SharedRepository;
+use crate::return_code::print_code;
+
+/// 构建 API 路由；数据来源由 `repo` 决定，便于在测试中替换为内存实现。
+/// `extra` 用于挂载依赖具体数据库的调试路由，同样会套上 CORS 设置。
+pub fn build_router(repo: SharedRepository, config: Config, extra: Router) -> Router {
+    let allow_origin = if config.allows_any_origin() {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(config.cors_header_values())
+    };
+
+    Router::new()
+        //用于测试前后端沟通
+        .route("/print", get(print_code))
+        //前端需要的公开配置（资源前缀等）
+        .route("/config", get(api_get_public_config))
+        //获取一级目录
+        .route("/nodes/root", get(api_get_root_directories))
+        //获取子目录（路径需要 URL 编码）
+        .route("/nodes/children/{path}", get(api_get_child_directories))
+        //获取节点对应的资源文件
+        .route("/nodes/assets/{path}", get(api_get_node_assets))
+        //以指定节点为根的嵌套子树（?depth=N），用于前端预取整个分支
+        .route("/nodes/tree/{path}", get(api_get_subtree))
+        //全文搜索节点名、路径与 markdown 内容（?q=&page=&per_page=）
+        .route("/nodes/search", get(api_search_nodes))
+        //单个节点详情：祖先链、同级节点、子节点数与资源计数
+        //（放在 detail 前缀下，名为 root / search 的一级节点不会与上面的静态路由冲突）
+        .route("/nodes/detail/{path}", get(api_get_node))
+        //加入节点仓库与配置
+        .layer(Extension(repo))
+        .layer(Extension(config))
+        .merge(extra)
+        // 设置 CORS
+        .layer(
+            CorsLayer::new()
+                .allow_origin(allow_origin)
+                .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+                .allow_headers([AUTHORIZATION, ACCEPT]), // .allow_credentials(true)
+        )
+}
@@ -1,140 +1,939 @@
-use anyhow::Result;
 use axum::{
-    extract::{Extension, Path},
-    response::{IntoResponse, Json},
+    extract::{Extension, Path, Query},
+    response::Json,
 };
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use sqlx::postgres::PgPool;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use sqlx::postgres::{PgPool, PgRow};
 use sqlx::Row;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::database_ctl::ordering::{
+    sort_assets, sort_directories, sort_tree, ListParams, SortMode,
+};
+use crate::database_ctl::repository::{NodeRepository, SharedRepository};
+use crate::database_ctl::visibility::Visibility;
+use crate::error::{ApiError, ApiResult};
+use crate::node_path::NodePath;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DirectoryNode {
     pub path: String,
     pub has_subnodes: bool,
     pub raw_filename: String,
+    /// 节点目录下是否有 `layout.md`
+    #[serde(default)]
+    pub has_layout: bool,
+    /// `layout.md` front matter 中的作品信息
+    #[serde(default)]
+    pub metadata: NodeMetadata,
+    /// 导入时写入的排序键，列表默认按它排序
+    #[serde(default)]
+    pub sort_key: String,
+    /// 节点（含继承自祖先）的可见性
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// 排版文件相对于资源根目录的路径，前端据此加载排版内容
+    #[serde(default)]
+    pub layout_path: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 作品信息，由 node-generate-tool 从 `layout.md` 的 front matter 中提取，
+/// 以 JSONB 存放在 `directory_nodes.metadata`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeMetadata {
+    /// 展示标题；为空时前端使用目录名
+    pub title: Option<String>,
+    pub material: Option<String>,
+    pub year: Option<i32>,
+    pub price: Option<String>,
+    pub tags: Vec<String>,
+    pub order: Option<i64>,
+    pub hidden: bool,
+}
+
+impl NodeMetadata {
+    /// 解析数据库或 CSV 中的 JSON 文本；内容无效时按空值处理
+    pub fn from_json(text: &str) -> Self {
+        serde_json::from_str(text).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct AssetNode {
     pub file_path: String,
     pub raw_path: String,
     pub raw_filename: String,
+    /// 导入时写入的排序键，列表默认按它排序
+    #[serde(default)]
+    pub sort_key: String,
+    /// 文件大小（字节）
+    #[serde(default)]
+    pub file_size: i64,
+    /// MIME 类型，如 `image/jpeg`
+    #[serde(default)]
+    pub file_type: String,
+    /// 修改时间（RFC 3339，UTC）
+    #[serde(default)]
+    pub modified_time: Option<String>,
+    /// 文件内容哈希，可用于缓存失效
+    #[serde(default)]
+    pub content_hash: String,
+    /// 图片或视频的像素尺寸，无法识别时为空
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub height: Option<i32>,
+    /// 视频时长（毫秒），非视频时为空
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+    /// 资源类型（`markdown` / `image` / `video` / `pdf` / `other`），由导入工具按目录结构约定写入；
+    /// 旧数据中为空，此时按扩展名判断，见 [`AssetNode::asset_kind`]
+    #[serde(default)]
+    pub kind: String,
+    /// 图片的缩放衍生图（WebP / AVIF），视频则为封面帧的衍生图；按格式、宽度排列，未生成时为空
+    #[serde(default)]
+    pub derivatives: Vec<ImageDerivative>,
+}
+
+impl AssetNode {
+    /// 资源类型：优先使用导入时写入的类型，未写入时按扩展名判断
+    pub fn asset_kind(&self) -> &str {
+        if self.kind.is_empty() {
+            classify_asset(&self.raw_filename)
+        } else {
+            &self.kind
+        }
+    }
+}
+
+/// 图片衍生图，由 node-generate-tool thumbnails 生成
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageDerivative {
+    /// `webp` 或 `avif`
+    pub format: String,
+    pub width: i32,
+    pub height: i32,
+    /// 相对于衍生图目录（`/derivatives/`）的路径
+    pub path: String,
+}
+
+/// 搜索命中项：目录节点或资源文件，附带相关度与高亮片段
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchHit {
+    Directory {
+        node: DirectoryNode,
+        rank: f32,
+        snippet: String,
+    },
+    Asset {
+        node: AssetNode,
+        rank: f32,
+        snippet: String,
+    },
+}
+
+/// 搜索接口的查询参数：`?q=关键词&page=1&per_page=20`
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    #[serde(default)]
+    pub q: String,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+pub const SEARCH_DEFAULT_PER_PAGE: u32 = 20;
+pub const SEARCH_MAX_PER_PAGE: u32 = 100;
+
+impl SearchParams {
+    /// 去除首尾空白后的关键词
+    pub fn keywords(&self) -> &str {
+        self.q.trim()
+    }
+
+    /// 页码从 1 开始，0 或缺省时按第 1 页处理
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// 每页条数限制在 1..=SEARCH_MAX_PER_PAGE
+    pub fn per_page(&self) -> u32 {
+        self.per_page
+            .unwrap_or(SEARCH_DEFAULT_PER_PAGE)
+            .clamp(1, SEARCH_MAX_PER_PAGE)
+    }
+
+    /// 换算为 SQL 的 LIMIT / OFFSET
+    pub fn limit_offset(&self) -> (i64, i64) {
+        let per_page = i64::from(self.per_page());
+        let offset = i64::from(self.page() - 1) * per_page;
+        (per_page, offset)
+    }
+}
+
+/// 一页搜索结果及命中总数
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub total: i64,
+}
+
+/// 单个节点的详情：面包屑、同级节点与各类计数，供前端深链接一次性还原界面
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeDetail {
+    pub node: DirectoryNode,
+    /// 从一级节点到父节点的祖先链（不含自身）
+    pub ancestors: Vec<DirectoryNode>,
+    /// 父节点下的全部子节点（含自身），顺序与 `/nodes/children` 一致
+    pub siblings: Vec<DirectoryNode>,
+    pub child_count: usize,
+    /// 按资源类型（markdown / image / video / pdf / other）统计的 visual_assets 数量
+    pub asset_counts: BTreeMap<String, usize>,
+}
+
+/// 子树接口的查询参数：`?depth=N`
+#[derive(Debug, Deserialize)]
+pub struct TreeParams {
+    pub depth: Option<u32>,
+    /// 同 [`ListParams::sort`]，作用于每一层的子节点与资源
+    pub sort: Option<String>,
+}
+
+pub const TREE_DEFAULT_DEPTH: u32 = 2;
+pub const TREE_MAX_DEPTH: u32 = 5;
+/// 单次子树响应最多包含的目录节点数（不含根节点）
+pub const TREE_MAX_NODES: usize = 500;
+
+impl TreeParams {
+    /// 展开层数限制在 1..=TREE_MAX_DEPTH
+    pub fn depth(&self) -> u32 {
+        self.depth
+            .unwrap_or(TREE_DEFAULT_DEPTH)
+            .clamp(1, TREE_MAX_DEPTH)
+    }
+}
+
+/// 子树中的一个节点及其资源文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeNode {
+    #[serde(flatten)]
+    pub node: DirectoryNode,
+    pub assets: Vec<AssetNode>,
+    /// 直接子节点；超出展开深度或因数量上限被截断时为 `None`（表示未加载，而非没有子节点）
+    pub children: Option<Vec<TreeNode>>,
+}
+
+/// 子树接口响应：`truncated` 为真时最深一层因数量上限未展开
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Subtree {
+    pub tree: TreeNode,
+    pub depth: u32,
+    pub truncated: bool,
+}
+
+/// 按默认扩展名划分资源类型，规则与前端 `classify_asset_kind` 一致；只用于未记录类型的旧数据
+pub fn classify_asset(filename: &str) -> &'static str {
+    let ext = filename.rsplit('.').next().map(|s| s.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("md") | Some("markdown") => "markdown",
+        Some("mp4") | Some("mov") | Some("webm") | Some("m4v") | Some("ogg") => "video",
+        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("bmp") | Some("svg")
+        | Some("webp") | Some("ico") => "image",
+        Some("pdf") => "pdf",
+        _ => "other",
+    }
+}
+
+fn directory_from_row(row: &PgRow) -> DirectoryNode {
+    DirectoryNode {
+        path: row.get::<String, _>("path"),
+        has_subnodes: row.get::<bool, _>("has_subnodes"),
+        raw_filename: row.get::<String, _>("raw_filename"),
+        has_layout: row.get::<bool, _>("has_layout"),
+        metadata: NodeMetadata::from_json(&row.get::<String, _>("metadata")),
+        sort_key: row.get::<String, _>("sort_key"),
+        visibility: Visibility::parse(&row.get::<String, _>("visibility")),
+        layout_path: row.get::<Option<String>, _>("layout_path"),
+    }
+}
+
+/// 资源查询共用的列；修改时间格式化为 RFC 3339 文本（sqlx 未启用时间类型）
+const ASSET_COLUMNS: &str = r#"file_path::text as file_path, raw_path, raw_filename, sort_key,
+        file_size, file_type,
+        to_char(modified_time AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as modified_time,
+        content_hash, width, height, duration_ms, kind,
+        COALESCE((
+            SELECT json_agg(json_build_object(
+                'format', d.format, 'width', d.width, 'height', d.height, 'path', d.path
+            ) ORDER BY d.format, d.width)
+            FROM image_derivatives d
+            WHERE d.file_path = file_nodes.file_path
+              AND d.content_hash = file_nodes.content_hash
+        ), '[]')::text as derivatives"#;
+
+fn asset_from_row(row: &PgRow) -> AssetNode {
+    AssetNode {
+        derivatives: serde_json::from_str(&row.get::<String, _>("derivatives")).unwrap_or_default(),
+        ..asset_with_path(row, "file_path")
+    }
+}
+
+/// 按给定列名读取资源路径（搜索结果中路径列名为 `path`）；不含衍生图
+fn asset_with_path(row: &PgRow, path_column: &str) -> AssetNode {
+    AssetNode {
+        file_path: row.get::<String, _>(path_column),
+        raw_path: row.get::<String, _>("raw_path"),
+        raw_filename: row.get::<String, _>("raw_filename"),
+        sort_key: row.get::<String, _>("sort_key"),
+        file_size: row.get::<i64, _>("file_size"),
+        file_type: row.get::<String, _>("file_type"),
+        modified_time: row.get::<Option<String>, _>("modified_time"),
+        content_hash: row.get::<String, _>("content_hash"),
+        width: row.get::<Option<i32>, _>("width"),
+        height: row.get::<Option<i32>, _>("height"),
+        duration_ms: row.get::<Option<i64>, _>("duration_ms"),
+        kind: row.get::<String, _>("kind"),
+        derivatives: Vec::new(),
+    }
 }
 
 /// 获取一级目录（路径深度为 1 的节点）
-pub async fn get_root_directories(pool: &PgPool) -> Result<Vec<DirectoryNode>> {
+pub async fn get_root_directories(pool: &PgPool) -> ApiResult<Vec<DirectoryNode>> {
     let rows = sqlx::query(
         r#"
-        SELECT path::text as path, has_subnodes, raw_filename
+        SELECT path::text as path, has_subnodes, raw_filename, has_layout,
+               metadata::text as metadata, sort_key, visibility, layout_path
         FROM directory_nodes
         WHERE nlevel(path) = 1
-        ORDER BY path;
+          AND visibility = 'public'
+        ORDER BY sort_key COLLATE "C", path;
         "#,
     )
     .fetch_all(pool)
     .await?;
 
-    let directories = rows
-        .iter()
-        .map(|row| DirectoryNode {
-            path: row.get::<String, _>("path"),
-            has_subnodes: row.get::<bool, _>("has_subnodes"),
-            raw_filename: row.get::<String, _>("raw_filename"),
-        })
-        .collect();
+    let directories = rows.iter().map(directory_from_row).collect();
 
     Ok(directories)
 }
 
 /// 获取指定路径的直接子目录
-pub async fn get_child_directories(pool: &PgPool, parent_path: &str) -> Result<Vec<DirectoryNode>> {
-    // 构建 lquery 模式：parent_path.*{1} 表示匹配 parent_path 的直接子节点（深度为 1）
-    let lquery_pattern = format!("{}.*{{1}}", parent_path);
+pub async fn get_child_directories(
+    pool: &PgPool,
+    parent_path: &NodePath,
+) -> ApiResult<Vec<DirectoryNode>> {
+    let context = visible_node(pool, parent_path).await?;
 
+    // 精确匹配父路径：path 是 parent_path 的后代，且层级恰好多一层
     let rows = sqlx::query(
         r#"
-        SELECT path::text as path, has_subnodes, raw_filename
+        SELECT path::text as path, has_subnodes, raw_filename, has_layout,
+               metadata::text as metadata, sort_key, visibility, layout_path
         FROM directory_nodes
-        WHERE path ~ $1::lquery
-        ORDER BY path;
+        WHERE path <@ $1::ltree
+          AND nlevel(path) = nlevel($1::ltree) + 1
+          AND visibility IN ('public', $2)
+        ORDER BY sort_key COLLATE "C", path;
         "#,
     )
-    .bind(&lquery_pattern)
+    .bind(parent_path.as_str())
+    .bind(context.as_str())
     .fetch_all(pool)
     .await?;
 
-    let directories = rows
-        .iter()
-        .map(|row| DirectoryNode {
-            path: row.get::<String, _>("path"),
-            has_subnodes: row.get::<bool, _>("has_subnodes"),
-            raw_filename: row.get::<String, _>("raw_filename"),
-        })
-        .collect();
+    let directories = rows.iter().map(directory_from_row).collect();
 
     Ok(directories)
 }
 
-/// 获取指定目录下的资源文件（目前默认读取 visual_assets 中的直接文件）
-pub async fn get_node_assets(pool: &PgPool, parent_path: &str) -> Result<Vec<AssetNode>> {
-    if parent_path.is_empty() {
-        return Ok(Vec::new());
-    }
+/// 按路径查找单个目录节点
+pub async fn get_directory(pool: &PgPool, path: &NodePath) -> ApiResult<Option<DirectoryNode>> {
+    let row = sqlx::query(
+        r#"
+        SELECT path::text as path, has_subnodes, raw_filename, has_layout,
+               metadata::text as metadata, sort_key, visibility, layout_path
+        FROM directory_nodes
+        WHERE path = $1::ltree
+          AND visibility <> 'hidden';
+        "#,
+    )
+    .bind(path.as_str())
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(directory_from_row))
+}
+
+/// 获取节点的全部祖先（不含自身），按层级由浅到深排列
+pub async fn get_ancestors(pool: &PgPool, path: &NodePath) -> ApiResult<Vec<DirectoryNode>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT path::text as path, has_subnodes, raw_filename, has_layout,
+               metadata::text as metadata, sort_key, visibility, layout_path
+        FROM directory_nodes
+        WHERE path @> $1::ltree
+          AND path <> $1::ltree
+          AND visibility <> 'hidden'
+        ORDER BY nlevel(path);
+        "#,
+    )
+    .bind(path.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(directory_from_row).collect())
+}
 
-    // file_nodes.file_path 的父路径形如 "<node>.visual_assets"
-    let assets_parent = format!("{}.visual_assets", parent_path);
+/// 获取节点自身及 `depth` 层以内的后代，按层级、路径排序，最多 `limit` 条
+pub async fn get_descendants(
+    pool: &PgPool,
+    path: &NodePath,
+    depth: u32,
+    limit: usize,
+) -> ApiResult<Vec<DirectoryNode>> {
+    let context = visible_node(pool, path).await?;
 
     let rows = sqlx::query(
         r#"
-        SELECT file_path::text as file_path, raw_path, raw_filename
+        SELECT path::text as path, has_subnodes, raw_filename, has_layout,
+               metadata::text as metadata, sort_key, visibility, layout_path
+        FROM directory_nodes
+        WHERE path <@ $1::ltree
+          AND nlevel(path) <= nlevel($1::ltree) + $2
+          AND visibility IN ('public', $4)
+        ORDER BY nlevel(path), sort_key COLLATE "C", path
+        LIMIT $3;
+        "#,
+    )
+    .bind(path.as_str())
+    .bind(depth as i32)
+    .bind(limit as i64)
+    .bind(context.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(directory_from_row).collect())
+}
+
+/// 批量获取多个节点资源目录下的直接文件
+pub async fn get_assets_of(pool: &PgPool, parents: &[NodePath]) -> ApiResult<Vec<AssetNode>> {
+    let parents = parents
+        .iter()
+        .map(|parent| parent.to_string())
+        .collect::<Vec<String>>();
+
+    let sql = format!(
+        r#"
+        SELECT {ASSET_COLUMNS}
         FROM file_nodes
-        WHERE subpath(file_path, 0, nlevel(file_path) - 1) = $1::ltree
-        ORDER BY file_path;
+        WHERE subpath(file_path, 0, nlevel(file_path) - 2) = ANY($1::text[]::ltree[])
+          AND visibility <> 'hidden'
+        ORDER BY sort_key COLLATE "C", file_path;
+        "#
+    );
+    let rows = sqlx::query(&sql).bind(&parents).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(asset_from_row).collect())
+}
+
+/// 确认节点存在且未隐藏并返回其可见性，否则返回 404（隐藏节点与不存在的节点不作区分）
+pub async fn visible_node(pool: &PgPool, path: &NodePath) -> ApiResult<Visibility> {
+    let visibility = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT visibility FROM directory_nodes WHERE path = $1::ltree;
         "#,
     )
-    .bind(&assets_parent)
+    .bind(path.as_str())
+    .fetch_optional(pool)
+    .await?
+    .map(|text| Visibility::parse(&text));
+
+    match visibility {
+        Some(visibility) if visibility != Visibility::Hidden => Ok(visibility),
+        _ => Err(ApiError::NotFound(path.to_string())),
+    }
+}
+
+/// 获取指定目录下的资源文件，即其资源目录中的直接文件
+pub async fn get_node_assets(pool: &PgPool, parent_path: &NodePath) -> ApiResult<Vec<AssetNode>> {
+    visible_node(pool, parent_path).await?;
+
+    let sql = format!(
+        r#"
+        SELECT {ASSET_COLUMNS}
+        FROM file_nodes
+        -- file_nodes.file_path 形如 "<node>.<资源目录>.<文件>"，资源目录名由导入工具的配置决定
+        WHERE subpath(file_path, 0, nlevel(file_path) - 2) = $1::ltree
+          AND visibility <> 'hidden'
+        ORDER BY sort_key COLLATE "C", file_path;
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(parent_path.as_str())
+        .fetch_all(pool)
+        .await?;
+
+    let assets = rows.iter().map(asset_from_row).collect();
+
+    Ok(assets)
+}
+
+/// `ts_headline` 标记命中词使用的控制字符；片段经 [`render_snippet`] 转义后再换成 `<mark>`，
+/// 避免 markdown 正文中的 HTML 原样出现在返回给前端的片段里
+pub const SNIPPET_START: char = '\u{2}';
+pub const SNIPPET_STOP: char = '\u{3}';
+
+/// 把带命中标记的纯文本片段转为 HTML：转义全部文本，仅把成对的标记换成 `<mark>`
+pub fn render_snippet(raw: &str) -> String {
+    let mut html = String::with_capacity(raw.len());
+    let mut open = false;
+    for c in raw.chars() {
+        match c {
+            SNIPPET_START if !open => {
+                html.push_str("<mark>");
+                open = true;
+            }
+            SNIPPET_STOP if open => {
+                html.push_str("</mark>");
+                open = false;
+            }
+            SNIPPET_START | SNIPPET_STOP => {}
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(c),
+        }
+    }
+    if open {
+        html.push_str("</mark>");
+    }
+    html
+}
+
+/// 全文搜索：匹配节点名、原始路径以及 visual_assets 中 markdown 的正文
+///
+/// 使用 `simple` 分词配置（不做词干处理，兼容中英文混排的文件名），
+/// 结果按 `ts_rank` 降序排列，片段中的命中词以 `<mark>` 包裹，其余文本均已转义。
+/// 命中总数单独统计，页码超出范围时结果为空但 `total` 仍是实际数量。
+pub async fn search_nodes(pool: &PgPool, params: &SearchParams) -> ApiResult<SearchPage> {
+    let keywords = params.keywords();
+    if keywords.is_empty() {
+        return Ok(SearchPage {
+            hits: Vec::new(),
+            total: 0,
+        });
+    }
+    let (limit, offset) = params.limit_offset();
+    let selectors = format!("StartSel={SNIPPET_START}, StopSel={SNIPPET_STOP}");
+
+    // 片段只为当前页生成；总数行与当前页左连接，当前页为空时仍返回一行（各列为 NULL）
+    let rows = sqlx::query(
+        r#"
+        WITH query AS (
+            SELECT websearch_to_tsquery('simple', $1) AS q
+        ),
+        hits AS (
+            SELECT
+                'directory' AS hit_kind,
+                d.path::text AS path,
+                d.has_subnodes,
+                d.has_layout,
+                d.metadata::text AS metadata,
+                d.sort_key,
+                d.visibility,
+                d.layout_path,
+                d.raw_path,
+                d.raw_filename,
+                0::bigint AS file_size,
+                '' AS file_type,
+                NULL::text AS modified_time,
+                '' AS content_hash,
+                NULL::integer AS width,
+                NULL::integer AS height,
+                NULL::bigint AS duration_ms,
+                '' AS kind,
+                ts_rank(to_tsvector('simple', d.raw_filename || ' ' || d.raw_path), query.q) AS rank,
+                d.raw_filename || ' ' || d.raw_path AS headline_text,
+                $4::text || ', HighlightAll=true' AS headline_options
+            FROM directory_nodes d, query
+            WHERE d.visibility = 'public'
+              AND to_tsvector('simple', d.raw_filename || ' ' || d.raw_path) @@ query.q
+            UNION ALL
+            SELECT
+                'asset' AS hit_kind,
+                f.file_path::text AS path,
+                false AS has_subnodes,
+                false AS has_layout,
+                '{}' AS metadata,
+                f.sort_key,
+                f.visibility,
+                NULL::text AS layout_path,
+                f.raw_path,
+                f.raw_filename,
+                f.file_size,
+                f.file_type,
+                to_char(f.modified_time AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+                    AS modified_time,
+                f.content_hash,
+                f.width,
+                f.height,
+                f.duration_ms,
+                f.kind,
+                ts_rank(
+                    to_tsvector('simple', f.raw_filename || ' ' || f.raw_path || ' ' || f.content),
+                    query.q
+                ) AS rank,
+                f.raw_filename || ' ' || f.content AS headline_text,
+                $4::text || ', MaxWords=30, MinWords=10, MaxFragments=2' AS headline_options
+            FROM file_nodes f, query
+            WHERE f.visibility = 'public'
+              AND to_tsvector('simple', f.raw_filename || ' ' || f.raw_path || ' ' || f.content)
+                @@ query.q
+        ),
+        page AS (
+            SELECT * FROM hits
+            ORDER BY rank DESC, path
+            LIMIT $2 OFFSET $3
+        )
+        SELECT totals.total, page.hit_kind, page.path, page.has_subnodes, page.has_layout,
+               page.metadata, page.sort_key, page.visibility, page.layout_path,
+               page.raw_path, page.raw_filename, page.file_size, page.file_type,
+               page.modified_time, page.content_hash, page.width, page.height,
+               page.duration_ms, page.kind, page.rank,
+               ts_headline('simple', page.headline_text, query.q, page.headline_options)
+                   AS snippet
+        FROM (SELECT count(*) AS total FROM hits) totals
+        CROSS JOIN query
+        LEFT JOIN page ON true
+        ORDER BY page.rank DESC, page.path;
+        "#,
+    )
+    .bind(keywords)
+    .bind(limit)
+    .bind(offset)
+    .bind(&selectors)
     .fetch_all(pool)
     .await?;
 
-    let assets = rows
+    let total = rows
+        .first()
+        .map(|row| row.get::<i64, _>("total"))
+        .unwrap_or(0);
+
+    let hits = rows
         .iter()
-        .map(|row| AssetNode {
-            file_path: row.get::<String, _>("file_path"),
-            raw_path: row.get::<String, _>("raw_path"),
-            raw_filename: row.get::<String, _>("raw_filename"),
+        .filter_map(|row| {
+            let kind = row.get::<Option<String>, _>("hit_kind")?;
+            let rank = row.get::<f32, _>("rank");
+            let snippet = render_snippet(&row.get::<String, _>("snippet"));
+            Some(if kind == "directory" {
+                SearchHit::Directory {
+                    node: directory_from_row(row),
+                    rank,
+                    snippet,
+                }
+            } else {
+                SearchHit::Asset {
+                    node: asset_with_path(row, "path"),
+                    rank,
+                    snippet,
+                }
+            })
         })
         .collect();
 
-    Ok(assets)
+    Ok(SearchPage { hits, total })
 }
 
-/// API 处理函数：获取一级目录
-pub async fn api_get_root_directories(pool: Extension<PgPool>) -> impl IntoResponse {
-    match get_root_directories(&pool).await {
-        Ok(directories) => Json(json!({ "directories": directories })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
+/// 组合仓库查询得到节点详情；节点不存在时返回 404
+pub async fn load_node_detail(repo: &dyn NodeRepository, path: &NodePath) -> ApiResult<NodeDetail> {
+    let node = repo
+        .node(path)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(path.to_string()))?;
+    let ancestors = repo.ancestors(path).await?;
+    let mut siblings = match path.parent() {
+        Some(parent) => repo.children(&parent).await?,
+        None => repo.root_nodes().await?,
+    };
+    // 通过链接访问的不公开节点不会出现在父节点的列表中，补上自身以便前端定位
+    if !siblings.iter().any(|sibling| sibling.path == node.path) {
+        let position = siblings.partition_point(|sibling| sibling.sort_key <= node.sort_key);
+        siblings.insert(position, node.clone());
+    }
+    let child_count = repo.children(path).await?.len();
+
+    let mut asset_counts = BTreeMap::new();
+    for asset in repo.assets(path).await? {
+        *asset_counts
+            .entry(asset.asset_kind().to_string())
+            .or_insert(0) += 1;
     }
+
+    Ok(NodeDetail {
+        node,
+        ancestors,
+        siblings,
+        child_count,
+        asset_counts,
+    })
+}
+
+/// 组合仓库查询得到嵌套子树。
+///
+/// 超过 [`TREE_MAX_NODES`] 时丢弃最深的一层（该层可能只取到一部分），
+/// 保证每个 `children` 为 `Some` 的节点其子节点列表都是完整的，前端可以放心写入缓存。
+pub async fn load_subtree(
+    repo: &dyn NodeRepository,
+    path: &NodePath,
+    depth: u32,
+) -> ApiResult<Subtree> {
+    // 多取一条用于判断是否超出上限（第一条是根节点自身）
+    let mut nodes = repo.descendants(path, depth, TREE_MAX_NODES + 2).await?;
+    let root_level = path.as_str().split('.').count();
+    let level_of = |node: &DirectoryNode| node.path.split('.').count() - root_level;
+
+    let truncated = nodes.len() > TREE_MAX_NODES + 1;
+    let mut expanded_depth = depth;
+    if truncated {
+        let deepest = nodes.last().map(level_of).unwrap_or(0) as u32;
+        nodes.retain(|node| (level_of(node) as u32) < deepest);
+        expanded_depth = deepest.saturating_sub(1);
+    }
+
+    let node_paths = nodes
+        .iter()
+        .map(|node| NodePath::parse(&node.path))
+        .collect::<ApiResult<Vec<_>>>()?;
+    let mut assets_by_parent: HashMap<String, Vec<AssetNode>> = HashMap::new();
+    for asset in repo.assets_of(&node_paths).await? {
+        // file_path 形如 "<node>.visual_assets.<file>"
+        let parent = asset
+            .file_path
+            .rsplitn(3, '.')
+            .nth(2)
+            .unwrap_or_default()
+            .to_string();
+        assets_by_parent.entry(parent).or_default().push(asset);
+    }
+
+    // 由深到浅构建：先处理的子节点暂存在 pending 中，轮到父节点时整体取出
+    let mut pending: HashMap<String, Vec<TreeNode>> = HashMap::new();
+    let mut root = None;
+    for node in nodes.into_iter().rev() {
+        let children = ((level_of(&node) as u32) < expanded_depth).then(|| {
+            let mut children = pending.remove(&node.path).unwrap_or_default();
+            children.reverse();
+            children
+        });
+        let tree_node = TreeNode {
+            assets: assets_by_parent.remove(&node.path).unwrap_or_default(),
+            children,
+            node,
+        };
+        if tree_node.node.path == path.as_str() {
+            root = Some(tree_node);
+        } else if let Some((parent, _)) = tree_node.node.path.rsplit_once('.') {
+            pending
+                .entry(parent.to_string())
+                .or_default()
+                .push(tree_node);
+        }
+    }
+
+    let tree = root.ok_or_else(|| ApiError::NotFound(path.to_string()))?;
+    Ok(Subtree {
+        tree,
+        depth: expanded_depth,
+        truncated,
+    })
+}
+
+/// API 处理函数：获取一级目录
+pub async fn api_get_root_directories(
+    Query(params): Query<ListParams>,
+    repo: Extension<SharedRepository>,
+) -> ApiResult<Json<Value>> {
+    let sort = params.sort_mode()?;
+    let mut directories = repo.root_nodes().await?;
+    sort_directories(&mut directories, sort);
+    Ok(Json(json!({ "directories": directories })))
+}
+
+/// API 处理函数：获取单个节点详情（面包屑、同级节点、计数）
+pub async fn api_get_node(
+    Path(path): Path<String>,
+    Query(params): Query<ListParams>,
+    repo: Extension<SharedRepository>,
+) -> ApiResult<Json<NodeDetail>> {
+    let sort = params.sort_mode()?;
+    let path = NodePath::parse(&path)?;
+    let mut detail = load_node_detail(repo.as_ref(), &path).await?;
+    sort_directories(&mut detail.siblings, sort);
+    Ok(Json(detail))
+}
+
+/// API 处理函数：获取以指定节点为根、展开 `depth` 层的嵌套子树
+pub async fn api_get_subtree(
+    Path(path): Path<String>,
+    Query(params): Query<TreeParams>,
+    repo: Extension<SharedRepository>,
+) -> ApiResult<Json<Subtree>> {
+    let sort = SortMode::parse(params.sort.as_deref())?;
+    let path = NodePath::parse(&path)?;
+    let mut subtree = load_subtree(repo.as_ref(), &path, params.depth()).await?;
+    sort_tree(&mut subtree.tree, sort);
+    Ok(Json(subtree))
 }
 
 /// API 处理函数：获取子目录
 pub async fn api_get_child_directories(
     Path(parent_path): Path<String>,
-    pool: Extension<PgPool>,
-) -> impl IntoResponse {
-    match get_child_directories(&pool, &parent_path).await {
-        Ok(directories) => Json(json!({ "directories": directories })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
-    }
+    Query(params): Query<ListParams>,
+    repo: Extension<SharedRepository>,
+) -> ApiResult<Json<Value>> {
+    let sort = params.sort_mode()?;
+    let parent_path = NodePath::parse(&parent_path)?;
+    let mut directories = repo.children(&parent_path).await?;
+    sort_directories(&mut directories, sort);
+    Ok(Json(json!({ "directories": directories })))
 }
 
 /// API 处理函数：获取节点资源文件
 pub async fn api_get_node_assets(
     Path(parent_path): Path<String>,
-    pool: Extension<PgPool>,
-) -> impl IntoResponse {
-    match get_node_assets(&pool, &parent_path).await {
-        Ok(assets) => Json(json!({ "assets": assets })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
+    Query(params): Query<ListParams>,
+    repo: Extension<SharedRepository>,
+) -> ApiResult<Json<Value>> {
+    let sort = params.sort_mode()?;
+    let parent_path = NodePath::parse(&parent_path)?;
+    let mut assets = repo.assets(&parent_path).await?;
+    sort_assets(&mut assets, sort);
+    Ok(Json(json!({ "assets": assets })))
+}
+
+/// API 处理函数：全文搜索节点与资源
+pub async fn api_search_nodes(
+    Query(params): Query<SearchParams>,
+    repo: Extension<SharedRepository>,
+) -> ApiResult<Json<Value>> {
+    let page = repo.search(&params).await?;
+    Ok(Json(json!({
+        "results": page.hits,
+        "total": page.total,
+        "page": params.page(),
+        "per_page": params.per_page(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(q: &str, page: Option<u32>, per_page: Option<u32>) -> SearchParams {
+        SearchParams {
+            q: q.to_string(),
+            page,
+            per_page,
+        }
+    }
+
+    #[test]
+    fn search_params_default_to_first_page() {
+        let p = params("book", None, None);
+        assert_eq!(p.page(), 1);
+        assert_eq!(p.per_page(), SEARCH_DEFAULT_PER_PAGE);
+        assert_eq!(p.limit_offset(), (i64::from(SEARCH_DEFAULT_PER_PAGE), 0));
+    }
+
+    #[test]
+    fn search_params_compute_offset_from_page() {
+        let p = params("book", Some(3), Some(10));
+        assert_eq!(p.limit_offset(), (10, 20));
+    }
+
+    #[test]
+    fn search_params_clamp_out_of_range_values() {
+        let p = params("book", Some(0), Some(10_000));
+        assert_eq!(p.page(), 1);
+        assert_eq!(p.per_page(), SEARCH_MAX_PER_PAGE);
+
+        let p = params("book", None, Some(0));
+        assert_eq!(p.per_page(), 1);
+    }
+
+    #[test]
+    fn search_params_trim_keywords() {
+        assert_eq!(params("  手工书 ", None, None).keywords(), "手工书");
+        assert!(params("   ", None, None).keywords().is_empty());
+    }
+
+    #[test]
+    fn node_metadata_tolerates_partial_or_invalid_json() {
+        let meta = NodeMetadata::from_json(r#"{"title":"反复之书","year":2023,"extra":1}"#);
+        assert_eq!(meta.title.as_deref(), Some("反复之书"));
+        assert_eq!(meta.year, Some(2023));
+        assert!(meta.tags.is_empty());
+        assert_eq!(NodeMetadata::from_json("not json"), NodeMetadata::default());
+        assert_eq!(NodeMetadata::from_json(""), NodeMetadata::default());
+    }
+
+    #[test]
+    fn tree_params_clamp_depth() {
+        let depth = |d| {
+            TreeParams {
+                depth: d,
+                sort: None,
+            }
+            .depth()
+        };
+        assert_eq!(depth(None), TREE_DEFAULT_DEPTH);
+        assert_eq!(depth(Some(0)), 1);
+        assert_eq!(depth(Some(99)), TREE_MAX_DEPTH);
+    }
+
+    #[test]
+    fn classify_asset_by_extension() {
+        assert_eq!(classify_asset("intro.MD"), "markdown");
+        assert_eq!(classify_asset("cover.jpeg"), "image");
+        assert_eq!(classify_asset("clip.webm"), "video");
+        assert_eq!(classify_asset("catalogue.pdf"), "pdf");
+        assert_eq!(classify_asset("notes.txt"), "other");
+        assert_eq!(classify_asset("README"), "other");
+    }
+
+    #[test]
+    fn render_snippet_escapes_text_and_keeps_marks() {
+        let raw = format!("a <b>{SNIPPET_START}&{SNIPPET_STOP}</b> \"c\"");
+        assert_eq!(
+            render_snippet(&raw),
+            "a &lt;b&gt;<mark>&amp;</mark>&lt;/b&gt; &quot;c&quot;"
+        );
+        // 不成对的标记被忽略或自动闭合
+        let raw = format!("{SNIPPET_STOP}x{SNIPPET_START}y");
+        assert_eq!(render_snippet(&raw), "x<mark>y</mark>");
+    }
+
+    #[test]
+    fn search_hit_serializes_with_kind_tag() {
+        let hit = SearchHit::Asset {
+            node: AssetNode {
+                file_path: "a.visual_assets.intro_md".into(),
+                raw_path: "a/visual_assets/intro.md".into(),
+                raw_filename: "intro.md".into(),
+                ..AssetNode::default()
+            },
+            rank: 0.5,
+            snippet: "<mark>intro</mark>".into(),
+        };
+        let value = serde_json::to_value(&hit).unwrap();
+        assert_eq!(value["kind"], "asset");
+        assert_eq!(value["node"]["raw_filename"], "intro.md");
+        assert_eq!(value["snippet"], "<mark>intro</mark>");
     }
 }
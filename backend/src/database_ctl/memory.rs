@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::database_ctl::directory::{
+    render_snippet, AssetNode, DirectoryNode, ImageDerivative, NodeMetadata, SearchHit, SearchPage,
+    SearchParams, SNIPPET_START, SNIPPET_STOP,
+};
+use crate::database_ctl::repository::NodeRepository;
+use crate::database_ctl::visibility::Visibility;
+use crate::error::{ApiError, ApiResult};
+use crate::node_path::NodePath;
+
+/// node.csv 的一行（`scan node` 输出）
+#[derive(Debug, Deserialize)]
+struct NodeRecord {
+    path: String,
+    has_subnodes: bool,
+    raw_path: String,
+    raw_filename: String,
+    #[serde(default)]
+    has_layout: bool,
+    #[serde(default)]
+    metadata: String,
+    #[serde(default)]
+    sort_key: String,
+    #[serde(default)]
+    visibility: Visibility,
+    #[serde(default)]
+    layout_path: Option<String>,
+}
+
+/// visual_assets.csv 的一行（`scan visual` 输出）
+#[derive(Debug, Deserialize)]
+struct AssetRecord {
+    file_path: String,
+    raw_path: String,
+    raw_filename: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    sort_key: String,
+    #[serde(default)]
+    visibility: Visibility,
+    #[serde(default)]
+    file_size: i64,
+    #[serde(default)]
+    file_type: String,
+    #[serde(default)]
+    modified_time: Option<String>,
+    #[serde(default)]
+    content_hash: String,
+    #[serde(default)]
+    width: Option<i32>,
+    #[serde(default)]
+    height: Option<i32>,
+    #[serde(default)]
+    duration_ms: Option<i64>,
+    #[serde(default)]
+    kind: String,
+}
+
+/// image_derivatives.csv 的一行（`thumbnails -o` 输出）
+#[derive(Debug, Deserialize)]
+struct DerivativeRecord {
+    file_path: String,
+    content_hash: String,
+    format: String,
+    width: i32,
+    height: i32,
+    path: String,
+}
+
+/// 以 ltree 标签序列作为键，BTreeMap 的顺序与 `ORDER BY path` 一致
+type Labels = Vec<String>;
+
+fn labels_of(path: &str) -> Labels {
+    path.split('.').map(str::to_string).collect()
+}
+
+/// 内存中的节点仓库，数据格式与 node-generate-tool 输出的 CSV 相同，
+/// 用于在没有 PostgreSQL 的情况下驱动完整的 API 路由（测试、本地演示）。
+#[derive(Debug, Default)]
+pub struct InMemoryNodeRepository {
+    nodes: BTreeMap<Labels, NodeRecord>,
+    assets: BTreeMap<Labels, AssetRecord>,
+    derivatives: Vec<DerivativeRecord>,
+}
+
+impl InMemoryNodeRepository {
+    /// 从 `node.csv` 与 `visual_assets.csv` 的文本内容构建
+    pub fn from_csv(node_csv: &str, visual_csv: &str) -> Result<Self> {
+        let mut repo = Self::default();
+
+        let mut reader = csv::Reader::from_reader(node_csv.as_bytes());
+        for record in reader.deserialize::<NodeRecord>() {
+            let record = record.context("解析 node.csv 失败")?;
+            repo.nodes.insert(labels_of(&record.path), record);
+        }
+
+        let mut reader = csv::Reader::from_reader(visual_csv.as_bytes());
+        for record in reader.deserialize::<AssetRecord>() {
+            let record = record.context("解析 visual_assets.csv 失败")?;
+            repo.assets.insert(labels_of(&record.file_path), record);
+        }
+
+        Ok(repo)
+    }
+
+    /// 追加 `image_derivatives.csv` 中的衍生图记录
+    pub fn with_derivatives_csv(mut self, derivatives_csv: &str) -> Result<Self> {
+        let mut reader = csv::Reader::from_reader(derivatives_csv.as_bytes());
+        for record in reader.deserialize::<DerivativeRecord>() {
+            self.derivatives
+                .push(record.context("解析 image_derivatives.csv 失败")?);
+        }
+        Ok(self)
+    }
+
+    /// 与 SQL 实现一致：只返回内容哈希与原图一致的衍生图，按格式、宽度排列
+    fn with_derivatives(&self, mut asset: AssetNode) -> AssetNode {
+        let mut derivatives: Vec<ImageDerivative> = self
+            .derivatives
+            .iter()
+            .filter(|record| {
+                record.file_path == asset.file_path && record.content_hash == asset.content_hash
+            })
+            .map(|record| ImageDerivative {
+                format: record.format.clone(),
+                width: record.width,
+                height: record.height,
+                path: record.path.clone(),
+            })
+            .collect();
+        derivatives.sort_by(|a, b| (&a.format, a.width).cmp(&(&b.format, b.width)));
+        asset.derivatives = derivatives;
+        asset
+    }
+
+    /// 从磁盘上的两个 CSV 文件构建
+    pub fn from_csv_files(node_csv: &Path, visual_csv: &Path) -> Result<Self> {
+        let nodes = std::fs::read_to_string(node_csv)
+            .with_context(|| format!("无法读取: {}", node_csv.display()))?;
+        let assets = std::fs::read_to_string(visual_csv)
+            .with_context(|| format!("无法读取: {}", visual_csv.display()))?;
+        Self::from_csv(&nodes, &assets)
+    }
+
+    /// 与 SQL 实现一致：节点不存在或被隐藏时返回 404，否则返回其可见性
+    fn visible_node(&self, path: &NodePath) -> ApiResult<Visibility> {
+        match self.nodes.get(&labels_of(path.as_str())) {
+            Some(record) if record.visibility != Visibility::Hidden => Ok(record.visibility),
+            _ => Err(ApiError::NotFound(path.to_string())),
+        }
+    }
+}
+
+fn to_directory(record: &NodeRecord) -> DirectoryNode {
+    DirectoryNode {
+        path: record.path.clone(),
+        has_subnodes: record.has_subnodes,
+        raw_filename: record.raw_filename.clone(),
+        has_layout: record.has_layout,
+        metadata: NodeMetadata::from_json(&record.metadata),
+        sort_key: record.sort_key.clone(),
+        visibility: record.visibility,
+        layout_path: record.layout_path.clone(),
+    }
+}
+
+fn to_asset(record: &AssetRecord) -> AssetNode {
+    AssetNode {
+        file_path: record.file_path.clone(),
+        raw_path: record.raw_path.clone(),
+        raw_filename: record.raw_filename.clone(),
+        sort_key: record.sort_key.clone(),
+        file_size: record.file_size,
+        file_type: record.file_type.clone(),
+        modified_time: record.modified_time.clone().filter(|time| !time.is_empty()),
+        content_hash: record.content_hash.clone(),
+        width: record.width,
+        height: record.height,
+        duration_ms: record.duration_ms,
+        kind: record.kind.clone(),
+        derivatives: Vec::new(),
+    }
+}
+
+/// 与 SQL 的 `ORDER BY sort_key COLLATE "C", path` 一致：
+/// BTreeMap 已按路径排好，按排序键（字节序）做一次稳定排序即可
+fn ordered<T>(mut items: Vec<T>, sort_key: impl Fn(&T) -> &str) -> Vec<T> {
+    items.sort_by(|a, b| sort_key(a).cmp(sort_key(b)));
+    items
+}
+
+/// 资源路径形如 `<node>.<资源目录>.<文件>`，与 SQL 的 `subpath(file_path, 0, nlevel(file_path) - 2)` 一致
+fn is_asset_of(labels: &[String], node: &[String]) -> bool {
+    labels.len() == node.len() + 2 && labels.starts_with(node)
+}
+
+/// 不区分大小写地统计关键词出现次数，作为简易相关度
+fn count_matches(haystack: &str, needle: &str) -> usize {
+    haystack.to_lowercase().matches(needle).count()
+}
+
+/// 用 `<mark>` 包裹第一处命中并转义其余文本，与 SQL 实现的片段形式保持一致
+fn highlight(text: &str, needle: &str) -> String {
+    let lower = text.to_lowercase();
+    let marked = match lower.find(needle) {
+        // 仅当大小写转换不改变字节长度时才能直接按下标切片
+        Some(start) if lower.len() == text.len() => {
+            let end = start + needle.len();
+            format!(
+                "{}{SNIPPET_START}{}{SNIPPET_STOP}{}",
+                &text[..start],
+                &text[start..end],
+                &text[end..]
+            )
+        }
+        _ => text.to_string(),
+    };
+    render_snippet(&marked)
+}
+
+#[async_trait]
+impl NodeRepository for InMemoryNodeRepository {
+    async fn root_nodes(&self) -> ApiResult<Vec<DirectoryNode>> {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(labels, record)| {
+                labels.len() == 1 && record.visibility.listed_in(Visibility::Public)
+            })
+            .map(|(_, record)| to_directory(record))
+            .collect();
+        Ok(ordered(nodes, |node: &DirectoryNode| &node.sort_key))
+    }
+
+    async fn children(&self, parent: &NodePath) -> ApiResult<Vec<DirectoryNode>> {
+        let context = self.visible_node(parent)?;
+        let parent = labels_of(parent.as_str());
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(labels, record)| {
+                labels.len() == parent.len() + 1
+                    && labels.starts_with(&parent)
+                    && record.visibility.listed_in(context)
+            })
+            .map(|(_, record)| to_directory(record))
+            .collect();
+        Ok(ordered(nodes, |node: &DirectoryNode| &node.sort_key))
+    }
+
+    async fn assets(&self, parent: &NodePath) -> ApiResult<Vec<AssetNode>> {
+        self.visible_node(parent)?;
+        let parent = labels_of(parent.as_str());
+        let assets = self
+            .assets
+            .iter()
+            .filter(|(labels, record)| {
+                is_asset_of(labels, &parent) && record.visibility != Visibility::Hidden
+            })
+            .map(|(_, record)| self.with_derivatives(to_asset(record)))
+            .collect();
+        Ok(ordered(assets, |asset: &AssetNode| &asset.sort_key))
+    }
+
+    async fn node(&self, path: &NodePath) -> ApiResult<Option<DirectoryNode>> {
+        Ok(self
+            .nodes
+            .get(&labels_of(path.as_str()))
+            .filter(|record| record.visibility != Visibility::Hidden)
+            .map(to_directory))
+    }
+
+    async fn ancestors(&self, path: &NodePath) -> ApiResult<Vec<DirectoryNode>> {
+        let labels = labels_of(path.as_str());
+        Ok((1..labels.len())
+            .filter_map(|depth| self.nodes.get(&labels[..depth]))
+            .filter(|record| record.visibility != Visibility::Hidden)
+            .map(to_directory)
+            .collect())
+    }
+
+    async fn descendants(
+        &self,
+        path: &NodePath,
+        depth: u32,
+        limit: usize,
+    ) -> ApiResult<Vec<DirectoryNode>> {
+        let context = self.visible_node(path)?;
+        let root = labels_of(path.as_str());
+        let mut found: Vec<&Labels> = self
+            .nodes
+            .iter()
+            .filter(|(labels, record)| {
+                labels.starts_with(&root)
+                    && labels.len() <= root.len() + depth as usize
+                    && record.visibility.listed_in(context)
+            })
+            .map(|(labels, _)| labels)
+            .collect();
+        // 与 SQL 的 ORDER BY nlevel(path), sort_key, path 一致
+        found.sort_by(|a, b| {
+            a.len()
+                .cmp(&b.len())
+                .then_with(|| self.nodes[*a].sort_key.cmp(&self.nodes[*b].sort_key))
+        });
+        Ok(found
+            .into_iter()
+            .take(limit)
+            .map(|labels| to_directory(&self.nodes[labels]))
+            .collect())
+    }
+
+    async fn assets_of(&self, parents: &[NodePath]) -> ApiResult<Vec<AssetNode>> {
+        let parents = parents
+            .iter()
+            .map(|parent| labels_of(parent.as_str()))
+            .collect::<Vec<Labels>>();
+        let assets = self
+            .assets
+            .iter()
+            .filter(|(labels, record)| {
+                record.visibility != Visibility::Hidden
+                    && parents.iter().any(|parent| is_asset_of(labels, parent))
+            })
+            .map(|(_, record)| self.with_derivatives(to_asset(record)))
+            .collect();
+        Ok(ordered(assets, |asset: &AssetNode| &asset.sort_key))
+    }
+
+    async fn search(&self, params: &SearchParams) -> ApiResult<SearchPage> {
+        let needle = params.keywords().to_lowercase();
+        if needle.is_empty() {
+            return Ok(SearchPage {
+                hits: Vec::new(),
+                total: 0,
+            });
+        }
+
+        let mut hits: Vec<(usize, String, SearchHit)> = Vec::new();
+        // 搜索只覆盖公开内容
+        for record in self.nodes.values() {
+            if record.visibility != Visibility::Public {
+                continue;
+            }
+            let matches = count_matches(&record.raw_filename, &needle)
+                + count_matches(&record.raw_path, &needle);
+            if matches > 0 {
+                hits.push((
+                    matches,
+                    record.path.clone(),
+                    SearchHit::Directory {
+                        node: to_directory(record),
+                        rank: matches as f32,
+                        snippet: highlight(&record.raw_filename, &needle),
+                    },
+                ));
+            }
+        }
+        for record in self.assets.values() {
+            if record.visibility != Visibility::Public {
+                continue;
+            }
+            let matches = count_matches(&record.raw_filename, &needle)
+                + count_matches(&record.raw_path, &needle)
+                + count_matches(&record.content, &needle);
+            if matches > 0 {
+                let snippet = if count_matches(&record.raw_filename, &needle) > 0 {
+                    highlight(&record.raw_filename, &needle)
+                } else {
+                    highlight(&record.content, &needle)
+                };
+                hits.push((
+                    matches,
+                    record.file_path.clone(),
+                    SearchHit::Asset {
+                        node: to_asset(record),
+                        rank: matches as f32,
+                        snippet,
+                    },
+                ));
+            }
+        }
+
+        // 与 SQL 实现一致：相关度降序，路径升序
+        hits.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        let total = hits.len() as i64;
+        let (limit, offset) = params.limit_offset();
+        let hits = hits
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(_, _, hit)| hit)
+            .collect();
+
+        Ok(SearchPage { hits, total })
+    }
+}
@@ -1,2 +1,6 @@
 pub mod directory;
+pub mod memory;
+pub mod ordering;
+pub mod repository;
 pub mod request_test;
+pub mod visibility;
@@ -0,0 +1,189 @@
+use std::cmp::Reverse;
+
+use serde::Deserialize;
+
+use crate::database_ctl::directory::{AssetNode, DirectoryNode, TreeNode};
+use crate::error::{ApiError, ApiResult};
+
+/// 列表排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// 导入时写入的排序键（默认）：显式序号优先，其余按原始名称自然排序
+    #[default]
+    Order,
+    /// 按展示名称自然排序
+    Name,
+    /// 按 front matter 中的年份由新到旧，无年份的排在最后；
+    /// 资源文件没有年份，按名称排序
+    Date,
+}
+
+impl SortMode {
+    /// 解析 `?sort=` 的值，缺省时为 [`SortMode::Order`]
+    pub fn parse(raw: Option<&str>) -> ApiResult<Self> {
+        match raw.map(str::trim) {
+            None | Some("") | Some("order") => Ok(SortMode::Order),
+            Some("name") => Ok(SortMode::Name),
+            Some("date") => Ok(SortMode::Date),
+            Some(other) => Err(ApiError::InvalidQuery(format!(
+                "sort 只能是 order / name / date，收到: {other}"
+            ))),
+        }
+    }
+}
+
+/// 列表接口的查询参数：`?sort=order|name|date`
+#[derive(Debug, Default, Deserialize)]
+pub struct ListParams {
+    pub sort: Option<String>,
+}
+
+impl ListParams {
+    pub fn sort_mode(&self) -> ApiResult<SortMode> {
+        SortMode::parse(self.sort.as_deref())
+    }
+}
+
+/// 按指定方式重排目录节点；`Order` 保持仓库返回的顺序
+pub fn sort_directories(nodes: &mut [DirectoryNode], mode: SortMode) {
+    if mode != SortMode::Order {
+        nodes.sort_by_cached_key(|node| directory_key(node, mode));
+    }
+}
+
+/// 按指定方式重排资源文件；`Order` 保持仓库返回的顺序
+pub fn sort_assets(assets: &mut [AssetNode], mode: SortMode) {
+    match mode {
+        SortMode::Order => {}
+        SortMode::Name | SortMode::Date => {
+            assets.sort_by_cached_key(|asset| natural_key(&asset.raw_filename))
+        }
+    }
+}
+
+/// 递归重排子树中每一层的子节点与资源
+pub fn sort_tree(tree: &mut TreeNode, mode: SortMode) {
+    if mode == SortMode::Order {
+        return;
+    }
+    sort_assets(&mut tree.assets, mode);
+    if let Some(children) = tree.children.as_mut() {
+        children.sort_by_cached_key(|child| directory_key(&child.node, mode));
+        for child in children {
+            sort_tree(child, mode);
+        }
+    }
+}
+
+/// `Name`：只比较名称；`Date`：有年份的在前、年份倒序，再比较名称
+fn directory_key(node: &DirectoryNode, mode: SortMode) -> (bool, Reverse<Option<i32>>, String) {
+    let year = match mode {
+        SortMode::Date => node.metadata.year,
+        _ => None,
+    };
+    (
+        mode == SortMode::Date && year.is_none(),
+        Reverse(year),
+        natural_key(display_name(node)),
+    )
+}
+
+fn display_name(node: &DirectoryNode) -> &str {
+    node.metadata
+        .title
+        .as_deref()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or(&node.raw_filename)
+}
+
+/// 自然排序键（与 node-generate-tool 的 `natural_key` 相同，两边的测试共用
+/// `natural_key_cases.txt` 中的测试向量）：
+/// 字母转小写，连续数字去掉前导零后以“位数(3 位) + 数字”表示
+pub fn natural_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len() + 8);
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut digits = String::from(c);
+            while let Some(&next) = chars.peek() {
+                if !next.is_ascii_digit() {
+                    break;
+                }
+                digits.push(next);
+                chars.next();
+            }
+            let trimmed = digits.trim_start_matches('0');
+            let trimmed = if trimmed.is_empty() { "0" } else { trimmed };
+            key.push_str(&format!("{:03}{}", trimmed.len(), trimmed));
+        } else {
+            key.extend(c.to_lowercase());
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_ctl::directory::NodeMetadata;
+
+    fn node(name: &str, year: Option<i32>) -> DirectoryNode {
+        DirectoryNode {
+            path: name.to_string(),
+            has_subnodes: false,
+            raw_filename: name.to_string(),
+            has_layout: false,
+            metadata: NodeMetadata {
+                year,
+                ..Default::default()
+            },
+            sort_key: String::new(),
+            visibility: Default::default(),
+            layout_path: None,
+        }
+    }
+
+    fn names(nodes: &[DirectoryNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.raw_filename.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_sort_mode() {
+        assert_eq!(SortMode::parse(None).unwrap(), SortMode::Order);
+        assert_eq!(SortMode::parse(Some("name")).unwrap(), SortMode::Name);
+        assert_eq!(SortMode::parse(Some("date")).unwrap(), SortMode::Date);
+        let err = SortMode::parse(Some("size")).unwrap_err();
+        assert_eq!(err.code(), "invalid_query");
+    }
+
+    #[test]
+    fn name_sort_is_numeric_aware() {
+        let mut nodes = vec![node("10_c", None), node("2_b", None), node("1_a", None)];
+        sort_directories(&mut nodes, SortMode::Name);
+        assert_eq!(names(&nodes), ["1_a", "2_b", "10_c"]);
+    }
+
+    #[test]
+    fn date_sort_puts_newest_first_and_undated_last() {
+        let mut nodes = vec![
+            node("a", None),
+            node("b", Some(2019)),
+            node("c", Some(2023)),
+        ];
+        sort_directories(&mut nodes, SortMode::Date);
+        assert_eq!(names(&nodes), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn natural_key_matches_shared_vectors() {
+        let cases = include_str!("../../../utils/node-generate-tool/src/natural_key_cases.txt");
+        for line in cases.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (input, expected) = line.split_once('\t').expect("测试向量应以制表符分隔");
+            assert_eq!(natural_key(input), expected, "输入: {input}");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+
+use crate::database_ctl::directory::{
+    get_ancestors, get_assets_of, get_child_directories, get_descendants, get_directory,
+    get_node_assets, get_root_directories, search_nodes, AssetNode, DirectoryNode, SearchPage,
+    SearchParams,
+};
+use crate::error::ApiResult;
+use crate::node_path::NodePath;
+
+/// 节点数据访问接口：API 处理函数只依赖该 trait，
+/// 生产环境使用 [`PgNodeRepository`]，测试使用内存实现
+/// [`InMemoryNodeRepository`](crate::database_ctl::memory::InMemoryNodeRepository)。
+#[async_trait]
+pub trait NodeRepository: Send + Sync {
+    /// 一级节点（路径深度为 1）
+    async fn root_nodes(&self) -> ApiResult<Vec<DirectoryNode>>;
+
+    /// 指定节点的直接子节点；节点不存在时返回 `ApiError::NotFound`
+    async fn children(&self, parent: &NodePath) -> ApiResult<Vec<DirectoryNode>>;
+
+    /// 指定节点 `visual_assets` 下的直接文件；节点不存在时返回 `ApiError::NotFound`
+    async fn assets(&self, parent: &NodePath) -> ApiResult<Vec<AssetNode>>;
+
+    /// 按路径查找单个节点
+    async fn node(&self, path: &NodePath) -> ApiResult<Option<DirectoryNode>>;
+
+    /// 节点的全部祖先（不含自身），按层级由浅到深排列
+    async fn ancestors(&self, path: &NodePath) -> ApiResult<Vec<DirectoryNode>>;
+
+    /// 节点自身及 `depth` 层以内的后代，按层级、路径排序，最多 `limit` 条；
+    /// 节点不存在时返回 `ApiError::NotFound`
+    async fn descendants(
+        &self,
+        path: &NodePath,
+        depth: u32,
+        limit: usize,
+    ) -> ApiResult<Vec<DirectoryNode>>;
+
+    /// 多个节点 `visual_assets` 下的直接文件，按路径排序
+    async fn assets_of(&self, parents: &[NodePath]) -> ApiResult<Vec<AssetNode>>;
+
+    /// 按关键词搜索节点与资源
+    async fn search(&self, params: &SearchParams) -> ApiResult<SearchPage>;
+}
+
+/// 通过 `Extension` 注入到路由中的仓库实例
+pub type SharedRepository = Arc<dyn NodeRepository>;
+
+/// 基于 PostgreSQL（ltree）的实现
+#[derive(Clone)]
+pub struct PgNodeRepository {
+    pool: PgPool,
+}
+
+impl PgNodeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl NodeRepository for PgNodeRepository {
+    async fn root_nodes(&self) -> ApiResult<Vec<DirectoryNode>> {
+        get_root_directories(&self.pool).await
+    }
+
+    async fn children(&self, parent: &NodePath) -> ApiResult<Vec<DirectoryNode>> {
+        get_child_directories(&self.pool, parent).await
+    }
+
+    async fn assets(&self, parent: &NodePath) -> ApiResult<Vec<AssetNode>> {
+        get_node_assets(&self.pool, parent).await
+    }
+
+    async fn node(&self, path: &NodePath) -> ApiResult<Option<DirectoryNode>> {
+        get_directory(&self.pool, path).await
+    }
+
+    async fn ancestors(&self, path: &NodePath) -> ApiResult<Vec<DirectoryNode>> {
+        get_ancestors(&self.pool, path).await
+    }
+
+    async fn descendants(
+        &self,
+        path: &NodePath,
+        depth: u32,
+        limit: usize,
+    ) -> ApiResult<Vec<DirectoryNode>> {
+        get_descendants(&self.pool, path, depth, limit).await
+    }
+
+    async fn assets_of(&self, parents: &[NodePath]) -> ApiResult<Vec<AssetNode>> {
+        get_assets_of(&self.pool, parents).await
+    }
+
+    async fn search(&self, params: &SearchParams) -> ApiResult<SearchPage> {
+        search_nodes(&self.pool, params).await
+    }
+}
@@ -1,13 +1,11 @@
-use anyhow::Result;
-use axum::{
-    extract::Extension,
-    response::{IntoResponse, Json},
-};
-use serde_json::json;
+use axum::{extract::Extension, response::Json};
+use serde_json::{json, Value};
 use sqlx::postgres::PgPool;
 use sqlx::Row;
 
-pub async fn get_all_table_names(pool: &PgPool) -> Result<Vec<String>> {
+use crate::error::ApiResult;
+
+pub async fn get_all_table_names(pool: &PgPool) -> ApiResult<Vec<String>> {
     // 使用系统表查询（推荐方式）
     let rows = sqlx::query(
         r#"
@@ -35,9 +33,7 @@ pub async fn get_all_table_names(pool: &PgPool) -> Result<Vec<String>> {
 }
 
 // 使用示例（集成到 Axum 路由中）
-pub async fn list_tables(pool: Extension<PgPool>) -> impl IntoResponse {
-    match get_all_table_names(&pool).await {
-        Ok(tables) => Json(json!({ "tables": tables })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
-    }
+pub async fn list_tables(pool: Extension<PgPool>) -> ApiResult<Json<Value>> {
+    let tables = get_all_table_names(&pool).await?;
+    Ok(Json(json!({ "tables": tables })))
 }
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// 节点可见性，由 node-generate-tool 从 front matter 或标记文件中读取，
+/// 并向后代传递（取自身与祖先中最严格的一项）。顺序即严格程度。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// 正常出现在列表、子树与搜索中
+    #[default]
+    Public,
+    /// 不出现在公开列表与搜索中，知道链接即可访问
+    Unlisted,
+    /// 任何接口都不返回，按路径访问时与不存在一样返回 404
+    Hidden,
+}
+
+impl Visibility {
+    /// 数据库中存放的文本形式
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Hidden => "hidden",
+        }
+    }
+
+    /// 解析数据库中的文本；无法识别的值按隐藏处理
+    pub fn parse(text: &str) -> Self {
+        match text {
+            "public" => Visibility::Public,
+            "unlisted" => Visibility::Unlisted,
+            _ => Visibility::Hidden,
+        }
+    }
+
+    /// 在可见性为 `context` 的节点下列出时是否可见：
+    /// 公开节点总是列出；仅凭链接访问的节点只在同样仅凭链接访问的分支内列出；
+    /// 隐藏节点从不列出。与 SQL 中的 `visibility IN ('public', $context)` 一致。
+    pub fn listed_in(self, context: Visibility) -> bool {
+        self == Visibility::Public || (self == context && context != Visibility::Hidden)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_rules_follow_context() {
+        use Visibility::*;
+        assert!(Public.listed_in(Public));
+        assert!(Public.listed_in(Unlisted));
+        assert!(!Unlisted.listed_in(Public));
+        assert!(Unlisted.listed_in(Unlisted));
+        assert!(!Hidden.listed_in(Public));
+        assert!(!Hidden.listed_in(Unlisted));
+        assert!(!Hidden.listed_in(Hidden));
+    }
+
+    #[test]
+    fn parses_database_text_and_fails_closed() {
+        for visibility in [Visibility::Public, Visibility::Unlisted, Visibility::Hidden] {
+            assert_eq!(Visibility::parse(visibility.as_str()), visibility);
+        }
+        assert_eq!(Visibility::parse("draft"), Visibility::Hidden);
+    }
+}
@@ -0,0 +1,130 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+
+/// 后端统一错误类型，序列化为 `{"error": {"code": ..., "message": ...}}`
+#[derive(Debug)]
+pub enum ApiError {
+    /// 请求的节点不存在（404）；路径未知时为空字符串
+    NotFound(String),
+    /// 路径格式不合法（400）
+    InvalidPath(String),
+    /// 查询参数不合法（400）
+    InvalidQuery(String),
+    /// 数据库连接不可用（503）
+    DbUnavailable,
+    /// 其它未预期的错误（500），原始信息只写日志，不返回给前端
+    Internal(String),
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    /// 稳定的错误码，前端依此区分错误类型
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::InvalidPath(_) => "invalid_path",
+            ApiError::InvalidQuery(_) => "invalid_query",
+            ApiError::DbUnavailable => "db_unavailable",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::InvalidPath(_) | ApiError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            ApiError::DbUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// 返回给前端的可读信息
+    pub fn message(&self) -> String {
+        match self {
+            ApiError::NotFound(path) if path.is_empty() => "节点不存在".to_string(),
+            ApiError::NotFound(path) => format!("节点不存在: {path}"),
+            ApiError::InvalidPath(reason) => format!("路径无效: {reason}"),
+            ApiError::InvalidQuery(reason) => format!("查询参数无效: {reason}"),
+            ApiError::DbUnavailable => "数据库暂时不可用".to_string(),
+            ApiError::Internal(_) => "服务器内部错误".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Internal(detail) => write!(f, "{}: {detail}", self.code()),
+            _ => write!(f, "{}: {}", self.code(), self.message()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        match &err {
+            // 查询处并不知道请求的路径，返回通用信息
+            sqlx::Error::RowNotFound => ApiError::NotFound(String::new()),
+            sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_) => {
+                eprintln!("数据库连接失败: {err}");
+                ApiError::DbUnavailable
+            }
+            // 22P02 / 42601：ltree / lquery 解析失败，说明传入的路径不合法；
+            // 数据库原始错误只写日志，不返回给前端
+            sqlx::Error::Database(db_err)
+                if matches!(db_err.code().as_deref(), Some("22P02") | Some("42601")) =>
+            {
+                eprintln!("路径解析失败: {}", db_err.message());
+                ApiError::InvalidPath("无法解析节点路径".to_string())
+            }
+            _ => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let ApiError::Internal(detail) = &self {
+            eprintln!("请求处理失败: {detail}");
+        }
+        let body = json!({
+            "error": {
+                "code": self.code(),
+                "message": self.message(),
+            }
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_not_found_uses_generic_message() {
+        let err = ApiError::from(sqlx::Error::RowNotFound);
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert_eq!(err.message(), "节点不存在");
+        assert_eq!(
+            ApiError::NotFound("a.b".into()).message(),
+            "节点不存在: a.b"
+        );
+    }
+
+    #[test]
+    fn internal_details_stay_out_of_message() {
+        let err = ApiError::from(sqlx::Error::Protocol("secret detail".into()));
+        assert_eq!(err.code(), "internal");
+        assert!(!err.message().contains("secret"));
+    }
+}
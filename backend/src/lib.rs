@@ -1,2 +1,6 @@
+pub mod app;
+pub mod config;
 pub mod database_ctl;
+pub mod error;
+pub mod node_path;
 pub mod return_code;
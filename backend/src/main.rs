@@ -1,52 +1,49 @@
-use crate::database_ctl::directory::{
-    api_get_child_directories, api_get_node_assets, api_get_root_directories,
-};
-use crate::database_ctl::request_test::list_tables;
-use crate::return_code::print_code;
+use std::sync::Arc;
+
 use axum::{routing::get, Extension, Router};
-use http::header::{ACCEPT, AUTHORIZATION};
-use http::Method;
+use backend::app::build_router;
+use backend::config::Config;
+use backend::database_ctl::repository::PgNodeRepository;
+use backend::database_ctl::request_test::list_tables;
 use sqlx::postgres::PgPoolOptions;
-use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
-
-mod database_ctl;
-mod return_code;
 
 #[tokio::main]
 async fn main() {
+    // 读取配置（环境变量 / TOML），不合法时直接退出
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("配置错误: {e:#}");
+            std::process::exit(1);
+        }
+    };
+
     // 初始化数据库连接池
-    let pool = PgPoolOptions::new()
-        .max_connections(5) // 设置最大连接数
-        //这里注意直接连接的数据库地址
-        .connect("postgresql://huzz:liurui301@tp_database.:5432/tp_db")
+    let pool = match PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout())
+        .idle_timeout(config.idle_timeout())
+        .connect(&config.database_url)
         .await
-        .expect("Failed to create pool");
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("无法连接数据库: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    // 创建 Axum 路由
-    let app = Router::new()
-        //用于测试前后端沟通
-        .route("/print", get(print_code))
-        //用于测试后端和数据库沟通
+    // 配置已校验，这里不会失败
+    let addr = config.socket_addr().expect("invalid bind address");
+
+    // 用于测试后端和数据库沟通
+    let debug_routes = Router::new()
         .route("/tables", get(list_tables))
-        //获取一级目录
-        .route("/nodes/root", get(api_get_root_directories))
-        //获取子目录（路径需要 URL 编码）
-        .route("/nodes/children/{path}", get(api_get_child_directories))
-        //获取节点对应的资源文件
-        .route("/nodes/assets/{path}", get(api_get_node_assets))
-        //加入数据库连接池
-        .layer(Extension(pool))
-        // 设置 CORS
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-                .allow_headers([AUTHORIZATION, ACCEPT]), // .allow_credentials(true)
-        );
-
-    // 设置服务器地址和端口
-    let addr = SocketAddr::from(([0, 0, 0, 0], 80));
+        .layer(Extension(pool.clone()));
+
+    // 创建 Axum 路由
+    let app = build_router(Arc::new(PgNodeRepository::new(pool)), config, debug_routes);
+
     println!("Server running at http://{}", addr);
 
     // 启动服务器
@@ -0,0 +1,137 @@
+use std::fmt;
+
+use crate::error::ApiError;
+
+/// ltree 单个标签的最大长度（PostgreSQL 限制）
+pub const MAX_LABEL_LEN: usize = 255;
+
+/// 经过校验的 ltree 节点路径，例如 `1_OnceAndOnceAgain.handmadeBook`
+///
+/// 标签字符集与 node-generate-tool 的标签编码（`ltree_label`）一致：只允许
+/// ASCII 字母、数字和下划线，标签之间以 `.` 分隔。任何 lquery / ltxtquery
+/// 元字符（`*`、`|`、`!`、`@`、`{}` 等）都会被拒绝，因此可以安全地绑定为
+/// `$1::ltree` 参数。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodePath(String);
+
+impl NodePath {
+    /// 解析并校验 URL 中传入的路径，不合法时返回 400
+    pub fn parse(raw: &str) -> Result<Self, ApiError> {
+        if raw.is_empty() {
+            return Err(ApiError::InvalidPath("路径为空".to_string()));
+        }
+
+        for (idx, label) in raw.split('.').enumerate() {
+            validate_label(label).map_err(|reason| {
+                ApiError::InvalidPath(format!("第 {} 段标签{reason}", idx + 1))
+            })?;
+        }
+
+        Ok(Self(raw.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 父路径；一级节点返回 `None`
+    pub fn parent(&self) -> Option<Self> {
+        self.0
+            .rsplit_once('.')
+            .map(|(parent, _)| Self(parent.to_string()))
+    }
+
+    /// 在末尾追加一个子标签，例如 `a.b` + `visual_assets`
+    pub fn child(&self, label: &str) -> Result<Self, ApiError> {
+        validate_label(label).map_err(|reason| ApiError::InvalidPath(format!("子标签{reason}")))?;
+        Ok(Self(format!("{}.{}", self.0, label)))
+    }
+}
+
+fn validate_label(label: &str) -> Result<(), &'static str> {
+    if label.is_empty() {
+        return Err("为空");
+    }
+    if label.len() > MAX_LABEL_LEN {
+        return Err("过长");
+    }
+    if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err("包含非法字符（仅允许字母、数字和下划线）");
+    }
+    Ok(())
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for NodePath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_sanitized_paths() {
+        for raw in ["a", "1_OnceAndOnceAgain", "a.b.c", "__.x_1.Y2"] {
+            let path = NodePath::parse(raw).expect(raw);
+            assert_eq!(path.as_str(), raw);
+        }
+    }
+
+    #[test]
+    fn rejects_lquery_metacharacters() {
+        for raw in [
+            "a|b", "a.*", "*", "a.*{1}", "!a", "a@", "a%", "a.b{1,2}", "a&b", "a.!b",
+        ] {
+            let err = NodePath::parse(raw).expect_err(raw);
+            assert_eq!(err.code(), "invalid_path", "{raw}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_segments() {
+        for raw in [
+            "", ".", "a.", ".a", "a..b", " a", "a b", "a-b", "a/b", "a\0b",
+        ] {
+            assert!(NodePath::parse(raw).is_err(), "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_sql_and_unicode_payloads() {
+        for raw in ["a'; DROP TABLE directory_nodes; --", "作品", "a.é", "a\nb"] {
+            assert!(NodePath::parse(raw).is_err(), "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_overlong_labels() {
+        let long = "a".repeat(MAX_LABEL_LEN + 1);
+        assert!(NodePath::parse(&long).is_err());
+        assert!(NodePath::parse(&"a".repeat(MAX_LABEL_LEN)).is_ok());
+    }
+
+    #[test]
+    fn child_appends_validated_label() {
+        let path = NodePath::parse("a.b").unwrap();
+        assert_eq!(
+            path.child("visual_assets").unwrap().as_str(),
+            "a.b.visual_assets"
+        );
+        assert!(path.child("x|y").is_err());
+    }
+
+    #[test]
+    fn parent_drops_last_label() {
+        let path = NodePath::parse("a.b.c").unwrap();
+        assert_eq!(path.parent().unwrap().as_str(), "a.b");
+        assert!(NodePath::parse("a").unwrap().parent().is_none());
+    }
+}
@@ -0,0 +1,487 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::Router;
+use backend::app::build_router;
+use backend::config::Config;
+use backend::database_ctl::memory::InMemoryNodeRepository;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let repo = InMemoryNodeRepository::from_csv_files(
+        &fixtures.join("node.csv"),
+        &fixtures.join("visual_assets.csv"),
+    )
+    .and_then(|repo| repo.with_derivatives_csv(include_str!("fixtures/image_derivatives.csv")))
+    .expect("fixtures should parse");
+    build_router(Arc::new(repo), Config::default(), Router::new())
+}
+
+async fn get(uri: &str) -> (StatusCode, Value) {
+    get_from(app(), uri).await
+}
+
+async fn get_from(app: Router, uri: &str) -> (StatusCode, Value) {
+    let response = app
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, body)
+}
+
+fn paths(list: &Value, key: &str) -> Vec<String> {
+    list.as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item[key].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn root_lists_first_level_nodes_in_sort_key_order() {
+    let (status, body) = get("/nodes/root").await;
+    assert_eq!(status, StatusCode::OK);
+    // 2_Writing 有显式 order；其余按自然排序，10_Archive 排在 1_ 之后
+    assert_eq!(
+        paths(&body["directories"], "path"),
+        ["2_Writing", "1_OnceAndOnceAgain", "10_Archive"]
+    );
+}
+
+#[tokio::test]
+async fn sort_param_switches_listing_order() {
+    let (_, body) = get("/nodes/root?sort=name").await;
+    assert_eq!(
+        paths(&body["directories"], "path"),
+        ["1_OnceAndOnceAgain", "2_Writing", "10_Archive"]
+    );
+
+    let (_, body) = get("/nodes/children/1_OnceAndOnceAgain?sort=date").await;
+    assert_eq!(
+        paths(&body["directories"], "path"),
+        [
+            "1_OnceAndOnceAgain.painting",
+            "1_OnceAndOnceAgain.handmadeBook"
+        ]
+    );
+
+    let (_, body) = get("/nodes/tree/1_OnceAndOnceAgain?sort=date").await;
+    assert_eq!(
+        paths(&body["tree"]["children"], "path"),
+        [
+            "1_OnceAndOnceAgain.painting",
+            "1_OnceAndOnceAgain.handmadeBook"
+        ]
+    );
+
+    let (status, body) = get("/nodes/assets/1_OnceAndOnceAgain.handmadeBook?sort=size").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_query");
+}
+
+#[tokio::test]
+async fn children_returns_only_direct_descendants() {
+    let (status, body) = get("/nodes/children/1_OnceAndOnceAgain").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        paths(&body["directories"], "path"),
+        [
+            "1_OnceAndOnceAgain.handmadeBook",
+            "1_OnceAndOnceAgain.painting"
+        ]
+    );
+    assert_eq!(body["directories"][0]["has_subnodes"], true);
+}
+
+#[tokio::test]
+async fn children_of_leaf_is_empty() {
+    let (status, body) = get("/nodes/children/2_Writing").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["directories"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn assets_lists_visual_assets_of_node_only() {
+    let (status, body) = get("/nodes/assets/1_OnceAndOnceAgain.handmadeBook").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        paths(&body["assets"], "raw_filename"),
+        ["cover.jpg", "intro.md"]
+    );
+}
+
+#[tokio::test]
+async fn assets_follow_configured_folders_and_kinds() {
+    let nodes = "path,has_subnodes,raw_path,raw_filename\nwork,false,work,work\n";
+    let assets = "file_path,raw_path,raw_filename,sort_key,kind\n\
+        work.media.notes_txt,work/media/notes.txt,notes.txt,1,markdown\n\
+        work.media.scan_tiff,work/media/scan.tiff,scan.tiff,2,image\n\
+        work.media.sub.deep_png,work/media/sub/deep.png,deep.png,3,image\n";
+    let repo = InMemoryNodeRepository::from_csv(nodes, assets).unwrap();
+    let app = build_router(Arc::new(repo), Config::default(), Router::new());
+
+    let (_, body) = get_from(app.clone(), "/nodes/assets/work").await;
+    assert_eq!(
+        paths(&body["assets"], "raw_filename"),
+        ["notes.txt", "scan.tiff"]
+    );
+    assert_eq!(body["assets"][0]["kind"], "markdown");
+
+    let (_, body) = get_from(app, "/nodes/detail/work").await;
+    assert_eq!(body["asset_counts"]["markdown"], 1);
+    assert_eq!(body["asset_counts"]["image"], 1);
+}
+
+#[tokio::test]
+async fn assets_expose_file_metadata() {
+    let (_, body) = get("/nodes/assets/1_OnceAndOnceAgain.handmadeBook").await;
+    let cover = &body["assets"][0];
+    assert_eq!(cover["file_size"], 183402);
+    assert_eq!(cover["file_type"], "image/jpeg");
+    assert_eq!(cover["modified_time"], "2024-02-29T12:34:56Z");
+    assert_eq!(cover["width"], 1600);
+    assert_eq!(cover["height"], 1200);
+    assert_eq!(cover["content_hash"].as_str().unwrap().len(), 64);
+    assert_eq!(cover["kind"], "image");
+
+    let intro = &body["assets"][1];
+    assert_eq!(intro["file_type"], "text/markdown");
+    assert!(intro["width"].is_null() && intro["height"].is_null());
+}
+
+#[tokio::test]
+async fn assets_list_derivatives_matching_current_content() {
+    let (_, body) = get("/nodes/assets/1_OnceAndOnceAgain.handmadeBook").await;
+    let derivatives = &body["assets"][0]["derivatives"];
+    let sizes: Vec<(String, i64)> = derivatives
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            (
+                d["format"].as_str().unwrap().to_string(),
+                d["width"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        sizes,
+        [
+            ("avif".to_string(), 320),
+            ("webp".to_string(), 320),
+            ("webp".to_string(), 640)
+        ]
+    );
+    assert_eq!(derivatives[0]["height"], 240);
+    assert_eq!(
+        derivatives[0]["path"],
+        "1_OnceAndOnceAgain/handmadeBook/visual_assets/cover.jpg/9a8b7c6d-320.avif"
+    );
+    assert!(body["assets"][1]["derivatives"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    // 原图已替换（哈希不一致）的旧衍生图不返回
+    let (_, body) = get("/nodes/assets/1_OnceAndOnceAgain.handmadeBook.Book").await;
+    assert!(body["assets"][0]["derivatives"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn video_assets_expose_duration_and_poster() {
+    let (_, body) = get("/nodes/assets/1_OnceAndOnceAgain.handmadeBook.Book").await;
+    let video = &body["assets"][1];
+    assert_eq!(video["raw_filename"], "turning.mp4");
+    assert_eq!(video["file_type"], "video/mp4");
+    assert_eq!(video["duration_ms"], 83500);
+    assert_eq!(
+        (&video["width"], &video["height"]),
+        (&1080.into(), &1920.into())
+    );
+    assert_eq!(
+        video["derivatives"][0]["path"],
+        "1_OnceAndOnceAgain/handmadeBook/Book/visual_assets/turning.mp4/4b3a2918-640.webp"
+    );
+    assert!(body["assets"][0]["duration_ms"].is_null());
+}
+
+#[tokio::test]
+async fn node_detail_returns_breadcrumbs_siblings_and_counts() {
+    let (status, body) = get("/nodes/detail/1_OnceAndOnceAgain.handmadeBook").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["node"]["raw_filename"], "handmadeBook");
+    assert_eq!(body["node"]["has_layout"], true);
+    assert_eq!(
+        body["node"]["layout_path"],
+        "1_OnceAndOnceAgain/handmadeBook/layout.md"
+    );
+    assert!(body["ancestors"][0]["layout_path"].is_null());
+    assert_eq!(body["node"]["metadata"]["title"], "Handmade Book");
+    assert_eq!(body["node"]["metadata"]["year"], 2023);
+    assert_eq!(body["node"]["metadata"]["tags"][0], "book");
+    assert_eq!(paths(&body["ancestors"], "path"), ["1_OnceAndOnceAgain"]);
+    assert_eq!(
+        paths(&body["siblings"], "path"),
+        [
+            "1_OnceAndOnceAgain.handmadeBook",
+            "1_OnceAndOnceAgain.painting"
+        ]
+    );
+    assert_eq!(body["child_count"], 1);
+    assert_eq!(body["asset_counts"]["image"], 1);
+    assert_eq!(body["asset_counts"]["markdown"], 1);
+}
+
+#[tokio::test]
+async fn node_detail_of_deep_and_top_level_nodes() {
+    let (_, body) = get("/nodes/detail/1_OnceAndOnceAgain.handmadeBook.Book").await;
+    assert_eq!(
+        paths(&body["ancestors"], "raw_filename"),
+        ["1_OnceAndOnceAgain", "handmadeBook"]
+    );
+    assert_eq!(body["child_count"], 0);
+    assert!(body["node"]["metadata"]["title"].is_null());
+
+    let (_, body) = get("/nodes/detail/2_Writing").await;
+    assert!(body["ancestors"].as_array().unwrap().is_empty());
+    assert_eq!(
+        paths(&body["siblings"], "path"),
+        ["2_Writing", "1_OnceAndOnceAgain", "10_Archive"]
+    );
+    assert!(body["asset_counts"].as_object().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn node_detail_of_nodes_named_like_static_routes() {
+    let nodes = "path,has_subnodes,raw_path,raw_filename\n\
+        root,false,root,root\n\
+        search,false,search,search\n";
+    let repo =
+        InMemoryNodeRepository::from_csv(nodes, "file_path,raw_path,raw_filename\n").unwrap();
+    let app = build_router(Arc::new(repo), Config::default(), Router::new());
+
+    for name in ["root", "search"] {
+        let (status, body) = get_from(app.clone(), &format!("/nodes/detail/{name}")).await;
+        assert_eq!(status, StatusCode::OK, "{name}");
+        assert_eq!(body["node"]["path"], name);
+        assert_eq!(paths(&body["siblings"], "path"), ["root", "search"]);
+    }
+}
+
+#[tokio::test]
+async fn tree_nests_children_and_assets_up_to_depth() {
+    let (status, body) = get("/nodes/tree/1_OnceAndOnceAgain?depth=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["truncated"], false);
+    let tree = &body["tree"];
+    assert_eq!(tree["path"], "1_OnceAndOnceAgain");
+    assert_eq!(
+        paths(&tree["children"], "path"),
+        [
+            "1_OnceAndOnceAgain.handmadeBook",
+            "1_OnceAndOnceAgain.painting"
+        ]
+    );
+    let book = &tree["children"][0];
+    assert_eq!(
+        paths(&book["assets"], "raw_filename"),
+        ["cover.jpg", "intro.md"]
+    );
+    // 超出展开深度的节点不带 children，表示未加载
+    assert!(book["children"].is_null());
+
+    let (_, body) = get("/nodes/tree/1_OnceAndOnceAgain?depth=2").await;
+    let book = &body["tree"]["children"][0];
+    assert_eq!(
+        paths(&book["children"], "path"),
+        ["1_OnceAndOnceAgain.handmadeBook.Book"]
+    );
+    assert_eq!(
+        paths(&book["children"][0]["assets"], "raw_filename"),
+        ["page.png", "turning.mp4"]
+    );
+    assert!(body["tree"]["children"][1]["children"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn tree_drops_partial_level_when_over_size_cap() {
+    let mut nodes = String::from("path,has_subnodes,raw_path,raw_filename\nbig,true,big,big\n");
+    for i in 0..600 {
+        nodes.push_str(&format!("big.n{i:03},false,big/n{i:03},n{i:03}\n"));
+    }
+    let repo = InMemoryNodeRepository::from_csv(&nodes, "file_path,raw_path,raw_filename\n")
+        .expect("generated csv should parse");
+    let app = build_router(Arc::new(repo), Config::default(), Router::new());
+
+    let (status, body) = get_from(app, "/nodes/tree/big?depth=3").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["truncated"], true);
+    assert_eq!(body["depth"], 0);
+    assert!(body["tree"]["children"].is_null());
+}
+
+#[tokio::test]
+async fn unknown_node_is_404_with_error_envelope() {
+    for uri in [
+        "/nodes/children/missing",
+        "/nodes/assets/missing.node",
+        "/nodes/detail/missing.node",
+        "/nodes/tree/missing",
+    ] {
+        let (status, body) = get(uri).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+        assert_eq!(body["error"]["code"], "not_found", "{uri}");
+        assert!(body["error"]["message"].is_string());
+    }
+}
+
+#[tokio::test]
+async fn hostile_paths_are_rejected_with_400() {
+    for uri in [
+        "/nodes/children/1_OnceAndOnceAgain.*",
+        "/nodes/children/a%7Cb",
+        "/nodes/assets/!a",
+        "/nodes/children/a..b",
+        "/nodes/detail/a%7Cb",
+        "/nodes/tree/a.*",
+    ] {
+        let (status, body) = get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert_eq!(body["error"]["code"], "invalid_path", "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn hidden_nodes_and_assets_are_unreachable() {
+    for uri in [
+        "/nodes/detail/10_Archive.drafts",
+        "/nodes/children/10_Archive.drafts",
+        "/nodes/assets/10_Archive.drafts",
+        "/nodes/tree/10_Archive.drafts",
+    ] {
+        let (status, body) = get(uri).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+        assert_eq!(body["error"]["code"], "not_found", "{uri}");
+    }
+
+    let (_, body) = get("/nodes/search?q=unreleased").await;
+    assert_eq!(body["total"], 0);
+}
+
+#[tokio::test]
+async fn unlisted_nodes_are_reachable_by_link_only() {
+    // 不出现在父节点列表、子树与搜索中
+    let (_, body) = get("/nodes/children/10_Archive").await;
+    assert!(body["directories"].as_array().unwrap().is_empty());
+    let (_, body) = get("/nodes/tree/10_Archive").await;
+    assert!(body["tree"]["children"].as_array().unwrap().is_empty());
+    let (_, body) = get("/nodes/search?q=study").await;
+    assert_eq!(body["total"], 0);
+
+    // 按路径访问时正常返回，分支内的节点与资源照常列出
+    let (status, body) = get("/nodes/detail/10_Archive.preview").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["node"]["visibility"], "unlisted");
+    assert_eq!(paths(&body["siblings"], "path"), ["10_Archive.preview"]);
+    assert_eq!(body["child_count"], 1);
+
+    let (_, body) = get("/nodes/children/10_Archive.preview").await;
+    assert_eq!(
+        paths(&body["directories"], "path"),
+        ["10_Archive.preview.study"]
+    );
+    let (_, body) = get("/nodes/assets/10_Archive.preview").await;
+    assert_eq!(paths(&body["assets"], "raw_filename"), ["study.jpg"]);
+    let (_, body) = get("/nodes/tree/10_Archive.preview").await;
+    assert_eq!(
+        paths(&body["tree"]["children"], "path"),
+        ["10_Archive.preview.study"]
+    );
+}
+
+#[tokio::test]
+async fn search_matches_names_and_markdown_content() {
+    let (status, body) = get("/nodes/search?q=linen").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 1);
+    let hit = &body["results"][0];
+    assert_eq!(hit["kind"], "asset");
+    assert_eq!(hit["node"]["raw_filename"], "intro.md");
+    assert!(hit["snippet"]
+        .as_str()
+        .unwrap()
+        .contains("<mark>linen</mark>"));
+
+    let (_, body) = get("/nodes/search?q=book").await;
+    let kinds: Vec<&str> = body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["kind"].as_str().unwrap())
+        .collect();
+    assert!(kinds.contains(&"directory"));
+    assert!(kinds.contains(&"asset"));
+}
+
+#[tokio::test]
+async fn search_paginates() {
+    let (_, first) = get("/nodes/search?q=book&per_page=1").await;
+    let (_, second) = get("/nodes/search?q=book&per_page=1&page=2").await;
+    assert_eq!(first["results"].as_array().unwrap().len(), 1);
+    assert_eq!(first["total"], second["total"]);
+    assert_eq!(second["page"], 2);
+    assert_ne!(first["results"][0], second["results"][0]);
+}
+
+#[tokio::test]
+async fn search_past_last_page_keeps_total() {
+    let (_, first) = get("/nodes/search?q=book").await;
+    let (status, body) = get("/nodes/search?q=book&page=50").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["results"].as_array().unwrap().is_empty());
+    assert_eq!(body["total"], first["total"]);
+}
+
+#[tokio::test]
+async fn search_snippets_escape_html_in_content() {
+    let nodes = "path,has_subnodes,raw_path,raw_filename\nwork,false,work,work\n";
+    let assets = "file_path,raw_path,raw_filename,content\n\
+        work.visual_assets.intro_md,work/visual_assets/intro.md,intro.md,\
+        <img src=x onerror=alert(1)> linen & paper\n";
+    let repo = InMemoryNodeRepository::from_csv(nodes, assets).unwrap();
+    let app = build_router(Arc::new(repo), Config::default(), Router::new());
+
+    let (_, body) = get_from(app, "/nodes/search?q=linen").await;
+    assert_eq!(
+        body["results"][0]["snippet"],
+        "&lt;img src=x onerror=alert(1)&gt; <mark>linen</mark> &amp; paper"
+    );
+}
+
+#[tokio::test]
+async fn empty_search_returns_no_results() {
+    let (status, body) = get("/nodes/search?q=%20").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 0);
+}
+
+#[tokio::test]
+async fn public_config_exposes_resource_base_url() {
+    let (status, body) = get("/config").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["resource_base_url"], "/resource");
+}
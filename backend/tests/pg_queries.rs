@@ -0,0 +1,186 @@
+//! 直接针对 PostgreSQL 的查询测试。
+//!
+//! 需要设置 `TP_TEST_DATABASE_URL`（数据库需已安装 ltree 扩展），未设置时跳过；
+//! 每个测试在独立的 schema 中建表（表结构与 node-generate-tool 的 schema.sql 相同），
+//! 开始前清理同名 schema，互不干扰。
+
+use std::str::FromStr;
+
+use backend::database_ctl::directory::{
+    get_assets_of, get_node_assets, search_nodes, SearchHit, SearchParams,
+};
+use backend::error::ApiError;
+use backend::node_path::NodePath;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+
+const SCHEMA_SQL: &str = include_str!("../../utils/node-generate-tool/src/schema.sql");
+
+const SEED_SQL: &str = r#"
+INSERT INTO directory_nodes (path, has_subnodes, raw_path, raw_filename, sort_key, visibility)
+VALUES
+    ('1_OnceAndOnceAgain', true, '1_OnceAndOnceAgain', '1_OnceAndOnceAgain', '1', 'public'),
+    ('1_OnceAndOnceAgain.handmadeBook', true, '1_OnceAndOnceAgain/handmadeBook', 'handmadeBook',
+     '1handmadebook', 'public'),
+    ('1_OnceAndOnceAgain.handmadeBook.Book', false, '1_OnceAndOnceAgain/handmadeBook/Book', 'Book',
+     '1book', 'public'),
+    ('1_OnceAndOnceAgain.bookDraft', false, '1_OnceAndOnceAgain/bookDraft', 'bookDraft',
+     '1bookdraft', 'hidden'),
+    ('2_Book', false, '2_Book', '2_Book', '2', 'unlisted');
+INSERT INTO file_nodes (file_path, raw_path, raw_filename, content, sort_key, visibility)
+VALUES
+    ('1_OnceAndOnceAgain.handmadeBook.visual_assets.intro_md',
+     '1_OnceAndOnceAgain/handmadeBook/visual_assets/intro.md', 'intro.md',
+     'A book bound with linen thread, 1 < 2 & "3". <img src=x onerror=alert(1)>', '1intro.md', 'public'),
+    ('1_OnceAndOnceAgain.handmadeBook.visual_assets.notes_md',
+     '1_OnceAndOnceAgain/handmadeBook/visual_assets/notes.md', 'notes.md',
+     'Another book, unreleased.', '1notes.md', 'hidden');
+"#;
+
+/// 建立指向独立 schema 的连接池；未配置测试数据库时返回 `None`
+async fn test_pool(schema: &str) -> Option<PgPool> {
+    let Ok(url) = std::env::var("TP_TEST_DATABASE_URL") else {
+        eprintln!("未设置 TP_TEST_DATABASE_URL，跳过数据库测试");
+        return None;
+    };
+    let admin = PgPool::connect(&url).await.expect("无法连接测试数据库");
+    // 扩展装在 public 中，避免随测试 schema 一起被删除；并发创建时可能报错，忽略即可
+    let _ = sqlx::query("CREATE EXTENSION IF NOT EXISTS ltree WITH SCHEMA public")
+        .execute(&admin)
+        .await;
+    sqlx::raw_sql(&format!(
+        "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema};"
+    ))
+    .execute(&admin)
+    .await
+    .expect("无法创建测试 schema");
+    admin.close().await;
+
+    let options = PgConnectOptions::from_str(&url)
+        .unwrap()
+        .options([("search_path", format!("{schema},public"))]);
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(options)
+        .await
+        .expect("无法连接测试数据库");
+    sqlx::raw_sql(SCHEMA_SQL)
+        .execute(&pool)
+        .await
+        .expect("建表失败");
+    sqlx::raw_sql(SEED_SQL)
+        .execute(&pool)
+        .await
+        .expect("写入测试数据失败");
+    Some(pool)
+}
+
+fn params(q: &str, page: u32, per_page: u32) -> SearchParams {
+    SearchParams {
+        q: q.to_string(),
+        page: Some(page),
+        per_page: Some(per_page),
+    }
+}
+
+fn snippet(hit: &SearchHit) -> &str {
+    match hit {
+        SearchHit::Directory { snippet, .. } | SearchHit::Asset { snippet, .. } => snippet,
+    }
+}
+
+#[tokio::test]
+async fn search_counts_public_hits_across_pages() {
+    let Some(pool) = test_pool("tp_test_search_pages").await else {
+        return;
+    };
+
+    let first = search_nodes(&pool, &params("book", 1, 1)).await.unwrap();
+    // Book 与 intro.md；隐藏与不公开的内容不参与搜索
+    assert_eq!(first.total, 2);
+    assert_eq!(first.hits.len(), 1);
+
+    let second = search_nodes(&pool, &params("book", 2, 1)).await.unwrap();
+    assert_eq!(second.total, 2);
+    assert_eq!(second.hits.len(), 1);
+
+    let past_end = search_nodes(&pool, &params("book", 5, 10)).await.unwrap();
+    assert!(past_end.hits.is_empty());
+    assert_eq!(past_end.total, 2);
+
+    let none = search_nodes(&pool, &params("nothing", 1, 10))
+        .await
+        .unwrap();
+    assert!(none.hits.is_empty());
+    assert_eq!(none.total, 0);
+}
+
+#[tokio::test]
+async fn search_snippets_escape_markdown_html() {
+    let Some(pool) = test_pool("tp_test_search_snippets").await else {
+        return;
+    };
+
+    let page = search_nodes(&pool, &params("linen", 1, 10)).await.unwrap();
+    assert_eq!(page.total, 1);
+    let SearchHit::Asset { node, .. } = &page.hits[0] else {
+        panic!("应命中 markdown 资源");
+    };
+    assert_eq!(node.raw_filename, "intro.md");
+
+    let text = snippet(&page.hits[0]);
+    assert!(text.contains("<mark>linen</mark>"), "{text}");
+    assert!(text.contains("1 &lt; 2 &amp; &quot;3&quot;"), "{text}");
+    // 除高亮标签外不应出现任何 HTML
+    let rest = text.replace("<mark>", "").replace("</mark>", "");
+    assert!(!rest.contains('<') && !rest.contains('>'), "{text}");
+}
+
+#[tokio::test]
+async fn ltree_parse_errors_hide_database_message() {
+    let Some(pool) = test_pool("tp_test_ltree_errors").await else {
+        return;
+    };
+
+    let err = sqlx::query("SELECT 'a..b'::ltree")
+        .execute(&pool)
+        .await
+        .unwrap_err();
+    let err = ApiError::from(err);
+    assert_eq!(err.code(), "invalid_path");
+    assert_eq!(err.message(), "路径无效: 无法解析节点路径");
+}
+
+#[tokio::test]
+async fn assets_come_from_any_asset_folder_with_stored_kind() {
+    let Some(pool) = test_pool("tp_test_asset_folders").await else {
+        return;
+    };
+    sqlx::raw_sql(
+        r#"
+        INSERT INTO file_nodes (file_path, raw_path, raw_filename, sort_key, visibility, kind)
+        VALUES
+            ('1_OnceAndOnceAgain.handmadeBook.Book.media.notes_txt',
+             '1_OnceAndOnceAgain/handmadeBook/Book/media/notes.txt', 'notes.txt', '1', 'public',
+             'markdown'),
+            ('1_OnceAndOnceAgain.handmadeBook.Book.media.sub.deep_png',
+             '1_OnceAndOnceAgain/handmadeBook/Book/media/sub/deep.png', 'deep.png', '2', 'public',
+             'image');
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let book = NodePath::parse("1_OnceAndOnceAgain.handmadeBook.Book").unwrap();
+    let assets = get_node_assets(&pool, &book).await.unwrap();
+    let names: Vec<_> = assets.iter().map(|a| a.raw_filename.as_str()).collect();
+    assert_eq!(names, ["notes.txt"]);
+    assert_eq!(assets[0].asset_kind(), "markdown");
+
+    let parent = NodePath::parse("1_OnceAndOnceAgain.handmadeBook").unwrap();
+    let assets = get_assets_of(&pool, &[parent, book]).await.unwrap();
+    let names: Vec<_> = assets.iter().map(|a| a.raw_filename.as_str()).collect();
+    // 种子数据未写 kind，按扩展名判断
+    assert_eq!(names, ["notes.txt", "intro.md"]);
+    assert_eq!(assets[1].asset_kind(), "markdown");
+}
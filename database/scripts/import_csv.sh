@@ -9,6 +9,7 @@ COMPOSE_CMD="${COMPOSE_CMD:-podman-compose}"
 
 NODE_CSV="${EXCHANGE_DIR}/node.csv"
 VISUAL_CSV="${EXCHANGE_DIR}/visual_assets.csv"
+SCHEMA_SQL="${REPO_ROOT}/utils/node-generate-tool/src/schema.sql"
 
 if [[ ! -f "${NODE_CSV}" || ! -f "${VISUAL_CSV}" ]]; then
   echo "[数据库导入] 缺少 CSV 文件。请先运行 scripts/refresh_resources.sh" >&2
@@ -24,32 +25,26 @@ set -euo pipefail
 IMPORT_DIR="/import_exchange"
 NODE_CSV="${IMPORT_DIR}/node.csv"
 VISUAL_CSV="${IMPORT_DIR}/visual_assets.csv"
+DERIVATIVES_CSV="${IMPORT_DIR}/image_derivatives.csv"
 
 if [[ ! -f "${NODE_CSV}" || ! -f "${VISUAL_CSV}" ]]; then
   echo "[数据库导入] 容器内未找到 CSV 文件: ${IMPORT_DIR}" >&2
   exit 1
 fi
 
-psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" <<EOSQL
-CREATE EXTENSION IF NOT EXISTS ltree;
-CREATE TABLE IF NOT EXISTS directory_nodes (
-    path ltree PRIMARY KEY,
-    has_subnodes BOOLEAN NOT NULL,
-    raw_path TEXT NOT NULL,
-    raw_filename TEXT NOT NULL
-);
-CREATE TABLE IF NOT EXISTS file_nodes (
-    file_path ltree PRIMARY KEY,
-    raw_path TEXT NOT NULL,
-    raw_filename TEXT NOT NULL
-);
-TRUNCATE directory_nodes;
-TRUNCATE file_nodes;
-EOSQL
-
-psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "\copy directory_nodes(path,has_subnodes,raw_path,raw_filename) FROM '\''${NODE_CSV}'\'' WITH (FORMAT csv, HEADER true);"
-psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "\copy file_nodes(file_path,raw_path,raw_filename) FROM '\''${VISUAL_CSV}'\'' WITH (FORMAT csv, HEADER true);"
+# 表结构由标准输入传入的 schema.sql 创建 / 迁移，与 node-generate-tool 的 import 使用同一份
+PGOPTIONS="-c client_min_messages=warning" psql -v ON_ERROR_STOP=1 -q -U "${POSTGRES_USER}" -d "${POSTGRES_DB}"
+psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "TRUNCATE directory_nodes, file_nodes, import_state;"
+
+psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "\copy directory_nodes(path,has_subnodes,raw_path,raw_filename,has_layout,metadata,sort_key,visibility,layout_path) FROM '\''${NODE_CSV}'\'' WITH (FORMAT csv, HEADER true);"
+psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "\copy file_nodes(file_path,raw_path,raw_filename,content,sort_key,visibility,file_size,file_type,modified_time,content_hash,width,height,duration_ms,kind) FROM '\''${VISUAL_CSV}'\'' WITH (FORMAT csv, HEADER true, FORCE_NOT_NULL (content, content_hash));"
+
+# 衍生图记录可选：未生成时保留表中已有的记录
+if [[ -f "${DERIVATIVES_CSV}" ]]; then
+  psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "TRUNCATE image_derivatives;"
+  psql -U "${POSTGRES_USER}" -d "${POSTGRES_DB}" -c "\copy image_derivatives(file_path,content_hash,format,width,height,path) FROM '\''${DERIVATIVES_CSV}'\'' WITH (FORMAT csv, HEADER true);"
+fi
 
 echo "[数据库导入] 导入完成"
-'
+' < "${SCHEMA_SQL}"
 
@@ -1,5 +1,6 @@
 use crate::pages::home::HomeLogic;
-use crate::{DetailItem, NodeKind};
+use crate::utils::types::encode_path_segments;
+use crate::{DetailItem, FileInfo, NodeKind};
 use leptos::callback::Callback;
 use leptos::prelude::*;
 use std::sync::Arc;
@@ -15,9 +16,6 @@ pub fn DetailPanel(
     #[prop(optional_no_strip)] on_node_click: Option<Arc<dyn Fn(DetailItem) + Send + Sync>>,
 ) -> impl IntoView {
     {
-        let items = items.clone();
-        let loading = loading.clone();
-        let error = error.clone();
         Effect::new(move |_| {
             let snapshot = items.get();
             let serialized = serde_json::to_string(&snapshot).unwrap_or_else(|_| "[]".to_string());
@@ -115,7 +113,13 @@ fn render_detail_item(
         has_children,
         content,
         display_as_entry: _,
+        file,
     } = item;
+    let version = file
+        .as_ref()
+        .and_then(FileInfo::version)
+        .map(str::to_string);
+    let summary = file_summary(file.as_ref());
 
     match kind {
         NodeKind::Directory => {
@@ -145,31 +149,83 @@ fn render_detail_item(
         }
         NodeKind::Image => {
             let path = raw_path.unwrap_or_default();
-            let src = asset_to_url(&path);
+            let src = asset_to_url(&path, version.as_deref());
+            // 写入宽高属性，图片加载前按比例预留空间，避免布局跳动
+            let (width, height) = file
+                .as_ref()
+                .and_then(FileInfo::dimensions)
+                .map(|(width, height)| (Some(width), Some(height)))
+                .unwrap_or_default();
+            // 有衍生图时由浏览器按显示宽度与格式支持挑选，原图作为兜底
+            let sources = file
+                .as_ref()
+                .map(|file| {
+                    ["avif", "webp"]
+                        .into_iter()
+                        .filter_map(|format| {
+                            let srcset = derivative_srcset(file, format)?;
+                            Some(view! {
+                                <source
+                                    type=format!("image/{format}")
+                                    srcset=srcset
+                                    sizes=DETAIL_IMAGE_SIZES
+                                />
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
             view! {
                 <div class="space-y-1">
-                    <img src=src class="max-w-full rounded shadow" alt=label.clone()/>
+                    <picture>
+                        {sources}
+                        <img
+                            src=src
+                            width=width
+                            height=height
+                            loading="lazy"
+                            class="max-w-full h-auto rounded shadow"
+                            alt=label.clone()
+                        />
+                    </picture>
                     <div class="text-xs text-gray-400 break-all">{path}</div>
+                    <div class="text-xs text-gray-500">{summary}</div>
                 </div>
             }
             .into_any()
         }
         NodeKind::Video => {
             let path = raw_path.unwrap_or_default();
-            let src = asset_to_url(&path);
+            let src = asset_to_url(&path, version.as_deref());
+            let poster = file.as_ref().and_then(poster_url);
+            let (width, height) = file
+                .as_ref()
+                .and_then(FileInfo::dimensions)
+                .map(|(width, height)| (Some(width), Some(height)))
+                .unwrap_or_default();
+            // 只加载封面帧，点击播放后才请求视频本身
             view! {
                 <div class="space-y-1">
-                    <video src=src.clone() controls class="w-full rounded shadow">
+                    <video
+                        src=src.clone()
+                        poster=poster
+                        preload="none"
+                        width=width
+                        height=height
+                        controls
+                        class="w-full h-auto rounded shadow"
+                    >
                         <track kind="captions"/>
                     </video>
                     <div class="text-xs text-gray-400 break-all">{path}</div>
+                    <div class="text-xs text-gray-500">{summary}</div>
                 </div>
             }
             .into_any()
         }
         NodeKind::Pdf => {
             let path = raw_path.unwrap_or_default();
-            let src = asset_to_url(&path);
+            let src = asset_to_url(&path, version.as_deref());
             let iframe_src = src.clone();
             view! {
                 <div class="space-y-2">
@@ -178,6 +234,7 @@ fn render_detail_item(
                         <iframe src=iframe_src class="w-full h-full rounded" title=label.clone()></iframe>
                     </object>
                     <div class="text-xs text-gray-500 break-all">{path}</div>
+                    <div class="text-xs text-gray-500">{summary}</div>
                 </div>
             }
             .into_any()
@@ -189,6 +246,7 @@ fn render_detail_item(
                 <div class="bg-gray-900 text-gray-200 px-3 py-2 rounded">
                     <div class="font-medium text-base">{label.clone()}</div>
                     <div class="text-xs text-gray-500 break-all">{path}</div>
+                    <div class="text-xs text-gray-500">{summary}</div>
                 </div>
             }
             .into_any()
@@ -205,6 +263,7 @@ fn render_listing_entry(
     let directory_path = item.directory_path.clone();
     let raw_path = item.raw_path.clone();
     let has_children = item.has_children;
+    let summary = file_summary(item.file.as_ref());
 
     let detail = directory_path
         .clone()
@@ -233,6 +292,7 @@ fn render_listing_entry(
                 <div class="flex items-center gap-2">
                     <span class="text-xs text-gray-500">{badge}</span>
                     <span class="text-gray-100">{label.clone()}</span>
+                    <span class="text-xs text-gray-500">{summary.clone()}</span>
                 </div>
                 <div class="text-xs text-gray-600 break-all mt-1">{detail.clone()}</div>
             </div>
@@ -263,17 +323,86 @@ fn render_listing_entry(
     .into_any()
 }
 
-fn asset_to_url(raw_path: &str) -> String {
+/// Detail 栏图片的显示宽度：桌面端约占视口的一半，移动端占满
+const DETAIL_IMAGE_SIZES: &str = "(min-width: 1024px) 50vw, 100vw";
+
+/// 某一格式衍生图的 `srcset`，如 `.../a-320.webp 320w, .../a-640.webp 640w`
+fn derivative_srcset(file: &FileInfo, format: &str) -> Option<String> {
+    let list = file.derivatives_of(format);
+    if list.is_empty() {
+        return None;
+    }
+    Some(
+        list.iter()
+            .map(|derivative| {
+                format!(
+                    "{} {}w",
+                    derivative_to_url(&derivative.path),
+                    derivative.width
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// 视频封面帧：取不窄于 [`POSTER_MIN_WIDTH`] 的最小 WebP 衍生图，都更窄时取最宽的一张
+fn poster_url(file: &FileInfo) -> Option<String> {
+    let list = file.derivatives_of("webp");
+    let poster = list
+        .iter()
+        .find(|derivative| derivative.width >= POSTER_MIN_WIDTH)
+        .or(list.last())?;
+    Some(derivative_to_url(&poster.path))
+}
+
+/// 封面帧的最小宽度，覆盖 Detail 栏在常见屏幕上的显示宽度
+const POSTER_MIN_WIDTH: i32 = 960;
+
+/// 衍生图 URL；文件名已含内容哈希，无需版本参数
+fn derivative_to_url(path: &str) -> String {
+    let origin = web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    format!(
+        "{}/derivatives/{}",
+        origin.trim_end_matches('/'),
+        encode_path_segments(path.trim_start_matches('/'))
+    )
+}
+
+/// 资源的展示摘要，如 `1600×1200 · 179.1 KB`，视频另含时长；没有元数据时为空
+fn file_summary(file: Option<&FileInfo>) -> String {
+    let Some(file) = file else {
+        return String::new();
+    };
+    let dimensions = file
+        .dimensions()
+        .map(|(width, height)| format!("{width}×{height}"));
+    dimensions
+        .into_iter()
+        .chain(file.duration_label())
+        .chain(file.size_label())
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// 资源 URL；`version` 为内容哈希前缀，作为查询参数使文件替换后缓存失效
+fn asset_to_url(raw_path: &str, version: Option<&str>) -> String {
     let normalized = raw_path.replace('\\', "/");
-    if normalized.starts_with("http://") || normalized.starts_with("https://") {
+    let url = if normalized.starts_with("http://") || normalized.starts_with("https://") {
         normalized
     } else {
         let trimmed = normalized.trim_start_matches('/');
         let origin = web_sys::window()
             .and_then(|w| w.location().origin().ok())
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
         let base = origin.trim_end_matches('/');
-        format!("{}/resource/{}", base, trimmed)
+        format!("{}/resource/{}", base, encode_path_segments(trimmed))
+    };
+    match version {
+        Some(version) => format!("{url}?v={version}"),
+        None => url,
     }
 }
 
@@ -283,14 +412,14 @@ pub fn Detail(logic: HomeLogic, on_node_click: Callback<Option<String>>) -> impl
     let detail_items = logic.detail_items.read_only();
     let detail_loading = logic.detail_loading.read_only();
     let detail_error = logic.detail_error.read_only();
-    let detail_scroll_ref = logic.detail_scroll_ref.clone();
+    let detail_scroll_ref = logic.detail_scroll_ref;
     let pane_key = Memo::new({
-        let current_path = logic.current_path.clone();
+        let current_path = logic.current_path;
         move |_| current_path.get().unwrap_or_else(|| "root".to_string())
     });
 
     let detail_callback: Arc<dyn Fn(DetailItem) + Send + Sync> = {
-        let handler = on_node_click.clone();
+        let handler = on_node_click;
         Arc::new(move |item: DetailItem| {
             handler.run(item.directory_path.clone());
         })
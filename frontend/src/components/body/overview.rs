@@ -23,7 +23,7 @@ pub fn OverviewColumn(
                         .clone()
                         .or_else(|| node.directory_path.clone())
                         .unwrap_or_default();
-                    let highlight_signal = highlighted_path.clone();
+                    let highlight_signal = highlighted_path;
                     let node_clone = node.clone();
 
                     view! {
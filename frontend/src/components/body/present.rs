@@ -26,22 +26,20 @@ pub fn PresentColumn(
     let container_ref = scroll_container_ref;
 
     {
-        let nodes = nodes.clone();
-        let selected_index = selected_index.clone();
         Effect::new(move |_| {
             log_present_state(&nodes.get(), selected_index.get());
         });
     }
 
     view! {
-        <div class="h-full overflow-y-auto pr-1" node_ref=container_ref.clone()>
+        <div class="h-full overflow-y-auto pr-1" node_ref=container_ref>
             <ul class="text-2xl text-gray-500 outline-none space-y-1">
                 <For
                     each=move || nodes.get().into_iter().enumerate()
                     key=|(idx, node)| format!("{}:{}", idx, node.id)
                     children=move |(idx, node): (usize, UiNode)| {
                         let is_selected = Memo::new({
-                            let selected_index = selected_index.clone();
+                            let selected_index = selected_index;
                             move |_| selected_index.get() == Some(idx)
                         });
                         let label = node.label.clone();
@@ -53,7 +51,7 @@ pub fn PresentColumn(
                         let is_directory = matches!(node.kind, NodeKind::Directory);
                         let node_clone = node.clone();
                         let idx_attr = idx.to_string();
-                        let selected_index_for_click = selected_index.clone();
+                        let selected_index_for_click = selected_index;
 
                         view! {
                             <li class="w-full min-w-0" data-index=idx_attr.clone()>
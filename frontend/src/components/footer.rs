@@ -1,10 +1,16 @@
 use leptos::prelude::*;
 
+use crate::SortMode;
+
 #[component]
-pub fn Footer() -> impl IntoView {
+pub fn Footer(sort_mode: RwSignal<SortMode>) -> impl IntoView {
     view! {
         <div>
-        <p class="text-sm text-gray-500">"使用hjkl进行导航，使用Shift+J/K进行翻页"</p>
+        <p class="text-sm text-gray-500">
+            "使用hjkl进行导航，使用Shift+J/K进行翻页，使用/或Ctrl+K搜索跳转，使用s切换排序（当前："
+            {move || sort_mode.get().label()}
+            "）"
+        </p>
         </div>
     }
 }
@@ -9,7 +9,7 @@ pub fn Header() -> impl IntoView {
     view! {
         <header class="flex flex-col gap-2">
             <h1 class="text-4xl font-semibold">"The Temple Project"</h1>
-            <p class="text-sm text-gray-500">"使用 hjkl 导航，Shift + J/K 翻页"</p>
+            <p class="text-sm text-gray-500">"使用 hjkl 导航，Shift + J/K 翻页，/ 或 Ctrl + K 搜索跳转"</p>
         </header>
     }
 }
@@ -18,9 +18,10 @@ pub fn Header() -> impl IntoView {
 #[component]
 pub fn MobileHeader(
     current_path: RwSignal<Option<String>>,
+    breadcrumbs: Memo<Vec<String>>,
     set_pending_path: WriteSignal<Option<String>>,
 ) -> impl IntoView {
-    let segments = Memo::new(move |_| format_segments(current_path.get()));
+    let segments = Memo::new(move |_| format_segments(breadcrumbs.get()));
 
     view! {
         <div class="h-[5vh] min-h-[48px] flex items-center gap-3 px-4 border-b border-gray-900">
@@ -52,10 +53,11 @@ pub fn MobileHeader(
     }
 }
 
-fn format_segments(path: Option<String>) -> Vec<String> {
-    match path {
-        Some(p) if !p.is_empty() => p.split('.').map(truncate_segment).collect(),
-        _ => vec![ROOT_LABEL.to_string()],
+fn format_segments(names: Vec<String>) -> Vec<String> {
+    if names.is_empty() {
+        vec![ROOT_LABEL.to_string()]
+    } else {
+        names.iter().map(|name| truncate_segment(name)).collect()
     }
 }
 
@@ -63,6 +65,7 @@ fn truncate_segment(segment: &str) -> String {
     if segment.chars().count() <= 5 {
         segment.to_string()
     } else {
-        format!("{}…", &segment[..5])
+        // 显示名可能包含多字节字符，按字符截断
+        format!("{}…", segment.chars().take(5).collect::<String>())
     }
 }
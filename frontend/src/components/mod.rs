@@ -1,3 +1,4 @@
 pub mod body;
 pub mod footer;
 pub mod header;
+pub mod palette;
@@ -0,0 +1,191 @@
+use leptos::callback::{Callable, UnsyncCallback};
+use leptos::ev::{self, KeyboardEvent, MouseEvent};
+use leptos::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::{NodeKind, PaletteEntry};
+
+/// 命令面板：按 `/` 或 Ctrl + K 打开，输入关键词模糊匹配节点与资源；
+/// ↑ / ↓（或 Ctrl + N / Ctrl + P）移动选中项，Enter 跳转，Esc 关闭，全程无需鼠标。
+/// 面板打开期间无论焦点在哪里这些按键都生效，并会把焦点交还输入框。
+#[component]
+pub fn CommandPalette(
+    open: RwSignal<bool>,
+    query: RwSignal<String>,
+    results: Memo<Vec<PaletteEntry>>,
+    selected: RwSignal<usize>,
+    #[prop(into)] on_choose: UnsyncCallback<PaletteEntry>,
+) -> impl IntoView {
+    let input_ref = NodeRef::<leptos::html::Input>::new();
+    let list_ref = NodeRef::<leptos::html::Div>::new();
+
+    // 打开后聚焦输入框；输入框随面板渲染，节点就绪时 Effect 会再次运行
+    Effect::new(move |_| {
+        if open.get() {
+            if let Some(input) = input_ref.get() {
+                let _ = input.focus();
+            }
+        }
+    });
+
+    // 结果变少时把选中项收回范围内，并让选中项保持可见
+    Effect::new(move |_| {
+        let len = results.with(Vec::len);
+        let idx = selected.get();
+        if len > 0 && idx >= len {
+            selected.set(len - 1);
+            return;
+        }
+        if let Some(element) = list_ref.get().and_then(|list| {
+            list.dyn_ref::<web_sys::Element>().and_then(|el| {
+                el.query_selector(&format!(r#"[data-index="{}"]"#, idx))
+                    .ok()
+                    .flatten()
+            })
+        }) {
+            element.scroll_into_view_with_bool(false);
+        }
+    });
+
+    let choose = move |idx: usize| {
+        if let Some(entry) = results.with_untracked(|entries| entries.get(idx).cloned()) {
+            open.set(false);
+            on_choose.run(entry);
+        }
+    };
+
+    // 键盘监听挂在 window 上：焦点落到面板其他位置（如点击列表空白处）时按键依然生效，
+    // 并把焦点交还输入框，方便继续输入
+    let keydown = window_event_listener(ev::keydown, move |event: KeyboardEvent| {
+        if !open.get_untracked() {
+            return;
+        }
+        match (event.key().as_str(), event.ctrl_key()) {
+            ("ArrowDown", _) | ("n", true) => {
+                event.prevent_default();
+                let len = results.with_untracked(Vec::len);
+                selected.update(|idx| *idx = (*idx + 1).min(len.saturating_sub(1)));
+            }
+            ("ArrowUp", _) | ("p", true) => {
+                event.prevent_default();
+                selected.update(|idx| *idx = idx.saturating_sub(1));
+            }
+            ("Enter", _) => {
+                event.prevent_default();
+                choose(selected.get_untracked());
+                return;
+            }
+            ("Escape", _) => {
+                event.prevent_default();
+                open.set(false);
+                return;
+            }
+            (key, false) if key == "Backspace" || key.chars().count() == 1 => {
+                if event.alt_key() || event.meta_key() {
+                    return;
+                }
+            }
+            _ => return,
+        }
+        // 在默认行为发生前聚焦，字符会直接输入到输入框中
+        if let Some(input) = input_ref.get_untracked() {
+            let _ = input.focus();
+        }
+    });
+    on_cleanup(move || keydown.remove());
+
+    view! {
+        <Show when=move || open.get()>
+            <div
+                class="fixed inset-0 z-50 flex items-start justify-center pt-[15vh] bg-black/70"
+                on:click=move |_| open.set(false)
+            >
+                <div
+                    class="w-[40rem] max-w-[90vw] border border-gray-700 bg-black"
+                    on:click=|event: MouseEvent| event.stop_propagation()
+                >
+                    <input
+                        node_ref=input_ref
+                        type="text"
+                        placeholder="输入名称跳转到节点或文件"
+                        class="w-full px-4 py-3 bg-black text-white text-lg outline-none border-b border-gray-800"
+                        prop:value=move || query.get()
+                        on:input=move |event| {
+                            query.set(event_target_value(&event));
+                            selected.set(0);
+                        }
+                    />
+                    <div class="max-h-[50vh] overflow-y-auto" node_ref=list_ref>
+                        <Show
+                            when=move || !results.with(Vec::is_empty)
+                            fallback=move || {
+                                view! {
+                                    <p class="px-4 py-3 text-sm text-gray-600">
+                                        {move || {
+                                            if query.with(|q| q.trim().is_empty()) {
+                                                "已浏览过的节点会出现在这里"
+                                            } else {
+                                                "没有匹配的节点"
+                                            }
+                                        }}
+                                    </p>
+                                }
+                            }
+                        >
+                            <ul>
+                                <For
+                                    each=move || results.get().into_iter().enumerate()
+                                    key=|(idx, entry)| format!("{}:{}", idx, entry.id)
+                                    children=move |(idx, entry): (usize, PaletteEntry)| {
+                                        let label = entry.label.clone();
+                                        let kind = kind_label(&entry.kind);
+                                        let breadcrumb = format_breadcrumb(&entry.breadcrumb);
+                                        view! {
+                                            <li
+                                                data-index=idx.to_string()
+                                                class=move || {
+                                                    let base = "px-4 py-2 cursor-pointer";
+                                                    if selected.get() == idx {
+                                                        format!("{base} text-white bg-gray-800")
+                                                    } else {
+                                                        format!("{base} text-gray-400 hover:bg-gray-900")
+                                                    }
+                                                }
+                                                on:click=move |_| choose(idx)
+                                            >
+                                                <div class="flex items-baseline gap-2 min-w-0">
+                                                    <span class="truncate">{label}</span>
+                                                    <span class="text-xs text-gray-600">{kind}</span>
+                                                </div>
+                                                <div class="text-xs text-gray-600 truncate">{breadcrumb}</div>
+                                            </li>
+                                        }
+                                    }
+                                />
+                            </ul>
+                        </Show>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
+
+fn kind_label(kind: &NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Directory | NodeKind::Overview => "目录",
+        NodeKind::Image => "图片",
+        NodeKind::Video => "视频",
+        NodeKind::Pdf => "PDF",
+        NodeKind::Markdown => "文档",
+        NodeKind::Other => "文件",
+    }
+}
+
+fn format_breadcrumb(levels: &[String]) -> String {
+    if levels.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/ {}", levels.join(" / "))
+    }
+}
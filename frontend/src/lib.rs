@@ -4,6 +4,7 @@ mod pages;
 pub mod utils;
 
 pub use utils::types::{
-    AssetNode, AssetsCache, AssetsResponse, DetailItem, DirectoriesResponse, DirectoryNode,
-    NodeKind, NodesCache, UiNode, ROOT_PATH,
+    ApiError, AssetNode, AssetsCache, AssetsResponse, DetailItem, DirectoriesResponse,
+    DirectoryNode, FileInfo, ImageDerivative, NodeKind, NodesCache, PaletteEntry, SortMode, UiNode,
+    ROOT_PATH,
 };
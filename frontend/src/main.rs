@@ -1,6 +1,6 @@
 use leptos::prelude::*;
 use leptos_router::components::*;
-use TheTempleProject::app::App;
+use the_temple_project::app::App;
 
 fn main() {
     console_error_panic_hook::set_once();
@@ -26,8 +26,8 @@ pub fn Home() -> impl IntoView {
     let is_mobile = use_is_mobile_flag(MOBILE_BREAKPOINT_PX);
 
     {
-        let keyboard_enabled = logic.keyboard_enabled.clone();
-        let is_mobile_flag = is_mobile.clone();
+        let keyboard_enabled = logic.keyboard_enabled;
+        let is_mobile_flag = is_mobile;
         Effect::new(move |_| {
             keyboard_enabled.set(!is_mobile_flag.get());
         });
@@ -45,7 +45,7 @@ pub fn Home() -> impl IntoView {
     }
 
     {
-        let current_path = logic.current_path.clone();
+        let current_path = logic.current_path;
         Effect::new(move |_| {
             sync_url_with_path(current_path.get());
         });
@@ -71,7 +71,7 @@ fn use_is_mobile_flag(threshold: f64) -> ReadSignal<bool> {
 
     Effect::new(move |_| {
         if let Some(window) = web_sys::window() {
-            let set_is_mobile = set_is_mobile.clone();
+            let set_is_mobile = set_is_mobile;
             let closure = Closure::wrap(Box::new(move |_event: web_sys::UiEvent| {
                 set_is_mobile.set(is_mobile_viewport(threshold));
             }) as Box<dyn FnMut(_)>);
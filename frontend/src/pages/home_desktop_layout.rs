@@ -2,6 +2,7 @@ use super::HomeLogic;
 use crate::components::body::{DetailPanel, OverviewColumn, PresentColumn};
 use crate::components::footer::Footer;
 use crate::components::header::Header;
+use crate::components::palette::CommandPalette;
 use crate::utils::types::DetailItem;
 use leptos::prelude::*;
 
@@ -21,11 +22,17 @@ pub fn DesktopLayout(logic: HomeLogic) -> impl IntoView {
         detail_error,
         detail_path,
         selected_index,
+        palette_open,
+        palette_query,
+        palette_selected,
+        palette_results,
+        palette_choose_callback,
+        sort_mode,
         ..
     } = logic;
 
     let raw_detail_items = detail_items.read_only();
-    let detail_path_signal = detail_path.clone();
+    let detail_path_signal = detail_path;
     let (desktop_detail_items, set_desktop_detail_items) = signal(Vec::<DetailItem>::new());
 
     Effect::new(move |_| {
@@ -76,8 +83,15 @@ pub fn DesktopLayout(logic: HomeLogic) -> impl IntoView {
                 </div>
             </div>
             <div class="px-4 pb-4 flex-shrink-0">
-                <Footer/>
+                <Footer sort_mode=sort_mode/>
             </div>
+            <CommandPalette
+                open=palette_open
+                query=palette_query
+                results=palette_results
+                selected=palette_selected
+                on_choose=palette_choose_callback
+            />
         </div>
     }
 }
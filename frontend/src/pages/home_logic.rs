@@ -1,24 +1,40 @@
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::time::Duration;
 
 use gloo_net::http::Request;
 use leptos::callback::UnsyncCallback;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
-use pulldown_cmark::{html, Options, Parser};
 use serde_json;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 
-use crate::utils::api::{get_child_directories, get_node_assets, get_root_directories};
+use crate::utils::api::{
+    get_child_directories, get_node_assets, get_node_detail, get_root_directories, get_subtree,
+    search_nodes,
+};
+use crate::utils::fuzzy::fuzzy_score;
 use crate::utils::keyboard;
+use crate::utils::markdown::{node_links, render_markdown, LinkContext};
 use crate::utils::types::{
-    parent_path, split_levels, AssetNode, AssetsCache, DetailItem, DirectoryNode, NodeKind,
-    NodesCache, UiNode, ROOT_PATH,
+    asset_owner_path, encode_path_segments, parent_path, split_levels, AssetNode, AssetsCache,
+    DetailItem, DirectoryNode, FileInfo, NodeKind, NodeLabels, NodesCache, PaletteEntry, SearchHit,
+    SortMode, TreeNode, UiNode, ROOT_PATH,
 };
 
+/// 导航到某节点后在后台预取其子树的层数，`None` 关闭预取
+pub const PREFETCH_DEPTH: Option<u32> = Some(2);
+
+/// 命令面板最多展示的候选项数
+const PALETTE_MAX_RESULTS: usize = 30;
+/// 关键词达到该长度后才向后端发起搜索
+const PALETTE_SEARCH_MIN_CHARS: usize = 2;
+/// 输入停顿多久后发起搜索
+const PALETTE_SEARCH_DELAY: Duration = Duration::from_millis(200);
+
 /// 封装 Home 页面所需的所有信号、派生数据与操作方法。
 #[derive(Clone)]
 pub struct HomeLogic {
@@ -31,6 +47,7 @@ pub struct HomeLogic {
     pub present_nodes: Memo<Vec<UiNode>>,
     pub overview_nodes: Memo<Vec<UiNode>>,
     pub overview_highlight: Memo<Option<String>>,
+    pub breadcrumbs: Memo<Vec<String>>,
 
     pub present_select_callback: UnsyncCallback<usize>,
     pub present_enter_callback: UnsyncCallback<usize>,
@@ -42,12 +59,22 @@ pub struct HomeLogic {
 
     pub current_path: RwSignal<Option<String>>,
     pub keyboard_enabled: RwSignal<bool>,
+    /// 列表排序方式，按 `s` 循环切换；切换后清空目录与资源缓存并按新方式重新加载
+    pub sort_mode: RwSignal<SortMode>,
+
+    /// 命令面板：是否打开、输入的关键词、选中行与排序后的候选项
+    pub palette_open: RwSignal<bool>,
+    pub palette_query: RwSignal<String>,
+    pub palette_selected: RwSignal<usize>,
+    pub palette_results: Memo<Vec<PaletteEntry>>,
+    pub palette_choose_callback: UnsyncCallback<PaletteEntry>,
 }
 
 impl HomeLogic {
     pub fn new() -> Self {
         let path_cache: RwSignal<NodesCache> = RwSignal::new(HashMap::new());
         let assets_cache: RwSignal<AssetsCache> = RwSignal::new(HashMap::new());
+        let node_labels: RwSignal<NodeLabels> = RwSignal::new(HashMap::new());
         let current_path = RwSignal::new(None::<String>);
         let selected_index = RwSignal::new(None::<usize>);
         let detail_path = RwSignal::new(None::<String>);
@@ -57,11 +84,14 @@ impl HomeLogic {
         let detail_scroll_ref = NodeRef::<leptos::html::Div>::new();
         let present_scroll_ref = NodeRef::<leptos::html::Div>::new();
         let keyboard_enabled = RwSignal::new(true);
+        let sort_mode = RwSignal::new(SortMode::default());
+        let palette_open = RwSignal::new(false);
+        let palette_query = RwSignal::new(String::new());
+        let palette_selected = RwSignal::new(0usize);
+        // 后端搜索结果及其对应的关键词，关键词变化后旧结果不再展示
+        let palette_remote = RwSignal::new((String::new(), Vec::<SearchHit>::new()));
 
         let present_nodes = Memo::new({
-            let path_cache = path_cache.clone();
-            let assets_cache = assets_cache.clone();
-            let current_path = current_path.clone();
             move |_| {
                 let key = current_path.get().unwrap_or_else(|| ROOT_PATH.to_string());
                 let directories = path_cache
@@ -75,7 +105,7 @@ impl HomeLogic {
                         .unwrap_or_default()
                 };
 
-                let mut nodes = build_ui_nodes(&directories, &assets);
+                let mut nodes = build_ui_nodes(&directories, &assets, sort_mode.get());
                 let overview_label = "Overview".to_string();
                 let overview_node = UiNode {
                     id: format!("overview:{}", key),
@@ -88,6 +118,7 @@ impl HomeLogic {
                     },
                     raw_path: None,
                     has_children: false,
+                    file: None,
                 };
                 let mut combined = Vec::with_capacity(nodes.len() + 1);
                 combined.push(overview_node);
@@ -98,15 +129,14 @@ impl HomeLogic {
         });
 
         let overview_nodes = Memo::new({
-            let path_cache = path_cache.clone();
-            let current_path = current_path.clone();
             move |_| {
                 let cache = path_cache.get();
                 match current_path.get() {
                     Some(path) => {
                         let parent = parent_path(&path).unwrap_or_else(|| ROOT_PATH.to_string());
                         let directories = cache.get(&parent).cloned().unwrap_or_default();
-                        let snapshot = build_ui_nodes(&directories, &[] as &[AssetNode]);
+                        let snapshot =
+                            build_ui_nodes(&directories, &[] as &[AssetNode], sort_mode.get());
                         log_nodes("overview_nodes", &parent, &snapshot);
                         snapshot
                     }
@@ -117,21 +147,82 @@ impl HomeLogic {
                         directory_path: Some(ROOT_PATH.to_string()),
                         raw_path: Some("/".to_string()),
                         has_children: true,
+                        file: None,
                     }],
                 }
             }
         });
 
         let overview_highlight = Memo::new({
-            let current_path = current_path.clone();
             move |_| Some(current_path.get().unwrap_or_else(|| ROOT_PATH.to_string()))
         });
 
+        // 面包屑：优先从目录缓存中取显示名，深链接时回退到节点详情中的祖先链
+        let breadcrumbs: Memo<Vec<String>> = Memo::new({
+            move |_| {
+                let Some(path) = current_path.get() else {
+                    return Vec::new();
+                };
+                path_cache
+                    .with(|nodes| node_labels.with(|labels| level_labels(&path, nodes, labels)))
+            }
+        });
+
+        // 命令面板候选项：已缓存的节点与资源按模糊匹配得分排序，其后补充后端搜索的结果
+        let palette_results = Memo::new({
+            move |_| {
+                let query = palette_query.get();
+                let query = query.trim();
+                path_cache.with(|nodes| {
+                    node_labels.with(|labels| {
+                        let cached = assets_cache
+                            .with(|assets| cached_palette_entries(nodes, assets, labels));
+                        let remote = palette_remote.with(|(remote_query, hits)| {
+                            if remote_query == query {
+                                hits.iter()
+                                    .filter_map(|hit| search_hit_entry(hit, nodes, labels))
+                                    .collect()
+                            } else {
+                                Vec::new()
+                            }
+                        });
+                        rank_palette_entries(query, cached, remote)
+                    })
+                })
+            }
+        });
+
+        // 关键词停顿后向后端搜索，补充尚未缓存的节点
+        Effect::new(move |_| {
+            let query = palette_query.get().trim().to_string();
+            if query.chars().count() < PALETTE_SEARCH_MIN_CHARS {
+                return;
+            }
+            set_timeout(
+                move || {
+                    if palette_query.get_untracked().trim() != query {
+                        return;
+                    }
+                    spawn_local(async move {
+                        match search_nodes(&query, PALETTE_MAX_RESULTS as u32).await {
+                            Ok(hits) => {
+                                if palette_query.get_untracked().trim() == query {
+                                    palette_remote.set((query, hits));
+                                }
+                            }
+                            Err(e) => web_sys::console::log_2(
+                                &"[命令面板] 搜索失败".into(),
+                                &JsValue::from_str(&e.to_string()),
+                            ),
+                        }
+                    });
+                },
+                PALETTE_SEARCH_DELAY,
+            );
+        });
+
         // occupy placeholder for select_index closure, defined later
         let select_index_inner = Rc::new({
-            let selected_index = selected_index.clone();
-            let present_nodes = present_nodes.clone();
-            let present_scroll_ref = present_scroll_ref.clone();
             move |idx: usize| {
                 let len = present_nodes.get_untracked().len();
                 if len == 0 {
@@ -146,25 +237,24 @@ impl HomeLogic {
 
         // 主导航函数
         let navigate_to = Rc::new({
-            let path_cache = path_cache.clone();
-            let assets_cache = assets_cache.clone();
-            let current_path = current_path.clone();
-            let selected_index = selected_index.clone();
-            let detail_path = detail_path.clone();
-            let present_nodes = present_nodes.clone();
-            let present_scroll_ref = present_scroll_ref.clone();
             move |target: Option<String>, preferred_index: Option<usize>| {
-                let path_cache = path_cache.clone();
-                let assets_cache = assets_cache.clone();
-                let current_path = current_path.clone();
-                let selected_index = selected_index.clone();
-                let detail_path = detail_path.clone();
-                let present_nodes = present_nodes.clone();
-                let present_scroll_ref = present_scroll_ref.clone();
+                let path_cache = path_cache;
+                let assets_cache = assets_cache;
+                let node_labels = node_labels;
+                let current_path = current_path;
+                let selected_index = selected_index;
+                let detail_path = detail_path;
+                let present_nodes = present_nodes;
+                let present_scroll_ref = present_scroll_ref;
                 spawn_local(async move {
                     log_target("[导航] 请求", target.as_deref());
-                    if let Err(e) =
-                        ensure_path_and_ancestors(target.as_ref(), path_cache.clone()).await
+                    if let Err(e) = ensure_path_and_ancestors(
+                        target.as_ref(),
+                        path_cache,
+                        node_labels,
+                        sort_mode,
+                    )
+                    .await
                     {
                         web_sys::console::log_2(&"[导航] 加载失败".into(), &JsValue::from_str(&e));
                         return;
@@ -176,7 +266,7 @@ impl HomeLogic {
                         .unwrap_or_else(|| ROOT_PATH.to_string());
 
                     if let Some(ref path) = target {
-                        if let Err(e) = ensure_assets(path, assets_cache.clone()).await {
+                        if let Err(e) = ensure_assets(path, assets_cache, sort_mode).await {
                             web_sys::console::log_2(
                                 &"[导航] 资源加载失败".into(),
                                 &JsValue::from_str(&e),
@@ -186,6 +276,10 @@ impl HomeLogic {
 
                     current_path.set(target.clone());
 
+                    if let (Some(path), Some(depth)) = (target.clone(), PREFETCH_DEPTH) {
+                        prefetch_subtree(path, depth, path_cache, assets_cache, sort_mode);
+                    }
+
                     let nodes = present_nodes.get_untracked();
                     if nodes.is_empty() {
                         selected_index.set(None);
@@ -220,9 +314,6 @@ impl HomeLogic {
         });
 
         let move_selection = Rc::new({
-            let selected_index = selected_index.clone();
-            let present_nodes = present_nodes.clone();
-            let present_scroll_ref = present_scroll_ref.clone();
             move |delta: i32| {
                 let len = present_nodes.get_untracked().len() as i32;
                 if len == 0 {
@@ -241,8 +332,6 @@ impl HomeLogic {
         });
 
         let enter_selection = Rc::new({
-            let present_nodes = present_nodes.clone();
-            let selected_index = selected_index.clone();
             let navigate_to = navigate_to.clone();
             move || {
                 if let Some(idx) = selected_index.get_untracked() {
@@ -257,59 +346,26 @@ impl HomeLogic {
         });
 
         let go_back = Rc::new({
-            let current_path = current_path.clone();
-            let path_cache = path_cache.clone();
-            let assets_cache = assets_cache.clone();
             let navigate_to = navigate_to.clone();
             move || {
                 let current = current_path.get_untracked();
-                let path_cache = path_cache.clone();
-                let assets_cache = assets_cache.clone();
+                let path_cache = path_cache;
+                let assets_cache = assets_cache;
                 let navigate_to = navigate_to.clone();
                 spawn_local(async move {
                     match current {
                         Some(path) if !path.is_empty() => {
                             let parent =
                                 parent_path(&path).unwrap_or_else(|| ROOT_PATH.to_string());
-                            if let Err(e) = ensure_children(&parent, path_cache.clone()).await {
-                                web_sys::console::log_2(
+                            match row_in_parent(&parent, &path, path_cache, assets_cache, sort_mode)
+                                .await
+                            {
+                                Ok(row) => navigate_to(layer_target(parent), Some(row)),
+                                Err(e) => web_sys::console::log_2(
                                     &"[返回上级] 加载父级失败".into(),
                                     &JsValue::from_str(&e),
-                                );
-                                return;
-                            }
-                            if !parent.is_empty() {
-                                if let Err(e) = ensure_assets(&parent, assets_cache.clone()).await {
-                                    web_sys::console::log_2(
-                                        &"[返回上级] 加载资源失败".into(),
-                                        &JsValue::from_str(&e),
-                                    );
-                                }
+                                ),
                             }
-
-                            let directories = path_cache
-                                .with(|map| map.get(&parent).cloned())
-                                .unwrap_or_default();
-                            let assets = if parent.is_empty() {
-                                Vec::new()
-                            } else {
-                                assets_cache
-                                    .with(|map| map.get(&parent).cloned())
-                                    .unwrap_or_default()
-                            };
-                            let ui_nodes = build_ui_nodes(&directories, &assets);
-                            let idx = ui_nodes
-                                .iter()
-                                .position(|node| {
-                                    node.directory_path.as_deref() == Some(path.as_str())
-                                })
-                                .unwrap_or(0);
-                            let target = if parent.is_empty() {
-                                None
-                            } else {
-                                Some(parent)
-                            };
-                            navigate_to(target, Some(idx + 1));
                         }
                         Some(_) => navigate_to(None, None),
                         None => {}
@@ -318,19 +374,50 @@ impl HomeLogic {
             }
         });
 
+        // 切换排序方式：清空目录与资源缓存，按新方式重新加载当前层级，选中项保持不变
+        let cycle_sort = Rc::new({
+            let navigate_to = navigate_to.clone();
+            move || {
+                // 首行 Overview 不随排序移动，无需定位
+                let selected_id = selected_index
+                    .get_untracked()
+                    .filter(|idx| *idx > 0)
+                    .and_then(|idx| present_nodes.get_untracked().get(idx).map(|n| n.id.clone()));
+                let layer = current_path.get_untracked().unwrap_or_default();
+                sort_mode.update(|mode| *mode = mode.next());
+                path_cache.set(HashMap::new());
+                assets_cache.set(HashMap::new());
+
+                let path_cache = path_cache;
+                let assets_cache = assets_cache;
+                let navigate_to = navigate_to.clone();
+                spawn_local(async move {
+                    let row = match selected_id {
+                        Some(id) => row_in_parent(&layer, &id, path_cache, assets_cache, sort_mode)
+                            .await
+                            .ok(),
+                        None => None,
+                    };
+                    navigate_to(layer_target(layer), row);
+                });
+            }
+        });
+
         // Watch selected index -> detail path
         {
-            let present_nodes = present_nodes.clone();
-            let selected_index_signal = selected_index.clone();
-            let detail_path_signal = detail_path.clone();
-            let detail_items_signal = detail_items.clone();
-            let detail_loading_signal = detail_loading.clone();
-            let detail_error_signal = detail_error.clone();
-            let present_scroll_ref = present_scroll_ref.clone();
+            let selected_index_signal = selected_index;
+            let detail_path_signal = detail_path;
+            let detail_items_signal = detail_items;
+            let detail_loading_signal = detail_loading;
+            let detail_error_signal = detail_error;
+            // 每次重新计算 Detail 时递增；异步加载完成时代数已变说明用户已离开，丢弃结果
+            let detail_generation = Rc::new(Cell::new(0u64));
             Effect::new(move |_| {
                 let nodes = present_nodes.get();
                 let len = nodes.len();
                 let current_idx = selected_index_signal.get();
+                let generation = detail_generation.get() + 1;
+                detail_generation.set(generation);
 
                 let normalized_idx = if len == 0 {
                     None
@@ -357,7 +444,22 @@ impl HomeLogic {
                     Some(node) => match node.kind {
                         NodeKind::Overview => {
                             detail_error_signal.set(None);
-                            let overview_items = build_detail_items_from_nodes(&nodes[1..]);
+                            let mut overview_items = build_detail_items_from_nodes(&nodes[1..]);
+                            // 节点有排版文件时作为首项渲染；缓存只在此读取，不因预取而重新加载
+                            let layout_item = node.directory_path.as_deref().and_then(|path| {
+                                path_cache.with_untracked(|map| {
+                                    let parent = parent_path(path).unwrap_or_default();
+                                    map.get(&parent)?
+                                        .iter()
+                                        .find(|dir| dir.path == path)
+                                        .and_then(layout_detail_item)
+                                })
+                            });
+                            if let Some(item) = layout_item {
+                                overview_items.insert(0, item);
+                            }
+                            let node_assets =
+                                Rc::new(current_node_assets(current_path, assets_cache));
                             let markdown_indices: Vec<(usize, String)> = overview_items
                                 .iter()
                                 .enumerate()
@@ -383,15 +485,30 @@ impl HomeLogic {
                                 for (idx, path) in markdown_indices {
                                     let shared_items = shared_items.clone();
                                     let pending = pending.clone();
-                                    let detail_items_signal = detail_items_signal.clone();
-                                    let detail_loading_signal = detail_loading_signal.clone();
-                                    let detail_error_signal = detail_error_signal.clone();
+                                    let detail_items_signal = detail_items_signal;
+                                    let detail_loading_signal = detail_loading_signal;
+                                    let detail_error_signal = detail_error_signal;
+                                    let node_assets = node_assets.clone();
+                                    let detail_generation = detail_generation.clone();
                                     spawn_local(async move {
-                                        match fetch_text_asset(&path).await {
-                                            Ok(markdown) => {
+                                        let result = match fetch_text_asset(&path).await {
+                                            Ok(markdown) => Ok(render_markdown_file(
+                                                &markdown,
+                                                &path,
+                                                &node_assets,
+                                                path_cache,
+                                            )
+                                            .await),
+                                            Err(err) => Err(err),
+                                        };
+                                        if detail_generation.get() != generation {
+                                            return;
+                                        }
+                                        match result {
+                                            Ok(rendered) => {
                                                 let mut items = shared_items.borrow_mut();
                                                 if let Some(item) = items.get_mut(idx) {
-                                                    item.content = Some(render_markdown(&markdown));
+                                                    item.content = Some(rendered);
                                                 }
                                                 detail_items_signal.set(items.clone());
                                             }
@@ -430,15 +547,30 @@ impl HomeLogic {
                             detail_items_signal.set(Vec::new());
 
                             if let Some(path) = node.raw_path.clone() {
-                                let detail_loading_signal = detail_loading_signal.clone();
-                                let detail_error_signal = detail_error_signal.clone();
-                                let detail_items_signal = detail_items_signal.clone();
+                                let detail_loading_signal = detail_loading_signal;
+                                let detail_error_signal = detail_error_signal;
+                                let detail_items_signal = detail_items_signal;
                                 let item = detail_item_from_ui_node(node);
+                                let node_assets = current_node_assets(current_path, assets_cache);
+                                let detail_generation = detail_generation.clone();
                                 spawn_local(async move {
-                                    match fetch_text_asset(&path).await {
+                                    let result = match fetch_text_asset(&path).await {
+                                        Ok(content) => Ok(render_markdown_file(
+                                            &content,
+                                            &path,
+                                            &node_assets,
+                                            path_cache,
+                                        )
+                                        .await),
+                                        Err(err) => Err(err),
+                                    };
+                                    if detail_generation.get() != generation {
+                                        return;
+                                    }
+                                    match result {
                                         Ok(content) => {
                                             let mut rendered = item;
-                                            rendered.content = Some(render_markdown(&content));
+                                            rendered.content = Some(content);
                                             detail_items_signal.set(vec![rendered]);
                                             detail_loading_signal.set(false);
                                             detail_error_signal.set(None);
@@ -468,24 +600,19 @@ impl HomeLogic {
 
         // Detail panel data loader
         {
-            let detail_path_signal = detail_path.clone();
-            let path_cache = path_cache.clone();
-            let assets_cache = assets_cache.clone();
-            let detail_items = detail_items.clone();
-            let detail_loading = detail_loading.clone();
-            let detail_error = detail_error.clone();
+            let detail_path_signal = detail_path;
             Effect::new(move |_| {
                 if let Some(path) = detail_path_signal.get() {
                     detail_loading.set(true);
                     detail_error.set(None);
-                    let path_cache = path_cache.clone();
-                    let assets_cache = assets_cache.clone();
-                    let detail_items = detail_items.clone();
-                    let detail_loading = detail_loading.clone();
-                    let detail_error = detail_error.clone();
+                    let path_cache = path_cache;
+                    let assets_cache = assets_cache;
+                    let detail_items = detail_items;
+                    let detail_loading = detail_loading;
+                    let detail_error = detail_error;
                     spawn_local(async move {
-                        let dirs_result = ensure_children(&path, path_cache.clone()).await;
-                        let assets_result = ensure_assets(&path, assets_cache.clone()).await;
+                        let dirs_result = ensure_children(&path, path_cache, sort_mode).await;
+                        let assets_result = ensure_assets(&path, assets_cache, sort_mode).await;
                         if let Err(e) = dirs_result {
                             detail_error.set(Some(e));
                             detail_items.set(Vec::new());
@@ -513,26 +640,20 @@ impl HomeLogic {
         // 初始载入
         {
             let initialized = Rc::new(Cell::new(false));
-            let path_cache = path_cache.clone();
-            let current_path = current_path.clone();
-            let selected_index = selected_index.clone();
-            let detail_path = detail_path.clone();
-            let present_nodes = present_nodes.clone();
-            let present_scroll_ref = present_scroll_ref.clone();
             Effect::new(move |_| {
                 if initialized.get() {
                     return;
                 }
                 initialized.set(true);
 
-                let path_cache = path_cache.clone();
-                let current_path = current_path.clone();
-                let selected_index = selected_index.clone();
-                let detail_path = detail_path.clone();
-                let present_nodes = present_nodes.clone();
+                let path_cache = path_cache;
+                let current_path = current_path;
+                let selected_index = selected_index;
+                let detail_path = detail_path;
+                let present_nodes = present_nodes;
 
                 spawn_local(async move {
-                    if let Err(e) = ensure_children(ROOT_PATH, path_cache.clone()).await {
+                    if let Err(e) = ensure_children(ROOT_PATH, path_cache, sort_mode).await {
                         web_sys::console::log_2(
                             &"[初始化] 根节点加载失败".into(),
                             &JsValue::from_str(&e),
@@ -562,6 +683,47 @@ impl HomeLogic {
             });
         }
 
+        // Markdown 中的 `node:` 链接：拦截点击交给 navigate_to；
+        // 带修饰键或非左键点击时保留浏览器默认行为（如在新标签页打开 `?path=` 链接）
+        {
+            let navigate_to = navigate_to.clone();
+            let handle_node_link_click =
+                Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+                    if event.button() != 0
+                        || event.ctrl_key()
+                        || event.meta_key()
+                        || event.shift_key()
+                        || event.alt_key()
+                    {
+                        return;
+                    }
+                    let Some(link) = event
+                        .target()
+                        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                        .and_then(|element| element.closest("a[data-node]").ok().flatten())
+                    else {
+                        return;
+                    };
+                    event.prevent_default();
+                    let dead = link
+                        .get_attribute("class")
+                        .is_some_and(|class| class.contains("dead-link"));
+                    if let (false, Some(path)) = (dead, link.get_attribute("data-node")) {
+                        navigate_to(Some(path), None);
+                    }
+                }) as Box<dyn FnMut(web_sys::MouseEvent)>);
+
+            if let Some(window) = web_sys::window() {
+                if let Err(err) = window.add_event_listener_with_callback(
+                    "click",
+                    handle_node_link_click.as_ref().unchecked_ref(),
+                ) {
+                    web_sys::console::log_2(&"[事件] 注册链接监听失败".into(), &err);
+                }
+            }
+            handle_node_link_click.forget();
+        }
+
         // 注册键盘事件
         {
             let listener_added = Rc::new(Cell::new(false));
@@ -569,8 +731,9 @@ impl HomeLogic {
             let move_selection_cb = move_selection.clone();
             let enter_selection_cb = enter_selection.clone();
             let go_back_cb = go_back.clone();
-            let detail_scroll_ref_clone = detail_scroll_ref.clone();
-            let keyboard_enabled_signal = keyboard_enabled.clone();
+            let cycle_sort_cb = cycle_sort.clone();
+            let detail_scroll_ref_clone = detail_scroll_ref;
+            let keyboard_enabled_signal = keyboard_enabled;
             Effect::new(move |_| {
                 if listener_added_ref.get() {
                     return;
@@ -580,10 +743,11 @@ impl HomeLogic {
                 let move_selection = move_selection_cb.clone();
                 let enter_selection = enter_selection_cb.clone();
                 let go_back = go_back_cb.clone();
+                let cycle_sort = cycle_sort_cb.clone();
                 let detail_scroll_ref = detail_scroll_ref_clone;
-                let present_scroll_ref = present_scroll_ref.clone();
+                let present_scroll_ref = present_scroll_ref;
 
-                let keyboard_enabled_inner = keyboard_enabled_signal.clone();
+                let keyboard_enabled_inner = keyboard_enabled_signal;
                 let handle_global_keydown =
                     Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
                         if !keyboard_enabled_inner.get_untracked() {
@@ -601,13 +765,26 @@ impl HomeLogic {
                             }
                         }
 
+                        // 面板打开时按键由面板自己的监听处理
+                        if palette_open.get_untracked() {
+                            return;
+                        }
+                        if keyboard::is_palette_shortcut(&event) {
+                            event.prevent_default();
+                            palette_query.set(String::new());
+                            palette_selected.set(0);
+                            palette_open.set(true);
+                            return;
+                        }
+
                         keyboard::handle_keyboard_navigation(
                             &event,
                             move_selection.clone(),
                             enter_selection.clone(),
                             go_back.clone(),
-                            detail_scroll_ref.clone(),
-                            present_scroll_ref.clone(),
+                            cycle_sort.clone(),
+                            detail_scroll_ref,
+                            present_scroll_ref,
                         );
                     })
                         as Box<dyn FnMut(web_sys::KeyboardEvent)>);
@@ -617,10 +794,7 @@ impl HomeLogic {
                         "keydown",
                         handle_global_keydown.as_ref().unchecked_ref(),
                     ) {
-                        web_sys::console::log_2(
-                            &"[事件] 注册键盘监听失败".into(),
-                            &JsValue::from(err),
-                        );
+                        web_sys::console::log_2(&"[事件] 注册键盘监听失败".into(), &err);
                     }
                 }
                 handle_global_keydown.forget();
@@ -629,7 +803,6 @@ impl HomeLogic {
 
         // 调试：current_path 变化日志
         {
-            let current_path = current_path.clone();
             Effect::new(move |_| {
                 let path = current_path.get();
                 log_target("[路径] 当前路径", path.as_deref());
@@ -642,7 +815,6 @@ impl HomeLogic {
         };
 
         let present_enter_callback = {
-            let present_nodes = present_nodes.clone();
             let navigate_to = navigate_to.clone();
             UnsyncCallback::new(move |idx: usize| {
                 if let Some(node) = present_nodes.get_untracked().get(idx) {
@@ -655,12 +827,10 @@ impl HomeLogic {
 
         let overview_select_callback = {
             let navigate_to = navigate_to.clone();
-            let path_cache = path_cache.clone();
-            let assets_cache = assets_cache.clone();
             UnsyncCallback::new(move |target: Option<String>| {
                 let navigate_to = navigate_to.clone();
-                let path_cache = path_cache.clone();
-                let assets_cache = assets_cache.clone();
+                let path_cache = path_cache;
+                let assets_cache = assets_cache;
 
                 spawn_local(async move {
                     match target {
@@ -669,51 +839,48 @@ impl HomeLogic {
                         Some(path) => {
                             let parent =
                                 parent_path(&path).unwrap_or_else(|| ROOT_PATH.to_string());
-                            if let Err(e) = ensure_children(&parent, path_cache.clone()).await {
-                                web_sys::console::log_2(
+                            match row_in_parent(&parent, &path, path_cache, assets_cache, sort_mode)
+                                .await
+                            {
+                                Ok(row) => navigate_to(layer_target(parent), Some(row)),
+                                Err(e) => web_sys::console::log_2(
                                     &"[OverviewColumn] 加载父级失败".into(),
                                     &JsValue::from_str(&e),
-                                );
-                                return;
+                                ),
                             }
-                            if !parent.is_empty() {
-                                if let Err(e) = ensure_assets(&parent, assets_cache.clone()).await {
-                                    web_sys::console::log_2(
-                                        &"[OverviewColumn] 加载资源失败".into(),
-                                        &JsValue::from_str(&e),
-                                    );
-                                }
-                            }
-
-                            let directories = path_cache
-                                .with(|map| map.get(&parent).cloned())
-                                .unwrap_or_default();
-                            let assets = if parent.is_empty() {
-                                Vec::new()
-                            } else {
-                                assets_cache
-                                    .with(|map| map.get(&parent).cloned())
-                                    .unwrap_or_default()
-                            };
-                            let ui_nodes = build_ui_nodes(&directories, &assets);
-                            let idx = ui_nodes
-                                .iter()
-                                .position(|node| {
-                                    node.directory_path.as_deref() == Some(path.as_str())
-                                })
-                                .unwrap_or(0);
-                            let target_layer = if parent.is_empty() {
-                                None
-                            } else {
-                                Some(parent)
-                            };
-                            navigate_to(target_layer, Some(idx + 1));
                         }
                     }
                 });
             })
         };
 
+        // 命令面板选中项：导航到其所在层级并选中该行，与在 Overview 栏点击节点一致
+        let palette_choose_callback = {
+            let navigate_to = navigate_to.clone();
+            UnsyncCallback::new(move |entry: PaletteEntry| {
+                let navigate_to = navigate_to.clone();
+                let path_cache = path_cache;
+                let assets_cache = assets_cache;
+                spawn_local(async move {
+                    match row_in_parent(
+                        &entry.parent,
+                        &entry.id,
+                        path_cache,
+                        assets_cache,
+                        sort_mode,
+                    )
+                    .await
+                    {
+                        Ok(row) => navigate_to(layer_target(entry.parent), Some(row)),
+                        Err(e) => web_sys::console::log_2(
+                            &"[命令面板] 加载所在层级失败".into(),
+                            &JsValue::from_str(&e),
+                        ),
+                    }
+                });
+            })
+        };
+
         let mobile_navigate_callback = {
             let navigate_to = navigate_to.clone();
             UnsyncCallback::new(move |target: Option<String>| {
@@ -730,6 +897,7 @@ impl HomeLogic {
             present_nodes,
             overview_nodes,
             overview_highlight,
+            breadcrumbs,
             present_select_callback,
             present_enter_callback,
             overview_select_callback,
@@ -738,21 +906,37 @@ impl HomeLogic {
             present_scroll_ref,
             current_path,
             keyboard_enabled,
+            sort_mode,
+            palette_open,
+            palette_query,
+            palette_selected,
+            palette_results,
+            palette_choose_callback,
         }
     }
 }
 
-async fn ensure_children(path: &str, cache: RwSignal<NodesCache>) -> Result<(), String> {
+async fn ensure_children(
+    path: &str,
+    cache: RwSignal<NodesCache>,
+    sort: RwSignal<SortMode>,
+) -> Result<(), String> {
     if cache.with(|map| map.contains_key(path)) {
         return Ok(());
     }
 
+    let mode = sort.get_untracked();
     let data = if path.is_empty() {
-        get_root_directories().await
+        get_root_directories(mode).await
     } else {
-        get_child_directories(path).await
-    }?;
+        get_child_directories(path, mode).await
+    }
+    .map_err(|e| e.to_string())?;
 
+    // 请求期间切换了排序方式：缓存已清空并按新方式重新加载，丢弃旧顺序的结果
+    if sort.get_untracked() != mode {
+        return Ok(());
+    }
     cache.update(|map| {
         map.insert(path.to_string(), data);
     });
@@ -760,13 +944,23 @@ async fn ensure_children(path: &str, cache: RwSignal<NodesCache>) -> Result<(),
     Ok(())
 }
 
-async fn ensure_assets(path: &str, cache: RwSignal<AssetsCache>) -> Result<(), String> {
+async fn ensure_assets(
+    path: &str,
+    cache: RwSignal<AssetsCache>,
+    sort: RwSignal<SortMode>,
+) -> Result<(), String> {
     if path.is_empty() || cache.with(|map| map.contains_key(path)) {
         return Ok(());
     }
 
-    let data = get_node_assets(path).await?;
+    let mode = sort.get_untracked();
+    let data = get_node_assets(path, mode)
+        .await
+        .map_err(|e| e.to_string())?;
 
+    if sort.get_untracked() != mode {
+        return Ok(());
+    }
     cache.update(|map| {
         map.insert(path.to_string(), data);
     });
@@ -774,83 +968,342 @@ async fn ensure_assets(path: &str, cache: RwSignal<AssetsCache>) -> Result<(), S
     Ok(())
 }
 
+/// 确保目标路径及其父级列表已加载。
+///
+/// 正常逐级导航时父级列表已在缓存中；深链接打开时父级尚未加载，
+/// 此时调用 `/nodes/detail/{path}` 一次取回同级节点与祖先链，不再逐级请求每个祖先的子目录。
 async fn ensure_path_and_ancestors(
     path: Option<&String>,
     cache: RwSignal<NodesCache>,
+    labels: RwSignal<NodeLabels>,
+    sort: RwSignal<SortMode>,
 ) -> Result<(), String> {
-    ensure_children(ROOT_PATH, cache.clone()).await?;
+    ensure_children(ROOT_PATH, cache, sort).await?;
 
-    if let Some(path) = path {
-        for level in split_levels(path) {
-            if let Some(parent) = parent_path(&level) {
-                ensure_children(&parent, cache.clone()).await?;
+    let Some(path) = path else {
+        return Ok(());
+    };
+
+    let parent = parent_path(path).unwrap_or_else(|| ROOT_PATH.to_string());
+    if !cache.with(|map| map.contains_key(&parent)) {
+        let mode = sort.get_untracked();
+        let detail = get_node_detail(path, mode)
+            .await
+            .map_err(|e| e.to_string())?;
+        labels.update(|map| {
+            for node in detail.ancestors.iter().chain(std::iter::once(&detail.node)) {
+                map.insert(node.path.clone(), node.display_name().to_string());
             }
-            ensure_children(&level, cache.clone()).await?;
+        });
+        if sort.get_untracked() == mode {
+            cache.update(|map| {
+                map.insert(parent, detail.siblings);
+            });
         }
     }
 
-    Ok(())
+    ensure_children(path, cache, sort).await
 }
 
-fn build_ui_nodes(directories: &[DirectoryNode], assets: &[AssetNode]) -> Vec<UiNode> {
-    let mut nodes: Vec<UiNode> = directories
+/// 在后台预取以 `path` 为根的子树并写入缓存，使分支内的 hjkl 导航不再逐层等待请求。
+/// 子节点列表均已缓存时跳过；已有的缓存项不会被覆盖。
+fn prefetch_subtree(
+    path: String,
+    depth: u32,
+    path_cache: RwSignal<NodesCache>,
+    assets_cache: RwSignal<AssetsCache>,
+    sort: RwSignal<SortMode>,
+) {
+    let needs_fetch = path_cache.with_untracked(|map| match map.get(&path) {
+        Some(children) => children.iter().any(|child| !map.contains_key(&child.path)),
+        None => true,
+    });
+    if !needs_fetch {
+        return;
+    }
+
+    let mode = sort.get_untracked();
+    spawn_local(async move {
+        match get_subtree(&path, depth, mode).await {
+            // 请求期间切换了排序方式，丢弃旧顺序的子树
+            Ok(_) if sort.get_untracked() != mode => {}
+            Ok(subtree) => {
+                let mut nodes = NodesCache::new();
+                let mut assets = AssetsCache::new();
+                collect_tree(&subtree.tree, &mut nodes, &mut assets);
+                path_cache.update(|map| {
+                    for (key, value) in nodes {
+                        map.entry(key).or_insert(value);
+                    }
+                });
+                assets_cache.update(|map| {
+                    for (key, value) in assets {
+                        map.entry(key).or_insert(value);
+                    }
+                });
+            }
+            Err(e) => {
+                web_sys::console::log_2(
+                    &"[预取] 子树加载失败".into(),
+                    &JsValue::from_str(&e.to_string()),
+                );
+            }
+        }
+    });
+}
+
+/// 把子树展开为缓存项；`children` 为 `None` 的节点只写入资源，不写入子节点列表
+fn collect_tree(tree: &TreeNode, nodes: &mut NodesCache, assets: &mut AssetsCache) {
+    assets.insert(tree.node.path.clone(), tree.assets.clone());
+    if let Some(children) = &tree.children {
+        nodes.insert(
+            tree.node.path.clone(),
+            children.iter().map(|child| child.node.clone()).collect(),
+        );
+        for child in children {
+            collect_tree(child, nodes, assets);
+        }
+    }
+}
+
+/// 加载 `parent` 层级的子节点与资源，返回 `id` 对应行在 Present 栏中的序号
+/// （首行为 Overview，故加一）；找不到时返回首个子项所在行
+async fn row_in_parent(
+    parent: &str,
+    id: &str,
+    path_cache: RwSignal<NodesCache>,
+    assets_cache: RwSignal<AssetsCache>,
+    sort: RwSignal<SortMode>,
+) -> Result<usize, String> {
+    ensure_children(parent, path_cache, sort).await?;
+    if let Err(e) = ensure_assets(parent, assets_cache, sort).await {
+        web_sys::console::log_2(&"[定位] 加载资源失败".into(), &JsValue::from_str(&e));
+    }
+
+    let directories = path_cache
+        .with(|map| map.get(parent).cloned())
+        .unwrap_or_default();
+    let assets = if parent.is_empty() {
+        Vec::new()
+    } else {
+        assets_cache
+            .with(|map| map.get(parent).cloned())
+            .unwrap_or_default()
+    };
+    let idx = build_ui_nodes(&directories, &assets, sort.get_untracked())
         .iter()
-        .map(|dir| UiNode {
+        .position(|node| node.id == id)
+        .unwrap_or(0);
+    Ok(idx + 1)
+}
+
+/// 层级路径转为 `navigate_to` 的目标，根层级为 `None`
+fn layer_target(path: String) -> Option<String> {
+    (!path.is_empty()).then_some(path)
+}
+
+/// 路径上各层级的显示名：优先取父级目录缓存中的名称，其次是深链接时记录的祖先名，
+/// 最后回退到路径末段
+fn level_labels(path: &str, nodes: &NodesCache, labels: &NodeLabels) -> Vec<String> {
+    split_levels(path)
+        .into_iter()
+        .map(|level| {
+            let parent = parent_path(&level).unwrap_or_default();
+            nodes
+                .get(&parent)
+                .and_then(|dirs| {
+                    dirs.iter()
+                        .find(|dir| dir.path == level)
+                        .map(|dir| dir.display_name().to_string())
+                })
+                .or_else(|| labels.get(&level).cloned())
+                .unwrap_or_else(|| level.rsplit('.').next().unwrap_or_default().to_string())
+        })
+        .collect()
+}
+
+/// 已缓存的目录与资源转为命令面板候选项
+fn cached_palette_entries(
+    nodes: &NodesCache,
+    assets: &AssetsCache,
+    labels: &NodeLabels,
+) -> Vec<PaletteEntry> {
+    let directories = nodes.iter().flat_map(|(parent, dirs)| {
+        let breadcrumb = level_labels(parent, nodes, labels);
+        dirs.iter().map(move |dir| PaletteEntry {
             id: dir.path.clone(),
-            label: dir.raw_filename.clone(),
+            label: dir.display_name().to_string(),
             kind: NodeKind::Directory,
-            directory_path: Some(dir.path.clone()),
-            raw_path: Some(dir.path.clone()),
-            has_children: dir.has_subnodes,
+            parent: parent.clone(),
+            breadcrumb: breadcrumb.clone(),
+        })
+    });
+    let files = assets.iter().flat_map(|(parent, files)| {
+        let breadcrumb = level_labels(parent, nodes, labels);
+        files.iter().map(move |asset| PaletteEntry {
+            id: asset.file_path.clone(),
+            label: asset.raw_filename.clone(),
+            kind: asset.node_kind(),
+            parent: parent.clone(),
+            breadcrumb: breadcrumb.clone(),
+        })
+    });
+    directories.chain(files).collect()
+}
+
+/// 后端搜索命中项转为命令面板候选项
+fn search_hit_entry(
+    hit: &SearchHit,
+    nodes: &NodesCache,
+    labels: &NodeLabels,
+) -> Option<PaletteEntry> {
+    let (id, label, kind, parent) = match hit {
+        SearchHit::Directory { node, .. } => (
+            node.path.clone(),
+            node.display_name().to_string(),
+            NodeKind::Directory,
+            parent_path(&node.path)?,
+        ),
+        SearchHit::Asset { node, .. } => (
+            node.file_path.clone(),
+            node.raw_filename.clone(),
+            node.node_kind(),
+            asset_owner_path(&node.file_path)?.to_string(),
+        ),
+    };
+    Some(PaletteEntry {
+        breadcrumb: level_labels(&parent, nodes, labels),
+        id,
+        label,
+        kind,
+        parent,
+    })
+}
+
+/// 排序候选项：名称的模糊匹配优先于面包屑路径的匹配，同分时按路径排列；
+/// 后端命中但模糊匹配不上的项（如正文命中）按后端顺序排在最后。关键词为空时按路径排列缓存项
+fn rank_palette_entries(
+    query: &str,
+    cached: Vec<PaletteEntry>,
+    remote: Vec<PaletteEntry>,
+) -> Vec<PaletteEntry> {
+    // 名称匹配的额外得分，使其排在仅路径匹配的项之前
+    const LABEL_BONUS: i32 = 100;
+    let mut seen = HashSet::new();
+    let mut matched: Vec<(i32, PaletteEntry)> = Vec::new();
+    let mut content_hits: Vec<PaletteEntry> = Vec::new();
+    let candidates = cached
+        .into_iter()
+        .map(|entry| (entry, false))
+        .chain(remote.into_iter().map(|entry| (entry, true)));
+    for (entry, from_server) in candidates {
+        if !seen.insert(entry.id.clone()) {
+            continue;
+        }
+        let full_path = format!("{} {}", entry.breadcrumb.join(" "), entry.label);
+        let score = fuzzy_score(query, &entry.label)
+            .map(|score| score + LABEL_BONUS)
+            .or_else(|| fuzzy_score(query, &full_path));
+        match score {
+            Some(score) => matched.push((score, entry)),
+            None if from_server => content_hits.push(entry),
+            None => {}
+        }
+    }
+
+    matched.sort_by(|(score_a, a), (score_b, b)| {
+        score_b
+            .cmp(score_a)
+            .then_with(|| (&a.breadcrumb, &a.label).cmp(&(&b.breadcrumb, &b.label)))
+    });
+    matched
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .chain(content_hits)
+        .take(PALETTE_MAX_RESULTS)
+        .collect()
+}
+
+fn build_ui_nodes(
+    directories: &[DirectoryNode],
+    assets: &[AssetNode],
+    sort: SortMode,
+) -> Vec<UiNode> {
+    let mut keyed: Vec<(&str, UiNode)> = directories
+        .iter()
+        .map(|dir| {
+            (
+                dir.sort_key.as_str(),
+                UiNode {
+                    id: dir.path.clone(),
+                    label: dir.display_name().to_string(),
+                    kind: NodeKind::Directory,
+                    directory_path: Some(dir.path.clone()),
+                    raw_path: Some(dir.path.clone()),
+                    has_children: dir.has_subnodes,
+                    file: None,
+                },
+            )
         })
         .collect();
 
-    nodes.extend(assets.iter().map(|asset| UiNode {
-        id: asset.file_path.clone(),
-        label: asset.raw_filename.clone(),
-        kind: classify_asset_kind(&asset.raw_filename),
-        directory_path: None,
-        raw_path: Some(asset.raw_path.clone()),
-        has_children: false,
+    keyed.extend(assets.iter().map(|asset| {
+        (
+            asset.sort_key.as_str(),
+            UiNode {
+                id: asset.file_path.clone(),
+                label: asset.raw_filename.clone(),
+                kind: asset.node_kind(),
+                directory_path: None,
+                raw_path: Some(asset.raw_path.clone()),
+                has_children: false,
+                file: Some(FileInfo::from_asset(asset)),
+            },
+        )
     }));
 
-    nodes.sort_by_key(|node| node.label.to_ascii_lowercase());
-    nodes
+    // 默认排序下目录与资源按后端给出的排序键合并，稳定排序保留同键条目的接口顺序；
+    // 按名称或年份排序时保留接口顺序，目录在前、资源在后
+    if sort == SortMode::Order {
+        keyed.sort_by(|a, b| a.0.cmp(b.0));
+    }
+    keyed.into_iter().map(|(_, node)| node).collect()
 }
 
 fn build_detail_items_for_path(
     directories: &[DirectoryNode],
     assets: &[AssetNode],
 ) -> Vec<DetailItem> {
+    // 目录与资源均保持接口返回的顺序（已按排序键排列）
     let mut dir_items: Vec<DetailItem> = directories
         .iter()
         .map(|dir| DetailItem {
             id: dir.path.clone(),
-            label: dir.raw_filename.clone(),
+            label: dir.display_name().to_string(),
             kind: NodeKind::Directory,
             directory_path: Some(dir.path.clone()),
             raw_path: None,
             has_children: dir.has_subnodes,
             content: None,
             display_as_entry: true,
+            file: None,
         })
         .collect();
-    dir_items.sort_by_key(|item| item.label.to_ascii_lowercase());
 
-    let mut asset_items: Vec<DetailItem> = assets
+    let asset_items: Vec<DetailItem> = assets
         .iter()
         .map(|asset| DetailItem {
             id: asset.file_path.clone(),
             label: asset.raw_filename.clone(),
-            kind: classify_asset_kind(&asset.raw_filename),
+            kind: asset.node_kind(),
             directory_path: None,
             raw_path: Some(asset.raw_path.clone()),
             has_children: false,
             content: None,
             display_as_entry: false,
+            file: Some(FileInfo::from_asset(asset)),
         })
         .collect();
-    asset_items.sort_by_key(|item| item.label.to_ascii_lowercase());
 
     dir_items.extend(asset_items);
     dir_items
@@ -872,13 +1325,26 @@ fn build_detail_items_from_nodes(nodes: &[UiNode]) -> Vec<DetailItem> {
         }
     }
 
-    dir_items.sort_by_key(|item| item.label.to_ascii_lowercase());
-    asset_items.sort_by_key(|item| item.label.to_ascii_lowercase());
-
     dir_items.extend(asset_items);
     dir_items
 }
 
+/// 节点排版文件对应的 Detail 项，内容在加载后由 [`render_markdown_file`] 填充
+fn layout_detail_item(dir: &DirectoryNode) -> Option<DetailItem> {
+    let layout_path = dir.layout_path.clone()?;
+    Some(DetailItem {
+        id: format!("layout:{}", dir.path),
+        label: dir.display_name().to_string(),
+        kind: NodeKind::Markdown,
+        directory_path: None,
+        raw_path: Some(layout_path),
+        has_children: false,
+        content: None,
+        display_as_entry: false,
+        file: None,
+    })
+}
+
 fn detail_item_from_ui_node(node: &UiNode) -> DetailItem {
     DetailItem {
         id: node.id.clone(),
@@ -889,18 +1355,7 @@ fn detail_item_from_ui_node(node: &UiNode) -> DetailItem {
         has_children: node.has_children,
         content: None,
         display_as_entry: matches!(node.kind, NodeKind::Directory),
-    }
-}
-
-fn classify_asset_kind(filename: &str) -> NodeKind {
-    let ext = filename.rsplit('.').next().map(|s| s.to_ascii_lowercase());
-    match ext.as_deref() {
-        Some("md") | Some("markdown") => NodeKind::Markdown,
-        Some("mp4") | Some("mov") | Some("webm") | Some("m4v") | Some("ogg") => NodeKind::Video,
-        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("bmp") | Some("svg")
-        | Some("webp") | Some("ico") => NodeKind::Image,
-        Some("pdf") => NodeKind::Pdf,
-        _ => NodeKind::Other,
+        file: node.file.clone(),
     }
 }
 
@@ -918,15 +1373,69 @@ fn scroll_selected_into_view(container_ref: &NodeRef<leptos::html::Div>, index:
     }
 }
 
+/// 当前节点的资源列表（只读缓存，不建立响应式依赖）
+fn current_node_assets(
+    current_path: RwSignal<Option<String>>,
+    assets_cache: RwSignal<AssetsCache>,
+) -> Vec<AssetNode> {
+    current_path
+        .get_untracked()
+        .and_then(|path| assets_cache.with_untracked(|map| map.get(&path).cloned()))
+        .unwrap_or_default()
+}
+
+/// 渲染 markdown 文件：先确认其中 `node:` 链接的目标是否存在，再解析相对路径并标记失效链接
+async fn render_markdown_file(
+    raw: &str,
+    source_path: &str,
+    assets: &[AssetNode],
+    path_cache: RwSignal<NodesCache>,
+) -> String {
+    let mut missing_nodes = HashSet::new();
+    for path in node_links(raw) {
+        if !node_exists(&path, path_cache).await {
+            missing_nodes.insert(path);
+        }
+    }
+    render_markdown(
+        raw,
+        &LinkContext {
+            source_path,
+            assets,
+            missing_nodes: &missing_nodes,
+        },
+    )
+}
+
+/// 节点是否存在：已缓存的列表中找得到即存在，否则向后端查询；
+/// 只有明确的 404 才算不存在，网络等其它错误不把链接标为失效
+async fn node_exists(path: &str, path_cache: RwSignal<NodesCache>) -> bool {
+    let parent = parent_path(path).unwrap_or_default();
+    let cached = path_cache.with_untracked(|map| {
+        map.get(&parent)
+            .is_some_and(|dirs| dirs.iter().any(|dir| dir.path == path))
+    });
+    if cached {
+        return true;
+    }
+    match get_node_detail(path, SortMode::default()).await {
+        Ok(_) => true,
+        Err(err) => !err.is_not_found(),
+    }
+}
+
+/// 读取文本资源；非 2xx 响应（如 404 页面）按错误处理，不当作正文渲染
 async fn fetch_text_asset(path: &str) -> Result<String, String> {
     let url = asset_to_url(path);
-    Request::get(&url)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .text()
-        .await
-        .map_err(|e| e.to_string())
+    let response = Request::get(&url).send().await.map_err(|e| e.to_string())?;
+    if !response.ok() {
+        return Err(format!(
+            "无法加载 {path}: HTTP {} {}",
+            response.status(),
+            response.status_text()
+        ));
+    }
+    response.text().await.map_err(|e| e.to_string())
 }
 
 fn asset_to_url(path: &str) -> String {
@@ -937,23 +1446,12 @@ fn asset_to_url(path: &str) -> String {
         let trimmed = normalized.trim_start_matches('/');
         let origin = web_sys::window()
             .and_then(|w| w.location().origin().ok())
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
         let base = origin.trim_end_matches('/');
-        format!("{}/resource/{}", base, trimmed)
+        format!("{}/resource/{}", base, encode_path_segments(trimmed))
     }
 }
 
-fn render_markdown(raw: &str) -> String {
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_TABLES);
-    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
-    let parser = Parser::new_ext(raw, options);
-
-    let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
-    html_output
-}
-
 fn log_nodes(label: &str, path: &str, nodes: &[UiNode]) {
     if let Ok(serialized) = serde_json::to_string(nodes) {
         web_sys::console::log_3(
@@ -7,11 +7,9 @@ use leptos::prelude::*;
 #[component]
 pub fn MobileNavigator(logic: HomeLogic) -> impl IntoView {
     let (pending_path, set_pending_path) = signal::<Option<String>>(None);
-    let navigate = logic.mobile_navigate_callback.clone();
+    let navigate = logic.mobile_navigate_callback;
 
     Effect::new({
-        let pending_path = pending_path.clone();
-        let set_pending_path = set_pending_path.clone();
         move |_| {
             if let Some(target) = pending_path.get() {
                 if target.is_empty() {
@@ -27,12 +25,15 @@ pub fn MobileNavigator(logic: HomeLogic) -> impl IntoView {
     view! {
         <div class="flex min-h-[100dvh] bg-black text-white">
             <div class="flex flex-col w-full min-h-[100dvh]">
-                <MobileHeader current_path=logic.current_path.clone() set_pending_path=set_pending_path.clone() />
+                <MobileHeader
+                    current_path=logic.current_path
+                    breadcrumbs=logic.breadcrumbs
+                    set_pending_path=set_pending_path
+                />
                 <div class="relative flex-1 min-h-0 overflow-hidden">
                     <Detail
                         logic=logic.clone()
                         on_node_click=Callback::new({
-                            let set_pending_path = set_pending_path.clone();
                             move |value| set_pending_path.set(value)
                         })
                     />
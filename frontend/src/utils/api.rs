@@ -1,40 +1,96 @@
 use gloo_net::http::Request;
+use serde::de::DeserializeOwned;
 
-use crate::utils::types::{AssetNode, AssetsResponse, DirectoriesResponse, DirectoryNode};
+use crate::utils::types::{
+    ApiError, AssetNode, AssetsResponse, DirectoriesResponse, DirectoryNode, ErrorResponse,
+    NodeDetailResponse, SearchHit, SearchResponse, SortMode, SubtreeResponse,
+};
 
-/// 获取根目录列表
-pub async fn get_root_directories() -> Result<Vec<DirectoryNode>, String> {
-    match Request::get("/api/nodes/root").send().await {
-        Ok(resp) => match resp.json::<DirectoriesResponse>().await {
-            Ok(data) => Ok(data.directories),
-            Err(e) => Err(format!("解析错误: {e}")),
-        },
-        Err(e) => Err(format!("请求失败: {e}")),
+/// 发起 GET 请求并解析 JSON；非 2xx 响应解析为后端的标准错误体
+async fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T, ApiError> {
+    let resp = Request::get(url)
+        .send()
+        .await
+        .map_err(|e| ApiError::network(e.to_string()))?;
+
+    if resp.ok() {
+        return resp
+            .json::<T>()
+            .await
+            .map_err(|e| ApiError::decode(e.to_string()));
+    }
+
+    let status = resp.status();
+    match resp.json::<ErrorResponse>().await {
+        Ok(body) => Err(ApiError::from_body(status, body.error)),
+        Err(_) => Err(ApiError::from_status(status)),
+    }
+}
+
+/// 在列表接口的 URL 后追加 `sort` 参数；默认排序不追加
+fn with_sort(mut url: String, sort: SortMode) -> String {
+    if let Some(value) = sort.query_value() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str("sort=");
+        url.push_str(value);
     }
+    url
+}
+
+/// 获取根目录列表
+pub async fn get_root_directories(sort: SortMode) -> Result<Vec<DirectoryNode>, ApiError> {
+    let url = with_sort("/api/nodes/root".to_string(), sort);
+    let data = fetch_json::<DirectoriesResponse>(&url).await?;
+    Ok(data.directories)
 }
 
 /// 获取子目录列表
-pub async fn get_child_directories(path: &str) -> Result<Vec<DirectoryNode>, String> {
+pub async fn get_child_directories(
+    path: &str,
+    sort: SortMode,
+) -> Result<Vec<DirectoryNode>, ApiError> {
     let encoded_path = urlencoding::encode(path);
-    let url = format!("/api/nodes/children/{}", encoded_path);
-    match Request::get(&url).send().await {
-        Ok(resp) => match resp.json::<DirectoriesResponse>().await {
-            Ok(data) => Ok(data.directories),
-            Err(e) => Err(format!("解析错误: {e}")),
-        },
-        Err(e) => Err(format!("请求失败: {e}")),
-    }
+    let url = with_sort(format!("/api/nodes/children/{}", encoded_path), sort);
+    let data = fetch_json::<DirectoriesResponse>(&url).await?;
+    Ok(data.directories)
 }
 
 /// 获取节点资源文件列表
-pub async fn get_node_assets(path: &str) -> Result<Vec<AssetNode>, String> {
+pub async fn get_node_assets(path: &str, sort: SortMode) -> Result<Vec<AssetNode>, ApiError> {
     let encoded_path = urlencoding::encode(path);
-    let url = format!("/api/nodes/assets/{}", encoded_path);
-    match Request::get(&url).send().await {
-        Ok(resp) => match resp.json::<AssetsResponse>().await {
-            Ok(data) => Ok(data.assets),
-            Err(e) => Err(format!("解析错误: {e}")),
-        },
-        Err(e) => Err(format!("请求失败: {e}")),
-    }
+    let url = with_sort(format!("/api/nodes/assets/{}", encoded_path), sort);
+    let data = fetch_json::<AssetsResponse>(&url).await?;
+    Ok(data.assets)
+}
+
+/// 获取单个节点详情：祖先链、同级节点与计数，用于深链接一次性还原界面
+pub async fn get_node_detail(path: &str, sort: SortMode) -> Result<NodeDetailResponse, ApiError> {
+    let encoded_path = urlencoding::encode(path);
+    let url = with_sort(format!("/api/nodes/detail/{}", encoded_path), sort);
+    fetch_json::<NodeDetailResponse>(&url).await
+}
+
+/// 获取以指定节点为根、展开 `depth` 层的子树（含各节点资源），用于预取整个分支
+pub async fn get_subtree(
+    path: &str,
+    depth: u32,
+    sort: SortMode,
+) -> Result<SubtreeResponse, ApiError> {
+    let encoded_path = urlencoding::encode(path);
+    let url = with_sort(
+        format!("/api/nodes/tree/{}?depth={}", encoded_path, depth),
+        sort,
+    );
+    fetch_json::<SubtreeResponse>(&url).await
+}
+
+/// 全文搜索节点与资源，返回按相关度排序的第一页结果
+pub async fn search_nodes(query: &str, per_page: u32) -> Result<Vec<SearchHit>, ApiError> {
+    let url = format!(
+        "/api/nodes/search?q={}&per_page={}",
+        urlencoding::encode(query),
+        per_page
+    );
+    let data = fetch_json::<SearchResponse>(&url).await?;
+    Ok(data.results)
 }
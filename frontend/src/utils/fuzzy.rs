@@ -0,0 +1,90 @@
+//! 命令面板使用的模糊匹配：查询中的字符按顺序出现在候选文本中即视为匹配，
+//! 连续命中、落在词首的命中得分更高，命中之间的间隔扣分。
+
+/// 命中落在文本开头、分隔符之后或驼峰命名的大写字母上
+const WORD_START_BONUS: i32 = 8;
+/// 与上一个命中相邻
+const CONSECUTIVE_BONUS: i32 = 5;
+/// 单个间隔的最大扣分，避免长文本中的命中被过度惩罚
+const MAX_GAP_PENALTY: usize = 5;
+
+/// 模糊匹配得分，不区分大小写，查询中的空白被忽略；未匹配时返回 `None`，空查询匹配任意文本
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    // 小写后的字符，及其是否位于词首
+    let mut text_chars: Vec<(char, bool)> = Vec::new();
+    let mut last_char: Option<char> = None;
+    for c in text.chars() {
+        let word_start = match last_char {
+            None => true,
+            Some(p) => is_separator(p) || (p.is_lowercase() && c.is_uppercase()),
+        };
+        for (i, lower) in c.to_lowercase().enumerate() {
+            text_chars.push((lower, word_start && i == 0));
+        }
+        last_char = Some(c);
+    }
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+    for (idx, &(c, word_start)) in text_chars.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if c != query[matched] {
+            continue;
+        }
+        score += 1;
+        if word_start {
+            score += WORD_START_BONUS;
+        }
+        let gap = match previous {
+            Some(prev) => idx - prev - 1,
+            None => idx,
+        };
+        if previous.is_some() && gap == 0 {
+            score += CONSECUTIVE_BONUS;
+        } else {
+            score -= gap.min(MAX_GAP_PENALTY) as i32;
+        }
+        previous = Some(idx);
+        matched += 1;
+    }
+    (matched == query.len()).then_some(score)
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '_' | '-' | '.' | '/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subsequences_case_insensitively() {
+        assert!(fuzzy_score("hb", "handmadeBook").is_some());
+        assert!(fuzzy_score("HAND book", "handmadeBook").is_some());
+        assert!(fuzzy_score("作品", "1_作品集").is_some());
+        assert_eq!(fuzzy_score("bh", "handmadeBook"), None);
+        assert_eq!(fuzzy_score("", "任意"), Some(0));
+    }
+
+    #[test]
+    fn prefers_word_starts_and_consecutive_runs() {
+        let score = |query| fuzzy_score(query, "once_and_once_again").unwrap();
+        assert!(score("once") > score("onae"));
+        assert!(score("aoa") > score("nce"));
+        assert!(
+            fuzzy_score("hb", "handmadeBook").unwrap() > fuzzy_score("hb", "hashbrown").unwrap()
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! markdown 代码块的轻量高亮：按各语言的关键字、常量、注释与字符串规则切分 token，
+//! 输出带 `hl-` 前缀 class 的 `<span>`。只覆盖文章中常见的语言，不引入正则引擎与语法定义包，
+//! 以免 wasm 体积膨胀；未收录的语言由调用方按普通代码块输出。
+
+/// 高亮输出的 class 前缀，如 `hl-keyword`，配色见 `style/input.css`
+pub const CLASS_PREFIX: &str = "hl-";
+
+const COMMENT: &str = "hl-comment";
+const STRING: &str = "hl-string";
+const NUMBER: &str = "hl-constant hl-numeric";
+const CONSTANT: &str = "hl-constant hl-language";
+const KEYWORD: &str = "hl-keyword";
+const FUNCTION: &str = "hl-entity hl-name hl-function";
+
+/// 一种语言的词法规则
+pub struct Language {
+    /// 代码块的语言标记（小写）
+    tokens: &'static [&'static str],
+    /// 关键字，空白分隔
+    keywords: &'static str,
+    /// 字面量常量（如 `true`、`null`），空白分隔
+    constants: &'static str,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// 字符串定界符；反引号字符串可以跨行
+    quotes: &'static [char],
+    /// 关键字与常量不区分大小写（SQL）
+    case_insensitive: bool,
+}
+
+const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+
+static LANGUAGES: [Language; 12] = [
+    Language {
+        tokens: &["rust", "rs"],
+        keywords:
+            "as async await break const continue crate dyn else enum extern fn for if impl in let \
+            loop match mod move mut pub ref return self Self static struct super trait type \
+            unsafe use where while",
+        constants: "true false None Some Ok Err",
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+        quotes: &['"'],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["javascript", "js", "jsx", "mjs", "typescript", "ts", "tsx"],
+        keywords:
+            "async await break case catch class const continue debugger default delete do else \
+            export extends finally for from function if import in instanceof interface let new of \
+            return static super switch this throw try type typeof var void while yield",
+        constants: "true false null undefined NaN Infinity",
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+        quotes: &['"', '\'', '`'],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["python", "py"],
+        keywords:
+            "and as assert async await break class continue def del elif else except finally for \
+            from global if import in is lambda nonlocal not or pass raise return try while with \
+            yield",
+        constants: "True False None",
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["json", "jsonc"],
+        keywords: "",
+        constants: "true false null",
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+        quotes: &['"'],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["bash", "sh", "shell", "zsh", "console"],
+        keywords:
+            "if then else elif fi for while until do done case esac function in return local \
+            export",
+        constants: "true false",
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["css", "scss"],
+        keywords: "important",
+        constants: "",
+        line_comments: &[],
+        block_comment: C_BLOCK,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["sql", "postgresql", "psql"],
+        keywords:
+            "select from where insert into values update set delete create table alter add drop \
+            index on join left right inner outer group by order having limit offset as and or not \
+            is in like distinct union all case when then else end primary key references default \
+            exists if with returning",
+        constants: "true false null",
+        line_comments: &["--"],
+        block_comment: C_BLOCK,
+        quotes: &['\'', '"'],
+        case_insensitive: true,
+    },
+    Language {
+        tokens: &["toml"],
+        keywords: "",
+        constants: "true false",
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["yaml", "yml"],
+        keywords: "",
+        constants: "true false null yes no",
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["c", "h", "cpp", "c++", "cc", "cxx", "hpp"],
+        keywords:
+            "auto break case char class const continue default delete do double else enum extern \
+            float for goto if inline int long namespace new private protected public return short \
+            signed sizeof static struct switch template this typedef union unsigned using virtual \
+            void volatile while",
+        constants: "true false NULL nullptr",
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["go", "golang"],
+        keywords:
+            "break case chan const continue default defer else fallthrough for func go goto if \
+            import interface map package range return select struct switch type var",
+        constants: "true false nil iota",
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+        quotes: &['"', '\'', '`'],
+        case_insensitive: false,
+    },
+    Language {
+        tokens: &["java"],
+        keywords:
+            "abstract boolean break byte case catch char class continue default do double else \
+            enum extends final finally float for if implements import instanceof int interface \
+            long new package private protected public return short static super switch this throw \
+            throws try void while",
+        constants: "true false null",
+        line_comments: &["//"],
+        block_comment: C_BLOCK,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+];
+
+/// 按代码块的语言标记（不区分大小写）查找语言，未收录时返回 `None`
+pub fn find_language(token: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|language| {
+        language
+            .tokens
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(token))
+    })
+}
+
+impl Language {
+    /// 高亮整段代码，返回已转义的 HTML
+    pub fn highlight(&self, code: &str) -> String {
+        let mut out = String::with_capacity(code.len() * 2);
+        let mut rest = code;
+        while !rest.is_empty() {
+            let (len, class) = self.next_token(rest);
+            push_token(&mut out, &rest[..len], class);
+            rest = &rest[len..];
+        }
+        out
+    }
+
+    /// 下一个 token 的字节长度与 class；普通文本的 class 为 `None`
+    fn next_token(&self, rest: &str) -> (usize, Option<&'static str>) {
+        if self
+            .line_comments
+            .iter()
+            .any(|prefix| rest.starts_with(prefix))
+        {
+            return (rest.find('\n').unwrap_or(rest.len()), Some(COMMENT));
+        }
+        if let Some((open, close)) = self.block_comment {
+            if let Some(body) = rest.strip_prefix(open) {
+                let len = body
+                    .find(close)
+                    .map_or(rest.len(), |end| open.len() + end + close.len());
+                return (len, Some(COMMENT));
+            }
+        }
+        let Some(first) = rest.chars().next() else {
+            return (0, None);
+        };
+        if self.quotes.contains(&first) {
+            return (string_len(rest, first), Some(STRING));
+        }
+        if first.is_ascii_digit() {
+            return (number_len(rest), Some(NUMBER));
+        }
+        if first.is_alphabetic() || first == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let class = if self.contains(self.keywords, word) {
+                Some(KEYWORD)
+            } else if self.contains(self.constants, word) {
+                Some(CONSTANT)
+            } else if rest[len..].starts_with('(') {
+                Some(FUNCTION)
+            } else {
+                None
+            };
+            return (len, class);
+        }
+        (first.len_utf8(), None)
+    }
+
+    fn contains(&self, words: &str, word: &str) -> bool {
+        words.split_whitespace().any(|w| {
+            if self.case_insensitive {
+                w.eq_ignore_ascii_case(word)
+            } else {
+                w == word
+            }
+        })
+    }
+}
+
+/// 以 `quote` 开头的字符串长度：支持反斜杠转义与三引号；
+/// 未闭合时普通字符串止于行尾，反引号与三引号字符串止于代码末尾
+fn string_len(rest: &str, quote: char) -> usize {
+    let triple: String = [quote; 3].iter().collect();
+    if rest.starts_with(&triple) {
+        return rest[3..]
+            .find(&triple)
+            .map_or(rest.len(), |end| 3 + end + 3);
+    }
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            _ if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+/// 数字字面量长度：字母数字与下划线（十六进制、后缀），以及后面跟数字的小数点
+fn number_len(rest: &str) -> usize {
+    let bytes = rest.as_bytes();
+    let mut len = 0;
+    while len < bytes.len() {
+        let b = bytes[len];
+        let decimal_point = b == b'.' && bytes.get(len + 1).is_some_and(u8::is_ascii_digit);
+        if !(b.is_ascii_alphanumeric() || b == b'_' || decimal_point) {
+            break;
+        }
+        len += 1;
+    }
+    len
+}
+
+fn push_token(out: &mut String, text: &str, class: Option<&str>) {
+    if let Some(class) = class {
+        out.push_str("<span class=\"");
+        out.push_str(class);
+        out.push_str("\">");
+    }
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    if class.is_some() {
+        out.push_str("</span>");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight(lang: &str, code: &str) -> String {
+        find_language(lang).unwrap().highlight(code)
+    }
+
+    #[test]
+    fn highlights_keywords_strings_comments_and_numbers() {
+        assert_eq!(
+            highlight("Rust", "let s = \"<b>\"; // 注释\nfoo(1.5)"),
+            "<span class=\"hl-keyword\">let</span> s = \
+             <span class=\"hl-string\">&quot;&lt;b&gt;&quot;</span>; \
+             <span class=\"hl-comment\">// 注释</span>\n\
+             <span class=\"hl-entity hl-name hl-function\">foo</span>(\
+             <span class=\"hl-constant hl-numeric\">1.5</span>)"
+        );
+        assert_eq!(
+            highlight("sql", "SELECT 'it\\'s' FROM t WHERE x IS NULL -- 末尾"),
+            "<span class=\"hl-keyword\">SELECT</span> \
+             <span class=\"hl-string\">'it\\'s'</span> \
+             <span class=\"hl-keyword\">FROM</span> t \
+             <span class=\"hl-keyword\">WHERE</span> x \
+             <span class=\"hl-keyword\">IS</span> \
+             <span class=\"hl-constant hl-language\">NULL</span> \
+             <span class=\"hl-comment\">-- 末尾</span>"
+        );
+        assert!(find_language("未知语言").is_none());
+    }
+
+    #[test]
+    fn unterminated_tokens_stop_at_line_or_code_end() {
+        assert_eq!(
+            highlight("python", "x = \"open\ny = '''多\n行'''"),
+            "x = <span class=\"hl-string\">&quot;open</span>\ny = \
+             <span class=\"hl-string\">'''多\n行'''</span>"
+        );
+        assert_eq!(
+            highlight("js", "/* 未闭合"),
+            "<span class=\"hl-comment\">/* 未闭合</span>"
+        );
+        assert_eq!(
+            highlight("go", "v.x1 = 0x1F.y"),
+            "v.x1 = <span class=\"hl-constant hl-numeric\">0x1F</span>.y"
+        );
+    }
+}
@@ -7,6 +7,7 @@ pub fn handle_keyboard_navigation(
     move_selection: Rc<dyn Fn(i32)>,
     enter_selection: Rc<dyn Fn()>,
     go_back: Rc<dyn Fn()>,
+    cycle_sort: Rc<dyn Fn()>,
     detail_scroll_ref: NodeRef<leptos::html::Div>,
     present_scroll_ref: NodeRef<leptos::html::Div>,
 ) {
@@ -31,9 +32,14 @@ pub fn handle_keyboard_navigation(
         return;
     }
 
-    // 只处理 j/k/l/h
+    // Ctrl / Cmd + S 留给浏览器
+    if key == "s" && (event.ctrl_key() || event.meta_key()) {
+        return;
+    }
+
+    // 只处理 j/k/l/h 与切换排序的 s
     match key.as_str() {
-        "j" | "k" | "l" | "h" => {
+        "j" | "k" | "l" | "h" | "s" => {
             event.prevent_default();
             event.stop_propagation();
         }
@@ -51,10 +57,21 @@ pub fn handle_keyboard_navigation(
         }
         "l" => enter_selection(),
         "h" => go_back(),
+        "s" => cycle_sort(),
         _ => {}
     }
 }
 
+/// 打开命令面板的快捷键：`/` 或 Ctrl + K（macOS 上为 Cmd + K）
+pub fn is_palette_shortcut(event: &web_sys::KeyboardEvent) -> bool {
+    let modified = event.ctrl_key() || event.meta_key();
+    match event.key().as_str() {
+        "/" => !modified && !event.alt_key(),
+        "k" | "K" => modified && !event.alt_key() && !event.shift_key(),
+        _ => false,
+    }
+}
+
 fn scroll_detail(detail_scroll_ref: &NodeRef<leptos::html::Div>, delta: f64) {
     if let Some(container) = detail_scroll_ref.get() {
         let current_scroll = container.scroll_top() as f64;
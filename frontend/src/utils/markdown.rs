@@ -0,0 +1,700 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::utils::highlight::{self, find_language, Language};
+use crate::utils::types::{
+    classify_asset_kind, encode_path_segments, AssetNode, FileInfo, NodeKind,
+};
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+
+/// 站内节点链接的前缀，如 `[作品](node:1_OnceAndOnceAgain.handmadeBook)`
+pub const NODE_LINK_PREFIX: &str = "node:";
+
+/// 解析选项：表格、标题属性、脚注、任务列表、删除线与 `$...$` / `$$...$$` 公式；
+/// front matter 只作为元数据，不渲染到正文
+fn parser_options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_MATH);
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    options.insert(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
+    options
+}
+
+/// 渲染时解析相对路径、检查失效链接所需的上下文
+pub struct LinkContext<'a> {
+    /// markdown 文件相对于资源根目录的路径，相对引用按其所在目录解析
+    pub source_path: &'a str,
+    /// 所属节点的资源：只写文件名时按名称匹配；资源所在目录的内容视为已知，
+    /// 指向这些目录中不存在的文件的链接标记为失效
+    pub assets: &'a [AssetNode],
+    /// 已确认不存在的节点（`node:` 链接的目标）
+    pub missing_nodes: &'a HashSet<String>,
+}
+
+/// 渲染 markdown 文件（`visual_assets` 中的说明或节点的排版文件）。
+///
+/// - 相对路径的图片、视频与 PDF 解析为 `/resource/...` URL，匹配到节点资源时附带版本参数；
+///   以图片语法引用的视频渲染为 `<video>`，PDF 渲染为内嵌的 `<object>`
+/// - `node:路径` 链接渲染为带 `data-node` 的站内链接，点击后由页面交给 `navigate_to`
+/// - 失效的链接与图片带 `dead-link` 样式并在提示中说明原因
+/// - 标明语言的代码块由 [`highlight`] 按语言高亮（只收录常见语言），公式转换为 MathML
+/// - 输出经过 [`sanitize_html`] 清理，markdown 中内嵌的脚本与事件属性不会带到页面上
+pub fn render_markdown(raw: &str, context: &LinkContext) -> String {
+    let base_dir = context
+        .source_path
+        .rsplit_once('/')
+        .map_or("", |(dir, _)| dir);
+    let mut events = Vec::new();
+    // 正在替换为自定义 HTML 的图片，及其 alt 文本
+    let mut pending_media: Option<Target> = None;
+    let mut alt = String::new();
+    // 起始标签已输出为自定义 HTML 的链接，结束标签也需对应输出
+    let mut custom_link = false;
+    // 正在收集内容的高亮代码块：语言、语言标记与代码
+    let mut code_block: Option<(&Language, String, String)> = None;
+
+    for event in Parser::new_ext(raw, parser_options()) {
+        if let Some((language, lang, code)) = &mut code_block {
+            match event {
+                Event::End(TagEnd::CodeBlock) => {
+                    events.push(Event::Html(CowStr::from(highlight_code(
+                        language, lang, code,
+                    ))));
+                    code_block = None;
+                }
+                Event::Text(text) => code.push_str(&text),
+                _ => {}
+            }
+            continue;
+        }
+        if let Some(media) = &pending_media {
+            match event {
+                Event::End(TagEnd::Image) => {
+                    events.push(Event::InlineHtml(CowStr::from(media_html(media, &alt))));
+                    pending_media = None;
+                    alt.clear();
+                }
+                Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => match resolve_target(&dest_url, base_dir, context) {
+                Target::File {
+                    url,
+                    kind: NodeKind::Image,
+                    dead: false,
+                } => events.push(Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: CowStr::from(url),
+                    title,
+                    id,
+                })),
+                Target::Unchanged | Target::Node { .. } => events.push(Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                })),
+                media => pending_media = Some(media),
+            },
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => match resolve_target(&dest_url, base_dir, context) {
+                Target::Unchanged => events.push(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                })),
+                Target::File {
+                    url, dead: false, ..
+                } => events.push(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url: CowStr::from(url),
+                    title,
+                    id,
+                })),
+                target => {
+                    events.push(Event::InlineHtml(CowStr::from(link_open_html(
+                        &target, &title,
+                    ))));
+                    custom_link = true;
+                }
+            },
+            Event::End(TagEnd::Link) if custom_link => {
+                events.push(Event::InlineHtml(CowStr::from("</a>")));
+                custom_link = false;
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let lang = info.split_whitespace().next().unwrap_or_default();
+                match find_language(lang) {
+                    Some(language) => {
+                        code_block = Some((language, lang.to_string(), String::new()));
+                    }
+                    _ => events.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))),
+                }
+            }
+            Event::InlineMath(tex) => events.push(Event::InlineHtml(CowStr::from(math_html(
+                &tex,
+                DisplayStyle::Inline,
+            )))),
+            Event::DisplayMath(tex) => events.push(Event::InlineHtml(CowStr::from(math_html(
+                &tex,
+                DisplayStyle::Block,
+            )))),
+            event => events.push(event),
+        }
+    }
+
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, events.into_iter());
+    sanitize_html(&html_output)
+}
+
+/// 按白名单清理 HTML：只保留正文排版、图片、视频、PDF、表格、标题与代码相关的标签和属性，
+/// 去掉 `<script>`、`<iframe>`、`on*` 事件属性以及 `javascript:` 等不在白名单内的 URL scheme。
+/// 标题与脚注的 `id` 统一加上 [`ID_PREFIX`]，页内锚点链接随之改写，
+/// 避免 markdown 中的 `id` 覆盖页面上的全局变量或元素（DOM clobbering）
+pub fn sanitize_html(html: &str) -> String {
+    SANITIZER.clean(html).to_string()
+}
+
+/// markdown 输出中 `id` 与页内锚点的前缀
+const ID_PREFIX: &str = "md-";
+
+static SANITIZER: LazyLock<ammonia::Builder<'static>> = LazyLock::new(|| {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .id_prefix(Some(ID_PREFIX))
+        .add_tags(["video", "object", "input"])
+        .add_tags(MATHML_TAGS)
+        .add_tag_attributes("a", ["class", "data-node"])
+        .add_tag_attributes("span", ["class"])
+        .add_tag_attributes("code", ["class", "title"])
+        .add_tag_attributes("sup", ["class"])
+        .add_tag_attributes("div", ["class", "id"])
+        .add_tag_attributes("input", ["type", "checked"])
+        .set_tag_attribute_value("input", "disabled", "")
+        .add_tag_attributes("img", ["title"])
+        .add_tag_attributes("video", ["src", "poster", "controls", "preload", "class"])
+        .add_tag_attributes("object", ["data", "type", "class"])
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .filter_style_properties(HashSet::from(["text-align"]))
+        .attribute_filter(filter_attribute);
+    for heading in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(heading, ["id"]);
+    }
+    for tag in MATHML_TAGS {
+        builder.add_tag_attributes(tag, MATHML_ATTRIBUTES);
+    }
+    builder
+});
+
+/// [`latex_to_mathml`] 输出中可能出现的 MathML 标签与属性
+const MATHML_TAGS: [&str; 24] = [
+    "math",
+    "mrow",
+    "mi",
+    "mn",
+    "mo",
+    "mtext",
+    "mspace",
+    "ms",
+    "msub",
+    "msup",
+    "msubsup",
+    "mfrac",
+    "msqrt",
+    "mroot",
+    "mover",
+    "munder",
+    "munderover",
+    "mtable",
+    "mtr",
+    "mtd",
+    "mstyle",
+    "mpadded",
+    "mphantom",
+    "menclose",
+];
+const MATHML_ATTRIBUTES: [&str; 19] = [
+    "display",
+    "displaystyle",
+    "scriptlevel",
+    "mathvariant",
+    "stretchy",
+    "fence",
+    "separator",
+    "form",
+    "lspace",
+    "rspace",
+    "largeop",
+    "movablelimits",
+    "accent",
+    "accentunder",
+    "linethickness",
+    "columnalign",
+    "columnspacing",
+    "rowspacing",
+    "width",
+];
+
+/// 白名单之外的取值限制：class 只保留渲染时生成的样式，
+/// `<object>` 只能内嵌资源服务中的 PDF，页内锚点改写为带 [`ID_PREFIX`] 的 `id`
+fn filter_attribute<'u>(element: &str, attribute: &str, value: &'u str) -> Option<Cow<'u, str>> {
+    match (element, attribute) {
+        (_, "class") => {
+            let classes: Vec<&str> = value
+                .split_whitespace()
+                .filter(|class| allowed_class(element, class))
+                .collect();
+            (!classes.is_empty()).then(|| Cow::Owned(classes.join(" ")))
+        }
+        ("a", "href") => match value.strip_prefix('#') {
+            Some(fragment) if !fragment.starts_with(ID_PREFIX) => {
+                Some(Cow::Owned(format!("#{ID_PREFIX}{fragment}")))
+            }
+            _ => Some(Cow::Borrowed(value)),
+        },
+        ("a", "data-node") => is_valid_node_path(value).then_some(Cow::Borrowed(value)),
+        ("input", "type") => (value == "checkbox").then_some(Cow::Borrowed(value)),
+        ("object", "type") => (value == "application/pdf").then_some(Cow::Borrowed(value)),
+        ("object", "data") => {
+            let path = value.split(['?', '#']).next().unwrap_or_default();
+            (value.starts_with("/resource/") && path.to_ascii_lowercase().ends_with(".pdf"))
+                .then_some(Cow::Borrowed(value))
+        }
+        _ => Some(Cow::Borrowed(value)),
+    }
+}
+
+/// 各标签允许的 class：站内链接与失效标记、[`media_html`] 中媒体的排版、代码块的语言与高亮、
+/// 脚注与无法解析的公式
+fn allowed_class(element: &str, class: &str) -> bool {
+    match element {
+        "a" => matches!(class, "node-link" | "dead-link"),
+        "span" => class == "dead-link" || class.starts_with(highlight::CLASS_PREFIX),
+        "video" => matches!(class, "w-full" | "h-auto" | "rounded"),
+        "object" => matches!(class, "w-full" | "h-[60vh]" | "rounded"),
+        "code" => class.starts_with("language-") || class == "math-error",
+        "sup" => matches!(class, "footnote-reference" | "footnote-definition-label"),
+        "div" => class == "footnote-definition",
+        _ => false,
+    }
+}
+
+/// 高亮代码块：每个 token 输出为带 `hl-` 前缀 class 的 `<span>`
+fn highlight_code(language: &Language, lang: &str, code: &str) -> String {
+    format!(
+        "<pre><code class=\"language-{}\">{}</code></pre>\n",
+        escape_attribute(lang),
+        language.highlight(code)
+    )
+}
+
+/// 公式转换为 MathML；无法解析时保留原文并在提示中给出原因
+fn math_html(tex: &str, display: DisplayStyle) -> String {
+    latex_to_mathml(tex, display).unwrap_or_else(|err| {
+        format!(
+            "<code class=\"math-error\" title=\"公式无法解析：{}\">{}</code>",
+            escape_attribute(&err.to_string()),
+            escape_attribute(tex)
+        )
+    })
+}
+
+/// 文档中 `node:` 链接的目标节点（去重，保持出现顺序）
+pub fn node_links(raw: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for event in Parser::new_ext(raw, parser_options()) {
+        if let Event::Start(Tag::Link { dest_url, .. }) = event {
+            if let Some(path) = dest_url.strip_prefix(NODE_LINK_PREFIX) {
+                if is_valid_node_path(path) && !paths.iter().any(|known| known == path) {
+                    paths.push(path.to_string());
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// 链接或图片的解析结果
+enum Target {
+    /// 外部链接、站内绝对路径与锚点，保持原样
+    Unchanged,
+    /// 资源根目录下的文件
+    File {
+        url: String,
+        kind: NodeKind,
+        dead: bool,
+    },
+    /// 站内节点
+    Node { path: String, dead: bool },
+}
+
+fn resolve_target(dest: &str, base_dir: &str, context: &LinkContext) -> Target {
+    if let Some(path) = dest.strip_prefix(NODE_LINK_PREFIX) {
+        let dead = !is_valid_node_path(path) || context.missing_nodes.contains(path);
+        return Target::Node {
+            path: path.to_string(),
+            dead,
+        };
+    }
+    if dest.is_empty() || dest.starts_with('/') || dest.starts_with('#') || has_scheme(dest) {
+        return Target::Unchanged;
+    }
+
+    let (path, suffix) = dest.split_at(dest.find(['?', '#']).unwrap_or(dest.len()));
+    let path = urlencoding::decode(path).map_or_else(|_| path.to_string(), |p| p.into_owned());
+    let Some(joined) = join_relative(base_dir, &path) else {
+        // 越过资源根目录，资源服务无法提供
+        return Target::File {
+            url: dest.to_string(),
+            kind: classify_asset_kind(&path),
+            dead: true,
+        };
+    };
+
+    let asset = context
+        .assets
+        .iter()
+        .find(|asset| asset.raw_path == joined)
+        .or_else(|| {
+            (!path.contains('/'))
+                .then(|| {
+                    context
+                        .assets
+                        .iter()
+                        .find(|asset| asset.raw_filename == path)
+                })
+                .flatten()
+        });
+    let Some(asset) = asset else {
+        let dir = joined.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let dead = context
+            .assets
+            .iter()
+            .any(|asset| asset.raw_path.rsplit_once('/').map_or("", |(d, _)| d) == dir);
+        return Target::File {
+            url: format!("/resource/{}{suffix}", encode_path_segments(&joined)),
+            kind: classify_asset_kind(&joined),
+            dead,
+        };
+    };
+
+    let mut url = format!("/resource/{}", encode_path_segments(&asset.raw_path));
+    match FileInfo::from_asset(asset).version() {
+        Some(version) if suffix.is_empty() => url.push_str(&format!("?v={version}")),
+        _ => url.push_str(suffix),
+    }
+    Target::File {
+        url,
+        kind: asset.node_kind(),
+        dead: false,
+    }
+}
+
+/// 节点路径是否为合法的 ltree 路径（点号分隔，标签只含字母、数字与下划线）
+fn is_valid_node_path(path: &str) -> bool {
+    !path.is_empty()
+        && path.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+/// 是否带有 URL scheme（如 `https:`、`mailto:`）
+fn has_scheme(dest: &str) -> bool {
+    dest.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// 以 `/` 连接目录与相对路径并消去 `.` 与 `..`；越过资源根目录时返回 `None`
+fn join_relative(base_dir: &str, relative: &str) -> Option<String> {
+    let mut parts: Vec<&str> = base_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    for part in relative.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// 需要自定义渲染的图片：视频、PDF、其它文件与失效引用
+fn media_html(target: &Target, alt: &str) -> String {
+    let Target::File { url, kind, dead } = target else {
+        return String::new();
+    };
+    let src = escape_attribute(url);
+    if *dead {
+        let text = if alt.is_empty() { url.as_str() } else { alt };
+        return format!(
+            "<span class=\"dead-link\" title=\"链接失效：文件不存在\">{}</span>",
+            escape_attribute(text)
+        );
+    }
+    match kind {
+        NodeKind::Video => format!(
+            "<video src=\"{src}\" controls preload=\"metadata\" class=\"w-full h-auto rounded\"></video>"
+        ),
+        NodeKind::Pdf => format!(
+            "<object data=\"{src}\" type=\"application/pdf\" class=\"w-full h-[60vh] rounded\"><a href=\"{src}\">{}</a></object>",
+            escape_attribute(if alt.is_empty() { url } else { alt })
+        ),
+        _ => format!(
+            "<a href=\"{src}\">{}</a>",
+            escape_attribute(if alt.is_empty() { url } else { alt })
+        ),
+    }
+}
+
+/// 站内节点链接与失效链接的起始标签
+fn link_open_html(target: &Target, title: &str) -> String {
+    match target {
+        Target::Node { path, dead } => {
+            let href = format!("?path={}", urlencoding::encode(path));
+            let (class, title) = if *dead {
+                ("node-link dead-link", "链接失效：节点不存在")
+            } else {
+                ("node-link", title)
+            };
+            format!(
+                "<a href=\"{}\" data-node=\"{}\" class=\"{class}\" title=\"{}\">",
+                escape_attribute(&href),
+                escape_attribute(path),
+                escape_attribute(title)
+            )
+        }
+        Target::File { url, .. } => format!(
+            "<a href=\"{}\" class=\"dead-link\" title=\"链接失效：文件不存在\">",
+            escape_attribute(url)
+        ),
+        Target::Unchanged => String::new(),
+    }
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(raw: &str) -> String {
+        let missing_nodes = HashSet::new();
+        render_markdown(
+            raw,
+            &LinkContext {
+                source_path: "work/visual_assets/intro.md",
+                assets: &[],
+                missing_nodes: &missing_nodes,
+            },
+        )
+    }
+
+    #[test]
+    fn strips_scripts_iframes_and_event_handlers() {
+        let html = render(
+            "# 标题\n\n<script>alert(1)</script>\n\n\
+             <iframe src=\"https://example.com\"></iframe>\n\n\
+             <img src=\"x.png\" onerror=\"alert(1)\">\n\n\
+             <p onclick=\"alert(1)\" style=\"position:fixed\">正文</p>\n",
+        );
+        assert!(html.contains("<h1>标题</h1>"));
+        assert!(html.contains("正文"));
+        for forbidden in [
+            "<script", "alert(1)", "<iframe", "onerror", "onclick", "position",
+        ] {
+            assert!(!html.contains(forbidden), "{forbidden} 未被清理: {html}");
+        }
+    }
+
+    #[test]
+    fn strips_javascript_urls() {
+        let html = render(
+            "[点我](javascript:alert(1))\n\n![图](javascript:alert(2))\n\n\
+             <a href=\"JavaScript:alert(3)\">原始链接</a>\n\n\
+             <object data=\"javascript:alert(4)\" type=\"application/pdf\"></object>\n\n\
+             <video src=\"data:text/html,x\" poster=\"javascript:alert(5)\"></video>\n",
+        );
+        assert!(html.contains("点我"));
+        assert!(html.contains("原始链接"));
+        assert!(!html.to_ascii_lowercase().contains("javascript:"), "{html}");
+        assert!(!html.contains("data:text/html"), "{html}");
+    }
+
+    #[test]
+    fn keeps_generated_links_media_and_tables() {
+        let html = render(
+            "[作品](node:work.child) [外部](https://example.com)\n\n\
+             ![封面](cover.png) ![片段](clip.mp4) ![说明](spec.pdf)\n\n\
+             | 左 | 中 |\n| :-- | :-: |\n| a | b |\n\n\
+             ```rust\nfn main() {}\n```\n\n\
+             ## 小节 {#part .wide}\n\n\
+             <object data=\"/resource/work/page.html\" type=\"text/html\"></object>\n",
+        );
+        assert!(html
+            .contains("<a href=\"?path=work.child\" data-node=\"work.child\" class=\"node-link\""));
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("<img src=\"/resource/work/visual_assets/cover.png\" alt=\"封面\">"));
+        assert!(html.contains(
+            "<video src=\"/resource/work/visual_assets/clip.mp4\" controls=\"\" preload=\"metadata\" class=\"w-full h-auto rounded\">"
+        ));
+        assert!(html.contains(
+            "<object data=\"/resource/work/visual_assets/spec.pdf\" type=\"application/pdf\""
+        ));
+        assert!(html.contains("<th style=\"text-align:center\">中</th>"));
+        assert!(html.contains("<code class=\"language-rust\">"));
+        assert!(html.contains("<h2 id=\"md-part\">小节</h2>"));
+        assert!(!html.contains("page.html\""), "{html}");
+    }
+
+    #[test]
+    fn renders_extensions_highlighting_and_math() {
+        let html = render(
+            "~~旧价~~ 新价[^1]，面积 $x^2$\n\n\
+             - [x] 完成\n- [ ] 待办\n\n\
+             [^1]: 含税\n\n\
+             ```rust\nlet s = \"<b>\";\n```\n\n\
+             ```未知语言\n<i>原样</i>\n```\n",
+        );
+        assert!(html.contains("<del>旧价</del>"));
+        assert!(html.contains("<sup class=\"footnote-reference\"><a href=\"#md-1\""));
+        assert!(html.contains("<div class=\"footnote-definition\" id=\"md-1\">"));
+        assert!(html.contains("<input type=\"checkbox\" checked=\"\" disabled=\"\">"));
+        assert!(html.contains("<math display=\"inline\">"), "{html}");
+        assert!(html.contains("<span class=\"hl-keyword\">let</span>"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(html.contains("<code class=\"language-未知语言\">&lt;i&gt;原样&lt;/i&gt;"));
+    }
+
+    #[test]
+    fn encodes_resource_url_segments() {
+        let asset = AssetNode {
+            file_path: "work.visual_assets.cover".into(),
+            raw_path: "work/visual_assets/my cover#1.png".into(),
+            raw_filename: "my cover#1.png".into(),
+            sort_key: String::new(),
+            file_size: 1,
+            file_type: "image/png".into(),
+            modified_time: None,
+            content_hash: "abcdef0123".into(),
+            width: None,
+            height: None,
+            duration_ms: None,
+            kind: "image".into(),
+            derivatives: Vec::new(),
+        };
+        let missing_nodes = HashSet::new();
+        let html = render_markdown(
+            "![a](my%20cover%231.png) ![b](<../extra/封面 图.jpg>)",
+            &LinkContext {
+                source_path: "work/visual_assets/intro.md",
+                assets: &[asset],
+                missing_nodes: &missing_nodes,
+            },
+        );
+        assert!(
+            html.contains("src=\"/resource/work/visual_assets/my%20cover%231.png?v=abcdef01\""),
+            "{html}"
+        );
+        assert!(
+            html.contains("src=\"/resource/work/extra/%E5%B0%81%E9%9D%A2%20%E5%9B%BE.jpg\""),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn embeds_assets_by_recorded_kind() {
+        let asset = AssetNode {
+            file_path: "work.media.clip_bin".into(),
+            raw_path: "work/media/clip.bin".into(),
+            raw_filename: "clip.bin".into(),
+            sort_key: String::new(),
+            file_size: 1,
+            file_type: String::new(),
+            modified_time: None,
+            content_hash: String::new(),
+            width: None,
+            height: None,
+            duration_ms: None,
+            kind: "video".into(),
+            derivatives: Vec::new(),
+        };
+        assert_eq!(asset.node_kind(), NodeKind::Video);
+        let legacy = AssetNode {
+            kind: String::new(),
+            raw_filename: "intro.md".into(),
+            ..asset.clone()
+        };
+        assert_eq!(legacy.node_kind(), NodeKind::Markdown);
+
+        let missing_nodes = HashSet::new();
+        let html = render_markdown(
+            "![片段](clip.bin)",
+            &LinkContext {
+                source_path: "work/media/intro.md",
+                assets: &[asset],
+                missing_nodes: &missing_nodes,
+            },
+        );
+        assert!(
+            html.contains("<video src=\"/resource/work/media/clip.bin\""),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn prefixes_ids_and_in_page_anchors() {
+        let html = render(
+            "## 设置 {#config}\n\n[跳转](#config) [外部](https://example.com/#config)\n\n\
+             <div id=\"location\"></div>\n\n<div id=\"md-kept\"></div>\n",
+        );
+        assert!(html.contains("<h2 id=\"md-config\">设置</h2>"), "{html}");
+        assert!(
+            html.contains("<a href=\"#md-config\" rel=\"noopener noreferrer\">跳转</a>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("href=\"https://example.com/#config\""),
+            "{html}"
+        );
+        assert!(html.contains("<div id=\"md-location\"></div>"), "{html}");
+        assert!(html.contains("<div id=\"md-kept\"></div>"), "{html}");
+        assert!(!html.contains("id=\"location\""), "{html}");
+    }
+}
@@ -1,5 +1,8 @@
 pub mod api;
+pub mod fuzzy;
+pub mod highlight;
 pub mod keyboard;
+pub mod markdown;
 pub mod mouse;
 pub mod types;
 
@@ -1,4 +1,5 @@
 use crate::utils::api::get_child_directories;
+use crate::utils::types::SortMode;
 use crate::DirectoryNode;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
@@ -20,6 +21,7 @@ use web_sys::console;
 /// - `set_preview_path`: 设置 Preview 显示的路径
 /// - `set_selected_path`: 设置当前选中的路径
 /// - `set_selected_index`: 设置当前选中的索引
+#[allow(clippy::too_many_arguments)]
 pub fn handle_node_click(
     path: String,
     has_subnodes: bool,
@@ -56,10 +58,10 @@ pub fn handle_node_click(
 
         // 加载被点击节点的子目录到 OverviewB
         let path_clone = path.clone();
-        let set_selected_index_clone = set_selected_index.clone();
+        let set_selected_index_clone = set_selected_index;
         spawn_local(async move {
             console::log_2(&"[鼠标点击] 请求子节点:".into(), &path_clone.clone().into());
-            match get_child_directories(&path_clone).await {
+            match get_child_directories(&path_clone, SortMode::default()).await {
                 Ok(children) => {
                     console::log_2(
                         &"[鼠标点击] 加载子节点成功，数量:".into(),
@@ -83,7 +85,7 @@ pub fn handle_node_click(
                     }
                 }
                 Err(e) => {
-                    console::log_2(&"[鼠标点击] 请求失败:".into(), &e.into());
+                    console::log_2(&"[鼠标点击] 请求失败:".into(), &e.to_string().into());
                 }
             }
         });
@@ -131,7 +133,7 @@ pub fn handle_overview_a_click(
     // 先获取被点击节点的信息，检查是否有子节点
     let path_for_info = path.clone();
     spawn_local(async move {
-        match get_child_directories(&path_for_info).await {
+        match get_child_directories(&path_for_info, SortMode::default()).await {
             Ok(children) => {
                 if !children.is_empty() {
                     set_preview_path.set(Some(path_for_info.clone()));
@@ -148,14 +150,14 @@ pub fn handle_overview_a_click(
     // 加载兄弟节点到 OverviewB
     let parent_for_b = parent_path.clone();
     let clicked_path = path.clone();
-    let set_selected_index_clone = set_selected_index.clone();
-    let set_preview_path_clone = set_preview_path.clone();
+    let set_selected_index_clone = set_selected_index;
+    let set_preview_path_clone = set_preview_path;
     spawn_local(async move {
         let result = if let Some(p) = parent_for_b {
-            get_child_directories(&p).await
+            get_child_directories(&p, SortMode::default()).await
         } else {
             // 如果父路径为空，加载 "1_OnceAndOnceAgain" 的子节点
-            get_child_directories("1_OnceAndOnceAgain").await
+            get_child_directories("1_OnceAndOnceAgain", SortMode::default()).await
         };
 
         if let Ok(data_dirs) = result {
@@ -171,8 +173,8 @@ pub fn handle_overview_a_click(
 
                 // 等待 overview_b.rs 的 effect 加载完新的 directories 后再设置 selected_index
                 // 使用双重 request_animation_frame 延迟，确保 directories 已经更新
-                let set_selected_index_delayed = set_selected_index_clone.clone();
-                let set_preview_path_delayed = set_preview_path_clone.clone();
+                let set_selected_index_delayed = set_selected_index_clone;
+                let set_preview_path_delayed = set_preview_path_clone;
                 let clicked_dir = data_dirs.get(index).cloned();
 
                 if let Some(window) = web_sys::window() {
@@ -254,10 +256,10 @@ pub fn handle_overview_a_click(
 
         spawn_local(async move {
             let result = if let Some(p) = parent_for_a {
-                get_child_directories(&p).await
+                get_child_directories(&p, SortMode::default()).await
             } else {
                 // 如果父路径为空，加载 "1_OnceAndOnceAgain" 的子节点
-                get_child_directories("1_OnceAndOnceAgain").await
+                get_child_directories("1_OnceAndOnceAgain", SortMode::default()).await
             };
             if let Ok(data_dirs) = result {
                 set_overview_a_directories.set(data_dirs);
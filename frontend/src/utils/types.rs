@@ -10,6 +10,55 @@ pub struct DirectoryNode {
     pub path: String,
     pub has_subnodes: bool,
     pub raw_filename: String,
+    /// 节点目录下是否有 `layout.md`
+    #[serde(default)]
+    pub has_layout: bool,
+    /// `layout.md` front matter 中的作品信息
+    #[serde(default)]
+    pub metadata: NodeMetadata,
+    /// 后端排序键，列表按它排列
+    #[serde(default)]
+    pub sort_key: String,
+    /// 可见性；隐藏节点不会由后端返回，不公开节点只能通过链接进入
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// 排版文件相对于资源根目录的路径，有值时作为 Overview 的首项渲染
+    #[serde(default)]
+    pub layout_path: Option<String>,
+}
+
+/// 节点可见性
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Unlisted,
+    Hidden,
+}
+
+impl DirectoryNode {
+    /// 展示名称：优先使用 front matter 中的标题，否则使用原始目录名
+    pub fn display_name(&self) -> &str {
+        self.metadata
+            .title
+            .as_deref()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or(&self.raw_filename)
+    }
+}
+
+/// 作品信息（标题、材料、年份、价格等）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NodeMetadata {
+    pub title: Option<String>,
+    pub material: Option<String>,
+    pub year: Option<i32>,
+    pub price: Option<String>,
+    pub tags: Vec<String>,
+    pub order: Option<i64>,
+    pub hidden: bool,
 }
 
 /// 资源文件数据结构
@@ -18,6 +67,154 @@ pub struct AssetNode {
     pub file_path: String,
     pub raw_path: String,
     pub raw_filename: String,
+    /// 后端排序键，列表按它排列
+    #[serde(default)]
+    pub sort_key: String,
+    /// 文件大小（字节）
+    #[serde(default)]
+    pub file_size: i64,
+    /// MIME 类型
+    #[serde(default)]
+    pub file_type: String,
+    /// 修改时间（RFC 3339）
+    #[serde(default)]
+    pub modified_time: Option<String>,
+    /// 文件内容哈希
+    #[serde(default)]
+    pub content_hash: String,
+    /// 图片或视频的像素尺寸
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub height: Option<i32>,
+    /// 视频时长（毫秒）
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+    /// 导入时按目录结构约定判断的资源类型；旧数据为空
+    #[serde(default)]
+    pub kind: String,
+    /// 图片的缩放衍生图（WebP / AVIF）；视频为封面帧的衍生图
+    #[serde(default)]
+    pub derivatives: Vec<ImageDerivative>,
+}
+
+impl AssetNode {
+    /// 资源类型：优先使用后端记录的类型，未记录时按扩展名判断
+    pub fn node_kind(&self) -> NodeKind {
+        match self.kind.as_str() {
+            "markdown" => NodeKind::Markdown,
+            "image" => NodeKind::Image,
+            "video" => NodeKind::Video,
+            "pdf" => NodeKind::Pdf,
+            "" => classify_asset_kind(&self.raw_filename),
+            _ => NodeKind::Other,
+        }
+    }
+}
+
+/// 图片衍生图
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImageDerivative {
+    /// `webp` 或 `avif`
+    pub format: String,
+    pub width: i32,
+    pub height: i32,
+    /// 相对于 `/derivatives/` 的路径
+    pub path: String,
+}
+
+/// 资源文件的展示信息：大小、尺寸与缓存版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileInfo {
+    pub size: i64,
+    pub mime: String,
+    pub modified_time: Option<String>,
+    pub hash: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub duration_ms: Option<i64>,
+    pub derivatives: Vec<ImageDerivative>,
+}
+
+impl FileInfo {
+    pub fn from_asset(asset: &AssetNode) -> Self {
+        Self {
+            size: asset.file_size,
+            mime: asset.file_type.clone(),
+            modified_time: asset.modified_time.clone(),
+            hash: asset.content_hash.clone(),
+            width: asset.width,
+            height: asset.height,
+            duration_ms: asset.duration_ms,
+            derivatives: asset.derivatives.clone(),
+        }
+    }
+
+    /// 指定格式的衍生图，按宽度升序
+    pub fn derivatives_of(&self, format: &str) -> Vec<&ImageDerivative> {
+        let mut list: Vec<&ImageDerivative> = self
+            .derivatives
+            .iter()
+            .filter(|derivative| derivative.format == format)
+            .collect();
+        list.sort_by_key(|derivative| derivative.width);
+        list
+    }
+
+    /// 人类可读的文件大小，如 `1.5 MB`；大小未知（旧数据）时为 `None`
+    pub fn size_label(&self) -> Option<String> {
+        if self.size <= 0 {
+            return None;
+        }
+        const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+        if self.size < 1024 {
+            return Some(format!("{} B", self.size));
+        }
+        let mut value = self.size as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit + 1 < UNITS.len() {
+            value /= 1024.0;
+            unit += 1;
+        }
+        Some(format!("{value:.1} {}", UNITS[unit]))
+    }
+
+    /// 视频时长，如 `1:23`，超过一小时为 `1:02:03`
+    pub fn duration_label(&self) -> Option<String> {
+        let secs = self.duration_ms.filter(|ms| *ms > 0)? / 1000;
+        let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+        Some(if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            format!("{minutes}:{seconds:02}")
+        })
+    }
+
+    /// 图片或视频尺寸，宽高都已知时返回
+    pub fn dimensions(&self) -> Option<(i32, i32)> {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => Some((width, height)),
+            _ => None,
+        }
+    }
+
+    /// 资源 URL 的版本参数（内容哈希前 8 位），文件替换后浏览器缓存随之失效
+    pub fn version(&self) -> Option<&str> {
+        self.hash.get(..8)
+    }
+}
+
+/// 按默认扩展名判断资源类型，用于没有资源记录的链接目标与旧数据
+pub fn classify_asset_kind(filename: &str) -> NodeKind {
+    let ext = filename.rsplit('.').next().map(|s| s.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("md") | Some("markdown") => NodeKind::Markdown,
+        Some("mp4") | Some("mov") | Some("webm") | Some("m4v") | Some("ogg") => NodeKind::Video,
+        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("bmp") | Some("svg")
+        | Some("webp") | Some("ico") => NodeKind::Image,
+        Some("pdf") => NodeKind::Pdf,
+        _ => NodeKind::Other,
+    }
 }
 
 /// UI 列表节点类型
@@ -41,6 +238,8 @@ pub struct UiNode {
     pub directory_path: Option<String>,
     pub raw_path: Option<String>,
     pub has_children: bool,
+    /// 资源文件的元数据，目录节点为 `None`
+    pub file: Option<FileInfo>,
 }
 
 /// Detail 栏展示所需的节点/资源描述
@@ -54,6 +253,21 @@ pub struct DetailItem {
     pub has_children: bool,
     pub content: Option<String>,
     pub display_as_entry: bool,
+    /// 资源文件的元数据，目录节点为 `None`
+    pub file: Option<FileInfo>,
+}
+
+/// 命令面板中的候选项：目录节点或资源文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    /// 与 `UiNode::id` 一致，用于在父级列表中定位
+    pub id: String,
+    pub label: String,
+    pub kind: NodeKind,
+    /// 所在层级（根层级为空字符串），选中后导航到该层并高亮此项
+    pub parent: String,
+    /// 所在层级的面包屑（各级显示名）
+    pub breadcrumb: Vec<String>,
 }
 
 /// 目录列表 API 响应体
@@ -68,12 +282,200 @@ pub struct AssetsResponse {
     pub assets: Vec<AssetNode>,
 }
 
+/// 单个节点详情 API（`/nodes/detail/{path}`）响应体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeDetailResponse {
+    pub node: DirectoryNode,
+    /// 从一级节点到父节点的祖先链（不含自身）
+    pub ancestors: Vec<DirectoryNode>,
+    /// 父节点下的全部子节点（含自身）
+    pub siblings: Vec<DirectoryNode>,
+    pub child_count: usize,
+    /// 按资源类型统计的 visual_assets 数量
+    #[serde(default)]
+    pub asset_counts: HashMap<String, usize>,
+}
+
+/// 子树中的一个节点（`/nodes/tree/{path}`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeNode {
+    #[serde(flatten)]
+    pub node: DirectoryNode,
+    #[serde(default)]
+    pub assets: Vec<AssetNode>,
+    /// 为 `None` 时表示该层未展开（超出深度或被截断），不能当作“没有子节点”写入缓存
+    #[serde(default)]
+    pub children: Option<Vec<TreeNode>>,
+}
+
+/// 子树 API 响应体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtreeResponse {
+    pub tree: TreeNode,
+    pub depth: u32,
+    pub truncated: bool,
+}
+
+/// 搜索命中项（`/nodes/search`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchHit {
+    Directory {
+        node: DirectoryNode,
+        rank: f32,
+        /// 带 `<mark>` 高亮的匹配片段
+        snippet: String,
+    },
+    Asset {
+        node: AssetNode,
+        rank: f32,
+        snippet: String,
+    },
+}
+
+/// 搜索 API 响应体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchHit>,
+    pub total: i64,
+}
+
+/// 后端错误响应体：`{"error": {"code": ..., "message": ...}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+}
+
+/// 前端统一的接口错误：保留状态码与后端错误码，便于 Detail 栏给出有意义的提示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// HTTP 状态码；网络层失败时为 0
+    pub status: u16,
+    /// 后端错误码（not_found / invalid_path / invalid_query / db_unavailable / internal），
+    /// 以及前端补充的 network / decode
+    pub code: String,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn network(message: impl Into<String>) -> Self {
+        Self {
+            status: 0,
+            code: "network".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn decode(message: impl Into<String>) -> Self {
+        Self {
+            status: 0,
+            code: "decode".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn from_body(status: u16, body: ErrorBody) -> Self {
+        Self {
+            status,
+            code: body.code,
+            message: body.message,
+        }
+    }
+
+    /// 后端未返回标准错误体时，仅凭状态码推断错误类型
+    pub fn from_status(status: u16) -> Self {
+        let code = match status {
+            400 => "invalid_path",
+            404 => "not_found",
+            503 => "db_unavailable",
+            _ => "internal",
+        };
+        Self {
+            status,
+            code: code.to_string(),
+            message: String::new(),
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.code == "not_found"
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let summary = match self.code.as_str() {
+            "not_found" => "内容不存在或已被移除",
+            "invalid_path" => "路径无效",
+            "invalid_query" => "查询参数无效",
+            "db_unavailable" => "数据库暂时不可用，请稍后重试",
+            "network" => "网络请求失败",
+            "decode" => "响应解析错误",
+            _ => "服务器错误",
+        };
+        if self.message.is_empty() {
+            write!(f, "{summary}")
+        } else {
+            write!(f, "{summary}（{}）", self.message)
+        }
+    }
+}
+
 /// 缓存结构：key 为父路径，value 为该路径下的直接子节点列表
 pub type NodesCache = HashMap<String, Vec<DirectoryNode>>;
 
 /// 资源缓存结构：key 为父路径，value 为该路径下的文件列表
 pub type AssetsCache = HashMap<String, Vec<AssetNode>>;
 
+/// 节点显示名缓存：key 为节点路径，value 为原始目录名（用于面包屑）
+pub type NodeLabels = HashMap<String, String>;
+
+/// 列表排序方式，对应后端列表接口的 `?sort=order|name|date`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// 导入时写入的排序键（默认）
+    #[default]
+    Order,
+    /// 按展示名称自然排序
+    Name,
+    /// 按年份由新到旧
+    Date,
+}
+
+impl SortMode {
+    /// `?sort=` 的取值；默认排序不带参数
+    pub fn query_value(self) -> Option<&'static str> {
+        match self {
+            SortMode::Order => None,
+            SortMode::Name => Some("name"),
+            SortMode::Date => Some("date"),
+        }
+    }
+
+    /// 按 Order → Name → Date 循环切换
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Order => SortMode::Name,
+            SortMode::Name => SortMode::Date,
+            SortMode::Date => SortMode::Order,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Order => "默认",
+            SortMode::Name => "名称",
+            SortMode::Date => "年份",
+        }
+    }
+}
+
 /// 按层级拆分路径，例如 "a.b.c" -> ["a", "a.b", "a.b.c"]
 pub fn split_levels(path: &str) -> Vec<String> {
     if path.is_empty() {
@@ -105,3 +507,16 @@ pub fn parent_path(path: &str) -> Option<String> {
         }
     }
 }
+
+/// 对 `/` 分隔的路径逐段做百分号编码，保留分隔符；用于拼接资源与衍生图 URL
+pub fn encode_path_segments(path: &str) -> String {
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 资源文件所属节点的路径：资源路径形如 `<节点>.visual_assets.<文件>`
+pub fn asset_owner_path(file_path: &str) -> Option<&str> {
+    file_path.rsplitn(3, '.').nth(2)
+}
@@ -8,6 +8,9 @@ TOOL_DIR="${REPO_ROOT}/utils/node-generate-tool"
 DEFAULT_RESOURCE_ROOT="${REPO_ROOT}/resource/resource"
 RESOURCE_ROOT="${1:-${DEFAULT_RESOURCE_ROOT}}"
 OUTPUT_DIR="${REPO_ROOT}/database/import_exchange"
+DENY_LIST="${REPO_ROOT}/resource/nginx/hidden_nodes.conf"
+MANIFEST="${OUTPUT_DIR}/manifest.json"
+DERIVATIVES_DIR="${REPO_ROOT}/resource/derivatives"
 
 echo "[资源扫描] 使用资源目录: ${RESOURCE_ROOT}"
 if [[ ! -d "${RESOURCE_ROOT}" ]]; then
@@ -19,18 +22,48 @@ mkdir -p "${OUTPUT_DIR}"
 
 pushd "${TOOL_DIR}" >/dev/null
 
-echo "[资源扫描] 生成 node.csv ..."
-cargo run --release -- scan node "${RESOURCE_ROOT}" -o "${OUTPUT_DIR}/node.csv"
+# 资源目录有错误（失效链接、放错位置的目录等）时在导入前停止；SKIP_VALIDATE=1 可跳过
+if [[ -z "${SKIP_VALIDATE:-}" ]]; then
+  echo "[资源扫描] 校验资源目录 ..."
+  if ! cargo run --release -- validate "${RESOURCE_ROOT}"; then
+    echo "[资源扫描] 错误: 资源目录校验未通过，已停止导入（修正后重试，或设置 SKIP_VALIDATE=1 跳过）" >&2
+    exit 1
+  fi
+fi
+
+if [[ -n "${DATABASE_URL:-}" ]]; then
+  # 能直接连上数据库时跳过 CSV 与容器内 psql，按清单只写入变化的部分（单个事务）
+  echo "[资源扫描] 检测到 DATABASE_URL，直接导入数据库 ..."
+  cargo run --release -- import "${RESOURCE_ROOT}" --deny-list "${DENY_LIST}" --manifest "${MANIFEST}"
+  if [[ -z "${SKIP_THUMBNAILS:-}" ]]; then
+    echo "[资源扫描] 生成图片衍生图 ..."
+    cargo run --release -- thumbnails "${RESOURCE_ROOT}" --cache-dir "${DERIVATIVES_DIR}" --manifest "${MANIFEST}"
+  fi
+  popd >/dev/null
+else
+  echo "[资源扫描] 生成 node.csv ..."
+  cargo run --release -- scan node "${RESOURCE_ROOT}" -o "${OUTPUT_DIR}/node.csv" --deny-list "${DENY_LIST}"
+
+  echo "[资源扫描] 生成 visual_assets.csv ..."
+  cargo run --release -- scan visual "${RESOURCE_ROOT}" -o "${OUTPUT_DIR}/visual_assets.csv"
 
-echo "[资源扫描] 生成 visual_assets.csv ..."
-cargo run --release -- scan visual "${RESOURCE_ROOT}" -o "${OUTPUT_DIR}/visual_assets.csv"
+  if [[ -z "${SKIP_THUMBNAILS:-}" ]]; then
+    echo "[资源扫描] 生成图片衍生图 ..."
+    cargo run --release -- thumbnails "${RESOURCE_ROOT}" --cache-dir "${DERIVATIVES_DIR}" -o "${OUTPUT_DIR}/image_derivatives.csv"
+  fi
 
-popd >/dev/null
+  popd >/dev/null
 
-echo "[资源扫描] CSV 已输出至 ${OUTPUT_DIR}"
+  echo "[资源扫描] CSV 已输出至 ${OUTPUT_DIR}"
+
+  echo "[资源扫描] 调用数据库导入脚本..."
+  "${REPO_ROOT}/database/scripts/import_csv.sh"
+fi
 
 COMPOSE_CMD="${COMPOSE_CMD:-podman-compose}"
-echo "[资源扫描] 调用数据库导入脚本..."
-"${REPO_ROOT}/database/scripts/import_csv.sh"
+echo "[资源扫描] 重新加载资源服务的隐藏节点屏蔽规则..."
+if ! ${COMPOSE_CMD} exec -T resource nginx -s reload >/dev/null 2>&1; then
+  echo "[资源扫描] 警告: 资源服务未运行，屏蔽规则将在下次启动时生效" >&2
+fi
 echo "[资源扫描] 资源刷新与数据库导入完成"
 
@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::ltree_label::print_label_report;
+use crate::manifest::Manifest;
+use crate::node_scan::{NodeRow, scan_nodes};
+use crate::visual_scan::{AssetRow, scan_assets};
+
+/// 扫描并与清单比较，打印摘要；可选写出 JSON 变更集并把清单更新为本次扫描结果
+pub fn run_change_scan(
+    root: &Path,
+    manifest_path: &Path,
+    output_path: Option<&Path>,
+    ignore_file: &str,
+    update_manifest: bool,
+) -> Result<()> {
+    let scan = scan_nodes(root, ignore_file)?;
+    print_label_report(&scan.renamed);
+    let previous = Manifest::load(manifest_path)?;
+    if previous.is_none() {
+        println!(
+            "清单不存在，所有记录均视为新增: {}",
+            manifest_path.display()
+        );
+    }
+    let assets = scan_assets(root, ignore_file, previous.as_ref())?;
+    let current = Manifest::build(&scan.rows, &assets);
+    let changes = ChangeSet::diff(&previous.unwrap_or_default(), &current, &scan.rows, &assets);
+
+    print!("{changes}");
+    if let Some(output_path) = output_path {
+        changes.write_json(output_path)?;
+        println!("已生成: {}", output_path.display());
+    }
+    if update_manifest {
+        current.save(manifest_path)?;
+        println!("已更新清单: {}", manifest_path.display());
+    }
+    Ok(())
+}
+
+/// 一类记录的变化；新增与修改携带完整的行，可直接按主键 upsert，删除只需主键
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Changes<T> {
+    pub added: Vec<T>,
+    pub modified: Vec<T>,
+    /// 被删除记录的 ltree 路径
+    pub removed: Vec<String>,
+}
+
+impl<T> Default for Changes<T> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            modified: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl<T> Changes<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    /// 需要写入（upsert）的行
+    pub fn upserts(&self) -> impl Iterator<Item = &T> {
+        self.added.iter().chain(&self.modified)
+    }
+
+    /// 按清单比较两次扫描：`key` 取行的主键，`changed` 判断同一主键的清单条目是否不同
+    fn diff<E>(
+        rows: &[T],
+        key: impl Fn(&T) -> &str,
+        previous: &BTreeMap<String, E>,
+        current: &BTreeMap<String, E>,
+        changed: impl Fn(&E, &E) -> bool,
+    ) -> Self
+    where
+        T: Clone,
+    {
+        let mut changes = Self::default();
+        for row in rows {
+            let path = key(row);
+            match (previous.get(path), current.get(path)) {
+                (None, _) => changes.added.push(row.clone()),
+                (Some(old), Some(new)) if changed(old, new) => changes.modified.push(row.clone()),
+                _ => {}
+            }
+        }
+        changes.removed = previous
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+/// 本次扫描相对上一次导入的变化
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChangeSet {
+    pub nodes: Changes<NodeRow>,
+    pub assets: Changes<AssetRow>,
+}
+
+impl ChangeSet {
+    /// `previous` 为上一次导入的清单，`current` 为由 `nodes` / `assets` 生成的本次清单
+    pub fn diff(
+        previous: &Manifest,
+        current: &Manifest,
+        nodes: &[NodeRow],
+        assets: &[AssetRow],
+    ) -> Self {
+        Self {
+            nodes: Changes::diff(
+                nodes,
+                |row| row.path.as_str(),
+                &previous.nodes,
+                &current.nodes,
+                |old, new| old.row != new.row,
+            ),
+            // 行中包含内容哈希，文件被替换时行哈希随之变化；哈希计算失败时总按修改处理
+            assets: Changes::diff(
+                assets,
+                |row| row.file_path.as_str(),
+                &previous.assets,
+                &current.assets,
+                |old, new| old.row != new.row || new.hash.is_empty(),
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.assets.is_empty()
+    }
+
+    /// 以 JSON 写出变更集
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let text = serde_json::to_string_pretty(self).context("无法序列化变更集")?;
+        fs::write(path, text).with_context(|| format!("无法写入变更集: {}", path.display()))
+    }
+}
+
+/// 人类可读的摘要：每类一行计数，随后逐条列出（`+` 新增，`~` 修改，`-` 删除）
+impl fmt::Display for ChangeSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "没有变化");
+        }
+        write_changes(f, "节点", &self.nodes, |row| &row.raw_path)?;
+        write_changes(f, "资源", &self.assets, |row| &row.raw_path)
+    }
+}
+
+fn write_changes<T>(
+    f: &mut fmt::Formatter<'_>,
+    label: &str,
+    changes: &Changes<T>,
+    raw_path: impl Fn(&T) -> &str,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "{label}：新增 {}，修改 {}，删除 {}",
+        changes.added.len(),
+        changes.modified.len(),
+        changes.removed.len()
+    )?;
+    for row in &changes.added {
+        writeln!(f, "  + {}", raw_path(row))?;
+    }
+    for row in &changes.modified {
+        writeln!(f, "  ~ {}", raw_path(row))?;
+    }
+    // 删除的记录已不在扫描结果中，只能列出 ltree 路径
+    for path in &changes.removed {
+        writeln!(f, "  - {path}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visibility::Visibility;
+
+    fn node(path: &str, sort_key: &str) -> NodeRow {
+        NodeRow {
+            path: path.into(),
+            has_subnodes: false,
+            raw_path: path.replace('.', "/"),
+            raw_filename: path.rsplit('.').next().unwrap().into(),
+            has_layout: false,
+            metadata: "{}".into(),
+            sort_key: sort_key.into(),
+            visibility: Visibility::Public,
+            layout_path: None,
+        }
+    }
+
+    fn asset(file_path: &str, content_hash: &str) -> AssetRow {
+        AssetRow {
+            file_path: file_path.into(),
+            raw_path: file_path.replace('.', "/"),
+            raw_filename: file_path.rsplit('.').next().unwrap().into(),
+            content: String::new(),
+            sort_key: "1".into(),
+            visibility: Visibility::Public,
+            file_size: 5,
+            file_type: "image/jpeg".into(),
+            modified_time: "2024-01-01T00:00:00Z".into(),
+            content_hash: content_hash.into(),
+            width: Some(4),
+            height: Some(3),
+            duration_ms: None,
+            kind: "image".into(),
+            modified_ns: 1,
+        }
+    }
+
+    #[test]
+    fn classifies_added_modified_and_removed_nodes() {
+        let before = [node("a", "1"), node("b", "1"), node("c", "1")];
+        let after = [node("a", "1"), node("b", "2"), node("d", "1")];
+        let previous = Manifest::build(&before, &[]);
+        let current = Manifest::build(&after, &[]);
+
+        let changes = ChangeSet::diff(&previous, &current, &after, &[]);
+        assert_eq!(changes.nodes.added, vec![node("d", "1")]);
+        assert_eq!(changes.nodes.modified, vec![node("b", "2")]);
+        assert_eq!(changes.nodes.removed, vec!["c".to_string()]);
+        assert!(changes.assets.is_empty());
+
+        let unchanged = ChangeSet::diff(&current, &current, &after, &[]);
+        assert!(unchanged.is_empty());
+        assert_eq!(unchanged.to_string(), "没有变化\n");
+    }
+
+    #[test]
+    fn asset_content_change_is_a_modification() {
+        let before = [asset("a.visual_assets.cover_jpg", "first")];
+        let previous = Manifest::build(&[], &before);
+        assert!(ChangeSet::diff(&previous, &previous, &[], &before).is_empty());
+
+        // 文件名、排序等都不变，只有内容哈希变化
+        let after = [asset("a.visual_assets.cover_jpg", "second")];
+        let current = Manifest::build(&[], &after);
+        let changes = ChangeSet::diff(&previous, &current, &[], &after);
+        assert_eq!(changes.assets.modified.len(), 1);
+        assert!(changes.to_string().contains("资源：新增 0，修改 1，删除 0"));
+    }
+}
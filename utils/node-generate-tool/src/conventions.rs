@@ -0,0 +1,215 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::front_matter::LAYOUT_FILE;
+
+/// `kinds` 可用的资源类型，即前端能展示的几类
+pub const KNOWN_KINDS: [&str; 4] = ["markdown", "image", "video", "pdf"];
+
+/// 目录结构约定的配置文件名，与 ignore 文件一样放在根目录旁
+pub const CONFIG_FILE: &str = "node-generate-tool.toml";
+
+/// 资源树的目录结构约定：哪些目录存放资源、哪些目录不算子节点、
+/// 排版文件叫什么、各扩展名属于哪类资源。
+///
+/// 依次查找根目录、可执行文件目录与程序目录下的 [`CONFIG_FILE`]，使用第一个找到的文件；
+/// 文件中未写的项沿用默认值，写了的项整体替换默认值。
+/// 资源类型随每条资源记录写入数据库，后端与前端按记录展示，不再自行按目录名或扩展名判断。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Conventions {
+    /// 节点下存放展示文件的目录名
+    pub asset_dirs: Vec<String>,
+    /// 除资源目录外，不算作子节点的目录名（如项目归档）
+    pub excluded_dirs: Vec<String>,
+    /// 节点的排版文件名，按顺序取第一个存在的文件
+    pub layout_files: Vec<String>,
+    /// 资源类型到扩展名（小写、不含点）的映射；未列出的扩展名视为不支持
+    pub kinds: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Conventions {
+    fn default() -> Self {
+        let kinds = [
+            ("markdown", &["md", "markdown"][..]),
+            (
+                "image",
+                &["png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico"],
+            ),
+            ("video", &["mp4", "mov", "webm", "m4v", "ogg"]),
+            ("pdf", &["pdf"]),
+        ]
+        .into_iter()
+        .map(|(kind, extensions)| {
+            let extensions = extensions.iter().map(|ext| ext.to_string()).collect();
+            (kind.to_string(), extensions)
+        })
+        .collect();
+        Self {
+            asset_dirs: vec!["visual_assets".into()],
+            excluded_dirs: vec!["project_archive".into()],
+            layout_files: vec![LAYOUT_FILE.into()],
+            kinds,
+        }
+    }
+}
+
+impl Conventions {
+    /// 读取配置；三个位置都没有配置文件时使用默认约定
+    pub fn load(root: &Path) -> Result<Self> {
+        let program_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(Path::to_path_buf));
+        let candidates = [Some(root.to_path_buf()), exe_dir, Some(program_dir)];
+        match candidates
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+        {
+            Some(path) => {
+                eprintln!("已加载配置文件: {}", path.display());
+                Self::from_file(&path)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("无法读取配置文件: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("配置文件格式错误: {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut conventions: Self = toml::from_str(text)?;
+        if conventions.asset_dirs.is_empty() {
+            bail!("asset_dirs 不能为空");
+        }
+        if let Some(name) = conventions
+            .asset_dirs
+            .iter()
+            .chain(&conventions.excluded_dirs)
+            .chain(&conventions.layout_files)
+            .find(|name| name.is_empty() || name.contains(['/', '\\']))
+        {
+            bail!("目录名或文件名无效: {name:?}");
+        }
+        for extensions in conventions.kinds.values_mut() {
+            for ext in extensions.iter_mut() {
+                *ext = ext.trim_start_matches('.').to_ascii_lowercase();
+            }
+        }
+        if let Some(kind) = conventions
+            .kinds
+            .keys()
+            .find(|kind| !KNOWN_KINDS.contains(&kind.as_str()))
+        {
+            bail!(
+                "未知的资源类型: {kind:?}（可用: {}）",
+                KNOWN_KINDS.join(", ")
+            );
+        }
+        Ok(conventions)
+    }
+
+    pub fn is_asset_dir(&self, name: &str) -> bool {
+        self.asset_dirs.iter().any(|dir| dir == name)
+    }
+
+    /// 资源目录与排除目录：属于节点本身，不算作子节点
+    pub fn is_structural_dir(&self, name: &str) -> bool {
+        self.is_asset_dir(name) || self.excluded_dirs.iter().any(|dir| dir == name)
+    }
+
+    /// 节点目录下的排版文件
+    pub fn layout_file(&self, dir: &Path) -> Option<PathBuf> {
+        self.layout_files
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// 按扩展名判断资源类型，不支持的扩展名返回 `None`
+    pub fn kind_of(&self, path: &Path) -> Option<&str> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        self.kinds
+            .iter()
+            .find(|(_, extensions)| extensions.contains(&ext))
+            .map(|(kind, _)| kind.as_str())
+    }
+
+    /// 资源相关模式下附加的“反忽略”规则，强制纳入资源目录
+    pub fn include_rules(&self) -> String {
+        let mut rules = String::from("\n# visual modes: force include asset dirs\n");
+        for dir in &self.asset_dirs {
+            rules.push_str(&format!("!{dir}/\n!**/{dir}/\n!**/{dir}/**\n"));
+        }
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_config_overrides_only_given_keys() {
+        let conventions = Conventions::parse(
+            "excluded_dirs = [\"drafts\"]\n\
+             [kinds]\nmarkdown = [\".MD\", \"markdown\"]\n",
+        )
+        .unwrap();
+        assert!(conventions.is_structural_dir("drafts"));
+        assert!(conventions.is_structural_dir("visual_assets"));
+        // excluded_dirs 整体替换默认值
+        assert!(!conventions.is_structural_dir("project_archive"));
+        assert_eq!(conventions.layout_files, [LAYOUT_FILE]);
+        assert_eq!(
+            conventions.kind_of(Path::new("a/intro.Md")),
+            Some("markdown")
+        );
+        // kinds 同样整体替换，未列出的扩展名不再支持
+        assert_eq!(conventions.kind_of(Path::new("cover.png")), None);
+        assert!(
+            conventions
+                .include_rules()
+                .contains("!**/visual_assets/**\n")
+        );
+
+        assert_eq!(
+            Conventions::default().kind_of(Path::new("clip.WebM")),
+            Some("video")
+        );
+        assert!(Conventions::parse("asset_dirs = []").is_err());
+        assert!(Conventions::parse("excluded_dirs = [\"a/b\"]").is_err());
+        assert!(Conventions::parse("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn accepts_custom_names_but_rejects_unknown_kinds() {
+        let conventions = Conventions::parse(
+            "asset_dirs = [\"media\"]\n\
+             layout_files = [\"index.md\"]\n\
+             [kinds]\nmarkdown = [\"txt\"]\nimage = [\"tiff\"]\n",
+        )
+        .unwrap();
+        assert!(conventions.is_asset_dir("media"));
+        assert!(!conventions.is_asset_dir("visual_assets"));
+        assert_eq!(
+            conventions.kind_of(Path::new("notes.TXT")),
+            Some("markdown")
+        );
+        assert_eq!(conventions.kind_of(Path::new("scan.tiff")), Some("image"));
+        assert_eq!(conventions.kind_of(Path::new("intro.md")), None);
+
+        assert!(Conventions::parse("[kinds]\naudio = [\"mp3\"]").is_err());
+    }
+}
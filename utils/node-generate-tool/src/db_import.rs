@@ -0,0 +1,620 @@
+use std::{io::Write, path::Path};
+
+use anyhow::{Context, Result};
+use postgres::{Client, NoTls, Transaction};
+
+use crate::change_set::ChangeSet;
+use crate::derivatives::{DERIVATIVE_CSV_COLUMNS, DerivativeRow, write_derivative_csv};
+use crate::ltree_label::print_label_report;
+use crate::manifest::Manifest;
+use crate::node_scan::{NODE_CSV_COLUMNS, NodeRow, scan_nodes, write_node_csv};
+use crate::visibility::write_nginx_deny_list;
+use crate::visual_scan::{AssetRow, VISUAL_CSV_COLUMNS, scan_assets, write_visual_csv};
+
+/// 建表 / 迁移语句，每次导入前执行
+const SCHEMA_SQL: &str = include_str!("schema.sql");
+
+/// 扫描节点与资源并直接写入 PostgreSQL。
+///
+/// 扫描在连接数据库之前完成；建表、写入在同一个事务中执行，
+/// 任何一步失败都会回滚，网站继续使用旧数据。
+///
+/// 指定 `manifest` 且清单已存在、数据库中记录的清单指纹与之相同时只写入变化的部分
+/// （按主键 upsert / 删除），否则清空两张表后全量导入；事务提交成功后才更新清单。
+pub fn run_import(
+    root: &Path,
+    database_url: &str,
+    ignore_file: &str,
+    deny_list: Option<&Path>,
+    manifest: Option<&Path>,
+) -> Result<()> {
+    println!("扫描节点与资源: {}", root.display());
+    let scan = scan_nodes(root, ignore_file)?;
+    print_label_report(&scan.renamed);
+    let previous = match manifest {
+        Some(path) => Manifest::load(path)?,
+        None => None,
+    };
+    let assets = scan_assets(root, ignore_file, previous.as_ref())?;
+    let current = Manifest::build(&scan.rows, &assets);
+    let changes = previous.as_ref().map(|previous| {
+        (
+            ChangeSet::diff(previous, &current, &scan.rows, &assets),
+            previous.fingerprint(),
+        )
+    });
+
+    let mut client = connect(database_url)?;
+    let base = changes
+        .as_ref()
+        .map(|(changes, fingerprint)| (changes, fingerprint.as_str()));
+    match import_scan(
+        &mut client,
+        &scan.rows,
+        &assets,
+        base,
+        &current.fingerprint(),
+    )? {
+        ImportOutcome::Incremental => {
+            if let Some((changes, _)) = &changes {
+                print!("{changes}");
+            }
+            println!("增量导入完成");
+        }
+        ImportOutcome::Full { nodes, assets } => {
+            println!("导入完成：{nodes} 个节点，{assets} 个资源文件");
+        }
+    }
+
+    if let Some(path) = manifest {
+        current.save(path)?;
+        println!("已更新清单: {}", path.display());
+    }
+
+    if let Some(deny_list) = deny_list {
+        write_nginx_deny_list(deny_list, &scan.hidden_roots)?;
+        println!(
+            "已生成隐藏节点屏蔽规则（{} 条）: {}",
+            scan.hidden_roots.len(),
+            deny_list.display()
+        );
+    }
+    Ok(())
+}
+
+/// 连接数据库；连接串中可能带有密码，错误信息中不回显
+pub fn connect(database_url: &str) -> Result<Client> {
+    Client::connect(database_url, NoTls).context("无法连接数据库")
+}
+
+/// [`import_scan`] 实际执行的写入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// 只写入了变更集
+    Incremental,
+    /// 清空后全量导入，附导入的行数
+    Full { nodes: u64, assets: u64 },
+}
+
+/// 在单个事务中建表 / 迁移并写入扫描结果。
+///
+/// `changes` 为相对旧清单的变更集及旧清单的指纹：数据库中记录的指纹与之相同时增量写入，
+/// 否则（没有旧清单，或数据库已被其它方式改写）清空两张表后全量导入。
+/// 写入后把 `fingerprint`（本次扫描生成的清单的指纹）记录到数据库
+pub fn import_scan(
+    client: &mut Client,
+    nodes: &[NodeRow],
+    assets: &[AssetRow],
+    changes: Option<(&ChangeSet, &str)>,
+    fingerprint: &str,
+) -> Result<ImportOutcome> {
+    let mut transaction = client.transaction().context("无法开启事务")?;
+    transaction
+        .batch_execute(SCHEMA_SQL)
+        .context("创建或迁移表结构失败")?;
+
+    let stored = stored_fingerprint(&mut transaction)?;
+    let outcome = match changes {
+        Some((changes, base)) if stored.as_deref() == Some(base) => {
+            apply_changes(&mut transaction, changes)?;
+            ImportOutcome::Incremental
+        }
+        _ => {
+            if changes.is_some() {
+                eprintln!("数据库内容与旧清单不一致，忽略旧清单并全量导入");
+            }
+            let mut node_csv = Vec::new();
+            write_node_csv(nodes, &mut node_csv)?;
+            let mut asset_csv = Vec::new();
+            write_visual_csv(assets, &mut asset_csv)?;
+
+            transaction
+                .batch_execute("TRUNCATE directory_nodes, file_nodes;")
+                .context("清空旧数据失败")?;
+            let nodes = copy_csv(
+                &mut transaction,
+                "directory_nodes",
+                NODE_CSV_COLUMNS,
+                "",
+                &node_csv,
+            )?;
+            // 非 markdown 文件的 content 为空字段（哈希失败时 content_hash 亦然），CSV 格式下默认会被当作 NULL
+            let assets = copy_csv(
+                &mut transaction,
+                "file_nodes",
+                VISUAL_CSV_COLUMNS,
+                ", FORCE_NOT_NULL (content, content_hash)",
+                &asset_csv,
+            )?;
+            ImportOutcome::Full { nodes, assets }
+        }
+    };
+    transaction
+        .execute(
+            "INSERT INTO import_state (manifest_fingerprint) VALUES ($1)
+             ON CONFLICT (id) DO UPDATE SET manifest_fingerprint = EXCLUDED.manifest_fingerprint",
+            &[&fingerprint],
+        )
+        .context("记录清单指纹失败")?;
+    transaction.commit().context("提交事务失败")?;
+    Ok(outcome)
+}
+
+/// 在单个事务中用本次生成的衍生图记录替换 `image_derivatives` 全表，返回导入的行数
+pub fn import_derivatives(client: &mut Client, rows: &[DerivativeRow]) -> Result<u64> {
+    let mut csv = Vec::new();
+    write_derivative_csv(rows, &mut csv)?;
+
+    let mut transaction = client.transaction().context("无法开启事务")?;
+    transaction
+        .batch_execute(SCHEMA_SQL)
+        .context("创建或迁移表结构失败")?;
+    transaction
+        .batch_execute("TRUNCATE image_derivatives;")
+        .context("清空旧的衍生图记录失败")?;
+    let count = copy_csv(
+        &mut transaction,
+        "image_derivatives",
+        DERIVATIVE_CSV_COLUMNS,
+        "",
+        &csv,
+    )?;
+    transaction.commit().context("提交事务失败")?;
+    Ok(count)
+}
+
+/// 数据库内容对应的清单指纹；从未由本工具写入或被 import_csv.sh 清空时为 `None`
+fn stored_fingerprint(transaction: &mut Transaction<'_>) -> Result<Option<String>> {
+    let row = transaction
+        .query_opt("SELECT manifest_fingerprint FROM import_state", &[])
+        .context("查询清单指纹失败")?;
+    Ok(row.map(|row| row.get(0)))
+}
+
+/// 把变更集写入数据库：先删除，再按主键 upsert 新增与修改的行
+fn apply_changes(transaction: &mut Transaction<'_>, changes: &ChangeSet) -> Result<()> {
+    transaction
+        .execute(
+            "DELETE FROM file_nodes WHERE file_path::text = ANY($1)",
+            &[&changes.assets.removed],
+        )
+        .context("删除资源失败")?;
+    transaction
+        .execute(
+            "DELETE FROM directory_nodes WHERE path::text = ANY($1)",
+            &[&changes.nodes.removed],
+        )
+        .context("删除节点失败")?;
+
+    let upsert_node = transaction
+        .prepare(
+            "INSERT INTO directory_nodes
+                (path, has_subnodes, raw_path, raw_filename, has_layout, metadata, sort_key, visibility,
+                 layout_path)
+             VALUES ($1::text::ltree, $2, $3, $4, $5, $6::text::jsonb, $7, $8, $9)
+             ON CONFLICT (path) DO UPDATE SET
+                has_subnodes = EXCLUDED.has_subnodes,
+                raw_path = EXCLUDED.raw_path,
+                raw_filename = EXCLUDED.raw_filename,
+                has_layout = EXCLUDED.has_layout,
+                metadata = EXCLUDED.metadata,
+                sort_key = EXCLUDED.sort_key,
+                visibility = EXCLUDED.visibility,
+                layout_path = EXCLUDED.layout_path",
+        )
+        .context("无法准备节点写入语句")?;
+    for row in changes.nodes.upserts() {
+        transaction
+            .execute(
+                &upsert_node,
+                &[
+                    &row.path,
+                    &row.has_subnodes,
+                    &row.raw_path,
+                    &row.raw_filename,
+                    &row.has_layout,
+                    &row.metadata,
+                    &row.sort_key,
+                    &row.visibility.as_str(),
+                    &row.layout_path,
+                ],
+            )
+            .with_context(|| format!("写入节点失败: {}", row.raw_path))?;
+    }
+
+    let upsert_asset = transaction
+        .prepare(
+            "INSERT INTO file_nodes
+                (file_path, raw_path, raw_filename, content, sort_key, visibility,
+                 file_size, file_type, modified_time, content_hash, width, height, duration_ms, kind)
+             VALUES ($1::text::ltree, $2, $3, $4, $5, $6,
+                     $7, $8, $9::text::timestamptz, $10, $11, $12, $13, $14)
+             ON CONFLICT (file_path) DO UPDATE SET
+                raw_path = EXCLUDED.raw_path,
+                raw_filename = EXCLUDED.raw_filename,
+                content = EXCLUDED.content,
+                sort_key = EXCLUDED.sort_key,
+                visibility = EXCLUDED.visibility,
+                file_size = EXCLUDED.file_size,
+                file_type = EXCLUDED.file_type,
+                modified_time = EXCLUDED.modified_time,
+                content_hash = EXCLUDED.content_hash,
+                width = EXCLUDED.width,
+                height = EXCLUDED.height,
+                duration_ms = EXCLUDED.duration_ms,
+                kind = EXCLUDED.kind",
+        )
+        .context("无法准备资源写入语句")?;
+    let to_i32 = |value: Option<u32>| value.and_then(|v| i32::try_from(v).ok());
+    for row in changes.assets.upserts() {
+        transaction
+            .execute(
+                &upsert_asset,
+                &[
+                    &row.file_path,
+                    &row.raw_path,
+                    &row.raw_filename,
+                    &row.content,
+                    &row.sort_key,
+                    &row.visibility.as_str(),
+                    &i64::try_from(row.file_size).unwrap_or(i64::MAX),
+                    &row.file_type,
+                    &row.modified_time,
+                    &row.content_hash,
+                    &to_i32(row.width),
+                    &to_i32(row.height),
+                    &row.duration_ms.and_then(|v| i64::try_from(v).ok()),
+                    &row.kind,
+                ],
+            )
+            .with_context(|| format!("写入资源失败: {}", row.raw_path))?;
+    }
+    Ok(())
+}
+
+/// 以 `COPY ... FROM STDIN` 导入扫描生成的 CSV（含表头），返回导入的行数；
+/// `extra_options` 追加在 `WITH (...)` 中
+fn copy_csv(
+    transaction: &mut Transaction<'_>,
+    table: &str,
+    columns: &str,
+    extra_options: &str,
+    csv: &[u8],
+) -> Result<u64> {
+    let statement =
+        format!("COPY {table}({columns}) FROM STDIN WITH (FORMAT csv, HEADER true{extra_options})");
+    let mut writer = transaction
+        .copy_in(statement.as_str())
+        .with_context(|| format!("无法开始导入 {table}"))?;
+    writer
+        .write_all(csv)
+        .with_context(|| format!("导入 {table} 失败"))?;
+    writer
+        .finish()
+        .with_context(|| format!("导入 {table} 失败"))
+}
+
+/// 需要设置 `TP_TEST_DATABASE_URL`（数据库需已安装 ltree 扩展），未设置时跳过；
+/// 每个测试在独立的 schema 中建表，开始前清理同名 schema，互不干扰
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visibility::Visibility;
+
+    /// 连接测试库并把 search_path 指向独立的 schema；未配置测试数据库时返回 `None`
+    fn test_client(schema: &str) -> Option<Client> {
+        let Ok(url) = std::env::var("TP_TEST_DATABASE_URL") else {
+            eprintln!("未设置 TP_TEST_DATABASE_URL，跳过数据库测试");
+            return None;
+        };
+        let mut admin = connect(&url).unwrap();
+        // 扩展装在 public 中，避免随测试 schema 一起被删除；并发创建时可能报错，忽略即可
+        let _ = admin.batch_execute("CREATE EXTENSION IF NOT EXISTS ltree WITH SCHEMA public");
+        admin
+            .batch_execute(&format!(
+                "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema};"
+            ))
+            .unwrap();
+
+        let mut config: postgres::Config = url.parse().unwrap();
+        config.options(&format!("-c search_path={schema},public"));
+        Some(config.connect(NoTls).unwrap())
+    }
+
+    fn node(path: &str, metadata: &str) -> NodeRow {
+        NodeRow {
+            path: path.into(),
+            has_subnodes: false,
+            raw_path: path.replace('.', "/"),
+            raw_filename: path.rsplit('.').next().unwrap().into(),
+            has_layout: false,
+            metadata: metadata.into(),
+            sort_key: "1".into(),
+            visibility: Visibility::Public,
+            layout_path: None,
+        }
+    }
+
+    fn asset(file_path: &str, content: &str) -> AssetRow {
+        AssetRow {
+            file_path: file_path.into(),
+            raw_path: file_path.replace('.', "/"),
+            raw_filename: file_path.rsplit('.').next().unwrap().into(),
+            content: content.into(),
+            sort_key: "1".into(),
+            visibility: Visibility::Public,
+            file_size: 5,
+            file_type: "text/markdown".into(),
+            modified_time: "2024-01-01T00:00:00Z".into(),
+            content_hash: "0123456789abcdef".into(),
+            width: None,
+            height: None,
+            duration_ms: None,
+            kind: "markdown".into(),
+            modified_ns: 0,
+        }
+    }
+
+    /// front matter 经 jsonb 存储后键顺序与空白会变化，比较前统一格式
+    fn normalized(mut nodes: Vec<NodeRow>) -> Vec<NodeRow> {
+        for node in &mut nodes {
+            let value: serde_json::Value = serde_json::from_str(&node.metadata).unwrap();
+            node.metadata = value.to_string();
+        }
+        nodes.sort_by(|a, b| a.path.cmp(&b.path));
+        nodes
+    }
+
+    fn visibility(value: String) -> Visibility {
+        serde_json::from_value(serde_json::Value::String(value)).unwrap()
+    }
+
+    fn stored_nodes(client: &mut Client) -> Vec<NodeRow> {
+        let rows = client
+            .query(
+                "SELECT path::text, has_subnodes, raw_path, raw_filename, has_layout,
+                        metadata::text, sort_key, visibility, layout_path
+                 FROM directory_nodes",
+                &[],
+            )
+            .unwrap();
+        normalized(
+            rows.into_iter()
+                .map(|row| NodeRow {
+                    path: row.get(0),
+                    has_subnodes: row.get(1),
+                    raw_path: row.get(2),
+                    raw_filename: row.get(3),
+                    has_layout: row.get(4),
+                    metadata: row.get(5),
+                    sort_key: row.get(6),
+                    visibility: visibility(row.get(7)),
+                    layout_path: row.get(8),
+                })
+                .collect(),
+        )
+    }
+
+    fn stored_assets(client: &mut Client) -> Vec<AssetRow> {
+        let rows = client
+            .query(
+                "SELECT file_path::text, raw_path, raw_filename, content, sort_key, visibility,
+                        file_size, file_type,
+                        to_char(modified_time AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"'),
+                        content_hash, width, height, duration_ms, kind
+                 FROM file_nodes ORDER BY file_path",
+                &[],
+            )
+            .unwrap();
+        rows.into_iter()
+            .map(|row| AssetRow {
+                file_path: row.get(0),
+                raw_path: row.get(1),
+                raw_filename: row.get(2),
+                content: row.get(3),
+                sort_key: row.get(4),
+                visibility: visibility(row.get(5)),
+                file_size: row.get::<_, i64>(6) as u64,
+                file_type: row.get(7),
+                modified_time: row.get(8),
+                content_hash: row.get(9),
+                width: row.get::<_, Option<i32>>(10).map(|v| v as u32),
+                height: row.get::<_, Option<i32>>(11).map(|v| v as u32),
+                duration_ms: row.get::<_, Option<i64>>(12).map(|v| v as u64),
+                kind: row.get(13),
+                modified_ns: 0,
+            })
+            .collect()
+    }
+
+    fn manifest(nodes: &[NodeRow], assets: &[AssetRow]) -> Manifest {
+        Manifest::build(nodes, assets)
+    }
+
+    #[test]
+    fn incremental_import_applies_added_modified_and_removed_rows() {
+        let Some(mut client) = test_client("tp_test_incremental_import") else {
+            return;
+        };
+
+        let before_nodes = vec![node("a", "{}"), node("a.b", "{}"), node("a.c", "{}")];
+        let before_assets = vec![
+            asset("a.visual_assets.cover_jpg", ""),
+            asset("a.b.visual_assets.notes_md", "旧的说明"),
+            asset("a.c.visual_assets.intro_md", "不变"),
+        ];
+        let previous = manifest(&before_nodes, &before_assets);
+        import_scan(
+            &mut client,
+            &before_nodes,
+            &before_assets,
+            None,
+            &previous.fingerprint(),
+        )
+        .unwrap();
+
+        // a 的 front matter 改变，a.b 连同其资源被删除，新增 a.d；
+        // cover 被替换（内容哈希变化），intro 不变，新增 a.d 的资源
+        let mut after_nodes = vec![
+            node("a", r#"{"year": 2024}"#),
+            node("a.c", "{}"),
+            node("a.d", "{}"),
+        ];
+        after_nodes[0].has_subnodes = true;
+        let mut cover = asset("a.visual_assets.cover_jpg", "");
+        cover.content_hash = "fedcba9876543210".into();
+        let after_assets = vec![
+            cover,
+            asset("a.c.visual_assets.intro_md", "不变"),
+            asset("a.d.visual_assets.clip_mp4", ""),
+        ];
+        let current = manifest(&after_nodes, &after_assets);
+        let changes = ChangeSet::diff(&previous, &current, &after_nodes, &after_assets);
+        assert_eq!(changes.nodes.added.len(), 1);
+        assert_eq!(changes.nodes.modified.len(), 1);
+        assert_eq!(changes.nodes.removed, ["a.b"]);
+        assert_eq!(changes.assets.added.len(), 1);
+        assert_eq!(changes.assets.modified.len(), 1);
+        assert_eq!(changes.assets.removed, ["a.b.visual_assets.notes_md"]);
+
+        let outcome = import_scan(
+            &mut client,
+            &after_nodes,
+            &after_assets,
+            Some((&changes, &previous.fingerprint())),
+            &current.fingerprint(),
+        )
+        .unwrap();
+        assert_eq!(outcome, ImportOutcome::Incremental);
+        assert_eq!(stored_nodes(&mut client), normalized(after_nodes.clone()));
+        let mut expected_assets = after_assets.clone();
+        expected_assets.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        assert_eq!(stored_assets(&mut client), expected_assets);
+    }
+
+    #[test]
+    fn stale_manifest_falls_back_to_full_import() {
+        let Some(mut client) = test_client("tp_test_stale_manifest") else {
+            return;
+        };
+
+        let nodes = vec![node("a", "{}"), node("a.b", "{}")];
+        let previous = manifest(&nodes, &[]);
+        import_scan(&mut client, &nodes, &[], None, &previous.fingerprint()).unwrap();
+        // 数据库被其它方式改写（例如 import_csv.sh 导入了另一批 CSV）
+        client
+            .batch_execute("DELETE FROM directory_nodes WHERE path = 'a.b'; TRUNCATE import_state;")
+            .unwrap();
+
+        let current_nodes = vec![node("a", "{}"), node("a.b", "{}"), node("a.c", "{}")];
+        let current = manifest(&current_nodes, &[]);
+        let changes = ChangeSet::diff(&previous, &current, &current_nodes, &[]);
+        let outcome = import_scan(
+            &mut client,
+            &current_nodes,
+            &[],
+            Some((&changes, &previous.fingerprint())),
+            &current.fingerprint(),
+        )
+        .unwrap();
+        // 只应用变更集会漏掉 a.b；全量导入后与本次扫描一致
+        assert_eq!(
+            outcome,
+            ImportOutcome::Full {
+                nodes: 3,
+                assets: 0
+            }
+        );
+        assert_eq!(stored_nodes(&mut client), normalized(current_nodes));
+
+        // 另一份清单的指纹同样不能走增量
+        let other = manifest(&[node("x", "{}")], &[]);
+        let outcome = import_scan(
+            &mut client,
+            &nodes,
+            &[],
+            Some((
+                &ChangeSet::diff(&other, &previous, &nodes, &[]),
+                &other.fingerprint(),
+            )),
+            &previous.fingerprint(),
+        )
+        .unwrap();
+        assert!(matches!(outcome, ImportOutcome::Full { .. }));
+    }
+
+    #[test]
+    fn full_import_round_trips_rows() {
+        let Some(mut client) = test_client("tp_test_full_import") else {
+            return;
+        };
+
+        let mut book = node("a.book", r#"{"title": "书, \"手工\"", "year": 2020}"#);
+        book.visibility = Visibility::Hidden;
+        book.layout_path = Some("a/book/layout.md".into());
+        let mut root = node("a", "{}");
+        root.has_subnodes = true;
+        root.sort_key = "00000000000000000001".into();
+        let nodes = vec![root, book];
+
+        // 正文含逗号、引号与换行；图片的 content 与 content_hash 为空，需按空字符串而非 NULL 写入
+        let notes = asset(
+            "a.book.visual_assets.notes_md",
+            "# 标题\n\n第一行, \"引号\"\r\n第二行",
+        );
+        let mut cover = asset("a.book.visual_assets.cover_jpg", "");
+        cover.content_hash = String::new();
+        cover.file_type = "image/jpeg".into();
+        cover.width = Some(1200);
+        cover.height = Some(800);
+        let mut clip = asset("a.visual_assets.clip_mp4", "");
+        clip.duration_ms = Some(12_345);
+        clip.visibility = Visibility::Unlisted;
+        let assets = vec![cover, notes, clip];
+
+        let outcome = import_scan(&mut client, &nodes, &assets, None, "first").unwrap();
+        assert_eq!(
+            outcome,
+            ImportOutcome::Full {
+                nodes: 2,
+                assets: 3
+            }
+        );
+        assert_eq!(stored_nodes(&mut client), normalized(nodes.clone()));
+        let mut expected_assets = assets.clone();
+        expected_assets.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        assert_eq!(stored_assets(&mut client), expected_assets);
+
+        // 再次全量导入会替换而不是追加
+        let outcome = import_scan(&mut client, &nodes[..1], &assets[..1], None, "second").unwrap();
+        assert_eq!(
+            outcome,
+            ImportOutcome::Full {
+                nodes: 1,
+                assets: 1
+            }
+        );
+        assert_eq!(stored_nodes(&mut client).len(), 1);
+        assert_eq!(stored_assets(&mut client).len(), 1);
+    }
+}
@@ -0,0 +1,684 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use image::{
+    DynamicImage, ImageDecoder, ImageFormat, ImageReader, codecs::avif::AvifEncoder, imageops,
+    metadata::Orientation,
+};
+
+use crate::db_import::{connect, import_derivatives};
+use crate::manifest::Manifest;
+use crate::util::escape_csv_field;
+use crate::visibility::Visibility;
+use crate::visual_scan::{AssetRow, scan_assets};
+
+/// image_derivatives.csv 的列，同时也是导入 `image_derivatives` 时的列顺序
+pub const DERIVATIVE_CSV_COLUMNS: &str = "file_path,content_hash,format,width,height,path";
+
+/// 默认生成的宽度（像素）
+pub const DEFAULT_WIDTHS: [u32; 4] = [320, 640, 1280, 1920];
+
+/// 可解码的原图类型；GIF 可能是动图，缩放后会丢帧，保留原图
+const SOURCE_TYPES: [&str; 3] = ["image/jpeg", "image/png", "image/webp"];
+
+/// 生成封面帧的视频类型；封面帧的衍生图同样记录在 `image_derivatives` 中
+const VIDEO_TYPES: [&str; 5] = [
+    "video/mp4",
+    "video/quicktime",
+    "video/x-m4v",
+    "video/webm",
+    "video/x-matroska",
+];
+
+/// 封面帧取自第 1 秒；不足 10 秒的视频取 1/10 处，避开开头的黑场
+const POSTER_OFFSET_MS: u64 = 1_000;
+
+/// 衍生图格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum DerivativeFormat {
+    Webp,
+    Avif,
+}
+
+impl DerivativeFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DerivativeFormat::Webp => "webp",
+            DerivativeFormat::Avif => "avif",
+        }
+    }
+}
+
+/// 衍生图的生成参数
+#[derive(Debug, Clone)]
+pub struct DerivativeOptions {
+    /// 目标宽度，只生成比原图窄的尺寸
+    pub widths: Vec<u32>,
+    pub formats: Vec<DerivativeFormat>,
+    /// 有损压缩质量（1-100）
+    pub quality: u8,
+    /// 抽取视频封面帧的 ffmpeg 可执行文件；为 `None` 时不处理视频
+    pub ffmpeg: Option<PathBuf>,
+}
+
+/// image_derivatives.csv 的一行：某张原图在某个格式、宽度下的衍生图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivativeRow {
+    /// 原图的 ltree 路径（对应 `file_nodes.file_path`）
+    pub file_path: String,
+    /// 生成时原图的内容哈希；原图替换后旧记录不再匹配
+    pub content_hash: String,
+    pub format: DerivativeFormat,
+    pub width: u32,
+    pub height: u32,
+    /// 相对于缓存目录的路径
+    pub path: String,
+}
+
+impl DerivativeRow {
+    /// 按 [`DERIVATIVE_CSV_COLUMNS`] 的顺序输出 CSV 行（不含换行）
+    pub fn csv_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.file_path,
+            self.content_hash,
+            self.format.extension(),
+            self.width,
+            self.height,
+            escape_csv_field(&self.path)
+        )
+    }
+}
+
+/// 为 visual_assets 中的图片生成缩放后的 WebP / AVIF 衍生图。
+///
+/// 衍生图写在与根目录平行的缓存目录中，路径为 `<原图相对路径>/<哈希前缀>-<宽度>.<格式>`，
+/// 已存在的文件直接复用；缓存目录中不再对应任何原图的衍生图会被删除，
+/// 本次处理出错的原图保留已有的衍生图并沿用其记录，留待下次重试。
+/// 视频通过 ffmpeg 抽取一帧作为封面，再按同样的尺寸与格式生成衍生图；
+/// 找不到或未指定 ffmpeg 时跳过视频，已有的封面帧衍生图同样保留。
+/// 隐藏节点的图片不生成衍生图。生成结果可写入数据库（整表替换）或输出为 CSV。
+pub fn run_derivatives(
+    root: &Path,
+    cache_dir: &Path,
+    ignore_file: &str,
+    options: &DerivativeOptions,
+    manifest: Option<&Path>,
+    database_url: Option<&str>,
+    output: Option<&Path>,
+) -> Result<()> {
+    ensure_separate_dirs(root, cache_dir)?;
+    let previous = match manifest {
+        Some(path) => Manifest::load(path)?,
+        None => None,
+    };
+    let assets = scan_assets(root, ignore_file, previous.as_ref())?;
+
+    let mut rows = Vec::new();
+    let mut generated = 0;
+    // 处理出错的原图对应的缓存目录，清理时保留
+    let mut failed = Vec::new();
+    let mut ffmpeg = options.ffmpeg.as_deref();
+    for asset in assets
+        .iter()
+        .filter(|asset| is_source(asset, SOURCE_TYPES.as_slice()))
+    {
+        match derive_image(root, cache_dir, asset, options) {
+            Ok((derived, count)) => {
+                rows.extend(derived);
+                generated += count;
+            }
+            Err(err) => {
+                eprintln!("跳过无法处理的图片 {}: {err:#}", asset.raw_path);
+                rows.extend(cached_rows(cache_dir, asset));
+                failed.push(cache_dir.join(&asset.raw_path));
+            }
+        }
+    }
+    for asset in assets
+        .iter()
+        .filter(|asset| is_source(asset, VIDEO_TYPES.as_slice()))
+    {
+        // 没有 ffmpeg 时视频原样跳过：保留已有的封面帧衍生图与记录
+        let Some(program) = ffmpeg else {
+            rows.extend(cached_rows(cache_dir, asset));
+            failed.push(cache_dir.join(&asset.raw_path));
+            continue;
+        };
+        match derive_poster(root, cache_dir, asset, options, program) {
+            Ok((derived, count)) => {
+                rows.extend(derived);
+                generated += count;
+            }
+            Err(err) if is_not_found(&err) => {
+                eprintln!("找不到 {}，跳过视频封面帧", program.display());
+                rows.extend(cached_rows(cache_dir, asset));
+                failed.push(cache_dir.join(&asset.raw_path));
+                ffmpeg = None;
+            }
+            Err(err) => {
+                eprintln!("跳过无法处理的视频 {}: {err:#}", asset.raw_path);
+                rows.extend(cached_rows(cache_dir, asset));
+                failed.push(cache_dir.join(&asset.raw_path));
+            }
+        }
+    }
+    let expected: HashSet<PathBuf> = rows.iter().map(|row| cache_dir.join(&row.path)).collect();
+    let removed = prune_cache(cache_dir, &expected, &failed)?;
+    println!(
+        "衍生图：共 {} 个，新生成 {generated} 个，删除过期 {removed} 个",
+        rows.len()
+    );
+
+    if let Some(output) = output {
+        let mut writer = BufWriter::new(
+            File::create(output)
+                .with_context(|| format!("无法创建输出文件: {}", output.display()))?,
+        );
+        write_derivative_csv(&rows, &mut writer)?;
+        writer
+            .flush()
+            .with_context(|| format!("写入输出文件失败: {}", output.display()))?;
+        println!("已生成: {}", output.display());
+    }
+    if let Some(database_url) = database_url {
+        let mut client = connect(database_url)?;
+        let count = import_derivatives(&mut client, &rows)?;
+        println!("已导入 {count} 条衍生图记录");
+    }
+    Ok(())
+}
+
+/// 把衍生图记录写成 CSV（含表头）
+pub fn write_derivative_csv(rows: &[DerivativeRow], writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, "{DERIVATIVE_CSV_COLUMNS}").with_context(|| "写入 CSV 表头失败")?;
+    for row in rows {
+        writeln!(writer, "{}", row.csv_line()).with_context(|| "写入 CSV 失败")?;
+    }
+    Ok(())
+}
+
+fn is_source(asset: &AssetRow, types: &[&str]) -> bool {
+    asset.visibility != Visibility::Hidden
+        && !asset.content_hash.is_empty()
+        && types.contains(&asset.file_type.as_str())
+}
+
+/// 缓存目录中的衍生图会被清理，不能与资源根目录互相包含
+fn ensure_separate_dirs(root: &Path, cache_dir: &Path) -> Result<()> {
+    fs::create_dir_all(cache_dir)
+        .with_context(|| format!("无法创建缓存目录: {}", cache_dir.display()))?;
+    let cache = fs::canonicalize(cache_dir)
+        .with_context(|| format!("无法解析缓存目录: {}", cache_dir.display()))?;
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    if cache.starts_with(&root) || root.starts_with(&cache) {
+        bail!("缓存目录不能与资源根目录互相包含: {}", cache_dir.display());
+    }
+    Ok(())
+}
+
+/// 计算一张原图需要的衍生尺寸：配置中比原图窄的宽度，
+/// 原图不超过最大配置宽度时再加上原图宽度（转换格式但不缩放）
+fn target_sizes(widths: &[u32], original: (u32, u32)) -> Vec<(u32, u32)> {
+    let (original_width, original_height) = original;
+    if original_width == 0 || original_height == 0 {
+        return Vec::new();
+    }
+    let mut targets: Vec<u32> = widths
+        .iter()
+        .copied()
+        .filter(|&width| width > 0 && width < original_width)
+        .collect();
+    if widths.iter().any(|&width| width >= original_width) {
+        targets.push(original_width);
+    }
+    targets.sort_unstable();
+    targets.dedup();
+    targets
+        .into_iter()
+        .map(|width| {
+            let height = (u64::from(original_height) * u64::from(width)
+                + u64::from(original_width) / 2)
+                / u64::from(original_width);
+            (width, (height as u32).max(1))
+        })
+        .collect()
+}
+
+/// 生成（或复用）一张原图的全部衍生图，返回记录与新生成的数量
+fn derive_image(
+    root: &Path,
+    cache_dir: &Path,
+    asset: &AssetRow,
+    options: &DerivativeOptions,
+) -> Result<(Vec<DerivativeRow>, usize)> {
+    let source = root.join(&asset.raw_path);
+    // 只读取文件头得到尺寸与 EXIF 方向，所有衍生图都已存在时不必解码
+    let mut decoder = open_decoder(&source)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let (width, height) = decoder.dimensions();
+    let original = if swaps_axes(orientation) {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    derive_sizes(cache_dir, asset, options, original, || {
+        let mut image = DynamicImage::from_decoder(decoder)
+            .with_context(|| format!("无法解码图片: {}", source.display()))?;
+        image.apply_orientation(orientation);
+        Ok(image)
+    })
+}
+
+/// 生成（或复用）一个视频封面帧的全部衍生图。
+/// 扫描时已从容器头部读出画面尺寸的，所有衍生图都已存在时不必调用 ffmpeg
+fn derive_poster(
+    root: &Path,
+    cache_dir: &Path,
+    asset: &AssetRow,
+    options: &DerivativeOptions,
+    ffmpeg: &Path,
+) -> Result<(Vec<DerivativeRow>, usize)> {
+    let source = root.join(&asset.raw_path);
+    let extract = || extract_frame(ffmpeg, &source, poster_offset_ms(asset.duration_ms));
+    match asset.width.zip(asset.height) {
+        Some(original) => derive_sizes(cache_dir, asset, options, original, extract),
+        None => {
+            let frame = extract()?;
+            let original = (frame.width(), frame.height());
+            derive_sizes(cache_dir, asset, options, original, || Ok(frame))
+        }
+    }
+}
+
+/// 按 `original` 计算目标尺寸并写出缺少的衍生图；`load` 只在需要生成时调用一次
+fn derive_sizes(
+    cache_dir: &Path,
+    asset: &AssetRow,
+    options: &DerivativeOptions,
+    original: (u32, u32),
+    load: impl FnOnce() -> Result<DynamicImage>,
+) -> Result<(Vec<DerivativeRow>, usize)> {
+    let hash_prefix = hash_prefix(asset);
+    let mut load = Some(load);
+    let mut image: Option<DynamicImage> = None;
+    let mut rows = Vec::new();
+    let mut generated = 0;
+
+    for (width, height) in target_sizes(&options.widths, original) {
+        for &format in &options.formats {
+            let relative = format!(
+                "{}/{hash_prefix}-{width}.{}",
+                asset.raw_path,
+                format.extension()
+            );
+            let target = cache_dir.join(&relative);
+            if !target.exists() {
+                if let Some(load) = load.take() {
+                    image = Some(load()?);
+                }
+                let resized = image.as_ref().expect("图片已解码").resize_exact(
+                    width,
+                    height,
+                    imageops::FilterType::Lanczos3,
+                );
+                write_derivative(&resized, format, options.quality, &target)?;
+                generated += 1;
+            }
+            rows.push(DerivativeRow {
+                file_path: asset.file_path.clone(),
+                content_hash: asset.content_hash.clone(),
+                format,
+                width,
+                height,
+                path: relative,
+            });
+        }
+    }
+    Ok((rows, generated))
+}
+
+/// 衍生图文件名中的原图哈希前缀
+fn hash_prefix(asset: &AssetRow) -> &str {
+    &asset.content_hash[..asset.content_hash.len().min(8)]
+}
+
+/// 缓存中已有的、与原图当前内容对应（哈希前缀一致）的衍生图记录，尺寸从衍生图的文件头读取。
+/// 用于本次处理出错的原图，使导入时整表替换不会丢掉仍在磁盘上的衍生图
+fn cached_rows(cache_dir: &Path, asset: &AssetRow) -> Vec<DerivativeRow> {
+    let Ok(entries) = fs::read_dir(cache_dir.join(&asset.raw_path)) else {
+        return Vec::new();
+    };
+    let mut rows: Vec<DerivativeRow> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let format = match path.extension()?.to_str()? {
+                "webp" => DerivativeFormat::Webp,
+                "avif" => DerivativeFormat::Avif,
+                _ => return None,
+            };
+            let (prefix, width) = path.file_stem()?.to_str()?.split_once('-')?;
+            if prefix != hash_prefix(asset) {
+                return None;
+            }
+            let width: u32 = width.parse().ok()?;
+            let size = imagesize::size(&path).ok()?;
+            if size.width != width as usize {
+                return None;
+            }
+            Some(DerivativeRow {
+                file_path: asset.file_path.clone(),
+                content_hash: asset.content_hash.clone(),
+                format,
+                width,
+                height: size.height.try_into().ok()?,
+                path: format!("{}/{}", asset.raw_path, path.file_name()?.to_str()?),
+            })
+        })
+        .collect();
+    rows.sort_by_key(|row| (row.width, row.format.extension()));
+    rows
+}
+
+fn poster_offset_ms(duration_ms: Option<u64>) -> u64 {
+    duration_ms.map_or(0, |duration| (duration / 10).min(POSTER_OFFSET_MS))
+}
+
+/// 调用 ffmpeg 把视频在 `offset_ms` 处的一帧输出为 PNG 并解码（ffmpeg 会按旋转信息转正画面）
+fn extract_frame(ffmpeg: &Path, video: &Path, offset_ms: u64) -> Result<DynamicImage> {
+    let output = Command::new(ffmpeg)
+        .args(["-v", "error", "-ss"])
+        .arg(format!("{}.{:03}", offset_ms / 1_000, offset_ms % 1_000))
+        .arg("-i")
+        .arg(video)
+        .args(["-frames:v", "1", "-f", "image2pipe", "-vcodec", "png", "-"])
+        .output()
+        .with_context(|| format!("无法运行 {}", ffmpeg.display()))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!(
+            "ffmpeg 未能抽取封面帧: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    image::load_from_memory_with_format(&output.stdout, ImageFormat::Png)
+        .with_context(|| format!("无法解码封面帧: {}", video.display()))
+}
+
+/// 错误是否由找不到可执行文件引起
+fn is_not_found(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
+    })
+}
+
+fn open_decoder(path: &Path) -> Result<impl ImageDecoder> {
+    ImageReader::open(path)
+        .with_context(|| format!("无法打开图片: {}", path.display()))?
+        .with_guessed_format()
+        .with_context(|| format!("无法识别图片格式: {}", path.display()))?
+        .into_decoder()
+        .with_context(|| format!("无法解码图片: {}", path.display()))
+}
+
+/// 旋转 90° / 270° 的方向会互换宽高
+fn swaps_axes(orientation: Orientation) -> bool {
+    matches!(
+        orientation,
+        Orientation::Rotate90
+            | Orientation::Rotate270
+            | Orientation::Rotate90FlipH
+            | Orientation::Rotate270FlipH
+    )
+}
+
+/// 编码并写出衍生图；先写临时文件再改名，中断时不会留下半个文件
+fn write_derivative(
+    image: &DynamicImage,
+    format: DerivativeFormat,
+    quality: u8,
+    target: &Path,
+) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+    }
+    let bytes = encode(image, format, quality)?;
+    let temp = target.with_extension(format!("{}.tmp", format.extension()));
+    fs::write(&temp, bytes).with_context(|| format!("无法写入衍生图: {}", temp.display()))?;
+    fs::rename(&temp, target).with_context(|| format!("无法写入衍生图: {}", target.display()))
+}
+
+fn encode(image: &DynamicImage, format: DerivativeFormat, quality: u8) -> Result<Vec<u8>> {
+    let rgba = image.to_rgba8();
+    match format {
+        DerivativeFormat::Webp => {
+            let encoder = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height());
+            let memory = encoder
+                .encode_simple(false, f32::from(quality))
+                .map_err(|err| anyhow::anyhow!("WebP 编码失败: {err:?}"))?;
+            Ok(memory.to_vec())
+        }
+        DerivativeFormat::Avif => {
+            let mut bytes = Vec::new();
+            // 速度 8 在体积与耗时之间取折中（1 最慢、压缩率最高，10 最快）
+            let encoder = AvifEncoder::new_with_speed_quality(&mut bytes, 8, quality);
+            DynamicImage::ImageRgba8(rgba)
+                .write_with_encoder(encoder)
+                .context("AVIF 编码失败")?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// 删除缓存目录中不在 `expected` 内的衍生图与随之变空的目录，返回删除的文件数。
+/// `kept` 中的目录（本次处理出错的原图）原样保留
+fn prune_cache(cache_dir: &Path, expected: &HashSet<PathBuf>, kept: &[PathBuf]) -> Result<usize> {
+    fn visit(
+        dir: &Path,
+        expected: &HashSet<PathBuf>,
+        kept: &[PathBuf],
+        removed: &mut usize,
+    ) -> Result<bool> {
+        let mut empty = true;
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("无法读取目录: {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if kept.contains(&path) {
+                empty = false;
+                continue;
+            }
+            if path.is_dir() {
+                if visit(&path, expected, kept, removed)? {
+                    fs::remove_dir(&path)
+                        .with_context(|| format!("无法删除目录: {}", path.display()))?;
+                } else {
+                    empty = false;
+                }
+                continue;
+            }
+            // 只清理本工具生成的文件（含中断留下的临时文件）
+            let generated = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext, "webp" | "avif" | "tmp"));
+            if generated && !expected.contains(&path) {
+                fs::remove_file(&path)
+                    .with_context(|| format!("无法删除过期衍生图: {}", path.display()))?;
+                *removed += 1;
+            } else {
+                empty = false;
+            }
+        }
+        Ok(empty)
+    }
+
+    let mut removed = 0;
+    visit(cache_dir, expected, kept, &mut removed)?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ltree_label::encode_label;
+
+    #[test]
+    fn targets_only_narrower_widths_plus_original() {
+        assert_eq!(
+            target_sizes(&DEFAULT_WIDTHS, (1000, 500)),
+            [(320, 160), (640, 320), (1000, 500)]
+        );
+        // 原图比最大配置宽度还宽时不生成原尺寸
+        assert_eq!(
+            target_sizes(&[320, 640], (4000, 3000)),
+            [(320, 240), (640, 480)]
+        );
+        assert_eq!(target_sizes(&[320], (200, 100)), [(200, 100)]);
+        assert!(target_sizes(&[320], (0, 0)).is_empty());
+    }
+
+    #[test]
+    fn poster_frame_avoids_the_opening_frames() {
+        assert_eq!(poster_offset_ms(None), 0);
+        assert_eq!(poster_offset_ms(Some(4_000)), 400);
+        assert_eq!(poster_offset_ms(Some(600_000)), POSTER_OFFSET_MS);
+
+        let missing = extract_frame(Path::new("ngt-no-such-ffmpeg"), Path::new("a.mp4"), 0);
+        assert!(is_not_found(&missing.unwrap_err()));
+    }
+
+    #[test]
+    fn generates_reuses_and_prunes_derivatives() {
+        let base = std::env::temp_dir().join(format!("ngt-derivatives-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let (root, cache) = (base.join("root"), base.join("cache"));
+        fs::create_dir_all(root.join("a/visual_assets")).unwrap();
+        image::RgbImage::from_pixel(48, 32, image::Rgb([200, 80, 40]))
+            .save(root.join("a/visual_assets/cover.png"))
+            .unwrap();
+        fs::create_dir_all(cache.join("old")).unwrap();
+        fs::write(cache.join("old/stale-320.webp"), b"stale").unwrap();
+        // 本次无法解码的原图保留上次生成的衍生图，并沿用其记录
+        fs::write(root.join("a/visual_assets/broken.png"), b"not a png").unwrap();
+        let broken_prefix = &blake3::hash(b"not a png").to_hex()[..8];
+        let broken = cache.join(format!(
+            "a/visual_assets/broken.png/{broken_prefix}-16.webp"
+        ));
+        let stale_broken = cache.join("a/visual_assets/broken.png/00000000-16.webp");
+        let previous = DynamicImage::ImageRgb8(image::RgbImage::new(16, 9));
+        write_derivative(&previous, DerivativeFormat::Webp, 80, &broken).unwrap();
+        fs::write(&stale_broken, b"previous").unwrap();
+
+        let options = DerivativeOptions {
+            widths: vec![16, 64],
+            formats: vec![DerivativeFormat::Webp],
+            quality: 80,
+            ffmpeg: None,
+        };
+        let output = base.join("derivatives.csv");
+        run_derivatives(
+            &root,
+            &cache,
+            "fileignore",
+            &options,
+            None,
+            None,
+            Some(&output),
+        )
+        .unwrap();
+
+        let csv = fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], DERIVATIVE_CSV_COLUMNS);
+        assert_eq!(lines.len(), 4);
+        let cover = format!("a.visual_assets.{},", encode_label("cover.png").0);
+        let cover_lines: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|l| l.starts_with(&cover))
+            .collect();
+        assert_eq!(cover_lines.len(), 2);
+        assert!(cover_lines[0].contains(",webp,16,11,a/visual_assets/cover.png/"));
+        assert!(cover_lines[1].contains(",webp,48,32,"));
+        let broken_line = format!(
+            "a.visual_assets.{},{},webp,16,9,a/visual_assets/broken.png/{broken_prefix}-16.webp",
+            encode_label("broken.png").0,
+            blake3::hash(b"not a png").to_hex()
+        );
+        assert!(lines.contains(&broken_line.as_str()), "{csv}");
+
+        let small = cache.join(cover_lines[0].rsplit(',').next().unwrap());
+        let decoded = image::open(&small).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (16, 11));
+        assert!(!cache.join("old").exists());
+        assert!(broken.exists());
+        assert_eq!(fs::read(&stale_broken).unwrap(), b"previous");
+
+        // 已存在的衍生图直接复用
+        fs::write(&small, b"kept").unwrap();
+        run_derivatives(&root, &cache, "fileignore", &options, None, None, None).unwrap();
+        assert_eq!(fs::read(&small).unwrap(), b"kept");
+
+        assert!(ensure_separate_dirs(&root, &root.join("cache")).is_err());
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn keeps_poster_derivatives_without_ffmpeg() {
+        let base = std::env::temp_dir().join(format!("ngt-posters-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let (root, cache) = (base.join("root"), base.join("cache"));
+        fs::create_dir_all(root.join("a/visual_assets")).unwrap();
+        for name in ["one.mp4", "two.mp4"] {
+            fs::write(root.join("a/visual_assets").join(name), name).unwrap();
+        }
+        let posters: Vec<PathBuf> = ["one.mp4", "two.mp4"]
+            .into_iter()
+            .map(|name| {
+                let prefix = &blake3::hash(name.as_bytes()).to_hex()[..8];
+                cache.join(format!("a/visual_assets/{name}/{prefix}-16.webp"))
+            })
+            .collect();
+        let frame = DynamicImage::ImageRgb8(image::RgbImage::new(16, 9));
+        for poster in &posters {
+            write_derivative(&frame, DerivativeFormat::Webp, 80, poster).unwrap();
+        }
+
+        let mut options = DerivativeOptions {
+            widths: vec![16],
+            formats: vec![DerivativeFormat::Webp],
+            quality: 80,
+            ffmpeg: None,
+        };
+        let output = base.join("derivatives.csv");
+        for ffmpeg in [None, Some(PathBuf::from("ngt-no-such-ffmpeg"))] {
+            options.ffmpeg = ffmpeg;
+            run_derivatives(
+                &root,
+                &cache,
+                "fileignore",
+                &options,
+                None,
+                None,
+                Some(&output),
+            )
+            .unwrap();
+            assert!(posters.iter().all(|poster| poster.exists()));
+            let csv = fs::read_to_string(&output).unwrap();
+            assert_eq!(csv.lines().filter(|l| l.contains(",webp,16,9,")).count(), 2);
+        }
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
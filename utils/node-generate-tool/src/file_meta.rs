@@ -0,0 +1,175 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result};
+
+use crate::manifest::AssetEntry;
+use crate::video_meta;
+
+/// 资源文件的元数据，对应 `file_nodes` 的 file_size / file_type / modified_time /
+/// content_hash / width / height / duration_ms 列
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileMeta {
+    pub size: u64,
+    /// MIME 类型：优先按文件内容识别，识别不出时按扩展名推断
+    pub mime: String,
+    /// 修改时间，自 Unix 纪元起的纳秒数（读取失败时为 0）
+    pub modified_ns: u64,
+    /// 文件内容的 BLAKE3 哈希（十六进制），读取失败时为空
+    pub hash: String,
+    /// 图片或视频的像素尺寸（宽, 高），无法识别时为 `None`
+    pub dimensions: Option<(u32, u32)>,
+    /// 视频时长（毫秒），非视频或容器中没有时长时为 `None`
+    pub duration_ms: Option<u64>,
+}
+
+impl FileMeta {
+    /// 读取文件元数据；`previous` 为上一次清单中的记录，
+    /// 大小与修改时间都未变化时沿用其哈希，不再读取整个文件
+    pub fn read(path: &Path, previous: Option<&AssetEntry>) -> Self {
+        let (size, modified_ns) = file_stamp(path).unwrap_or_default();
+        let hash = previous
+            .filter(|old| old.size == size && old.mtime_ns == modified_ns && modified_ns != 0)
+            .map(|old| old.hash.clone())
+            .unwrap_or_else(|| {
+                hash_file(path).unwrap_or_else(|err| {
+                    eprintln!("无法计算文件哈希: {err:#}");
+                    String::new()
+                })
+            });
+        let mime = detect_mime(path);
+        let (dimensions, duration_ms) = if mime.starts_with("image/") {
+            let dimensions = imagesize::size(path)
+                .ok()
+                .and_then(|size| Some((size.width.try_into().ok()?, size.height.try_into().ok()?)));
+            (dimensions, None)
+        } else if mime.starts_with("video/") {
+            let video = video_meta::probe(path, &mime).unwrap_or_default();
+            (video.dimensions, video.duration_ms)
+        } else {
+            (None, None)
+        };
+
+        Self {
+            size,
+            mime,
+            modified_ns,
+            hash,
+            dimensions,
+            duration_ms,
+        }
+    }
+
+    /// 修改时间的 RFC 3339 表示（UTC，精确到秒）
+    pub fn modified_rfc3339(&self) -> String {
+        format_rfc3339(self.modified_ns / 1_000_000_000)
+    }
+}
+
+fn detect_mime(path: &Path) -> String {
+    match infer::get_from_path(path) {
+        Ok(Some(kind)) => kind.mime_type().to_string(),
+        _ => mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .essence_str()
+            .to_string(),
+    }
+}
+
+/// 文件大小与修改时间
+fn file_stamp(path: &Path) -> Result<(u64, u64)> {
+    let metadata =
+        fs::metadata(path).with_context(|| format!("无法读取文件信息: {}", path.display()))?;
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX))
+        .unwrap_or(0);
+    Ok((metadata.len(), modified_ns))
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("无法打开文件: {}", path.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    hasher
+        .update_reader(file)
+        .with_context(|| format!("无法读取文件: {}", path.display()))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// 把 Unix 时间戳（秒）格式化为 `YYYY-MM-DDTHH:MM:SSZ`
+fn format_rfc3339(secs: u64) -> String {
+    let days = secs / 86_400;
+    let rem = secs % 86_400;
+    // 公历日期换算（Howard Hinnant 的 civil_from_days 算法）
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_unix_timestamps() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_rfc3339(1_709_210_096), "2024-02-29T12:34:56Z");
+        assert_eq!(format_rfc3339(4_102_444_799), "2099-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn reads_image_dimensions_mime_and_hash() {
+        let dir = std::env::temp_dir().join(format!("ngt-file-meta-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // 1x1 PNG 的签名与 IHDR 块，扩展名故意写错以验证按内容识别
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&3u32.to_be_bytes());
+        png.extend_from_slice(&2u32.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+        let image = dir.join("cover.jpg");
+        fs::write(&image, &png).unwrap();
+        let meta = FileMeta::read(&image, None);
+        assert_eq!(meta.mime, "image/png");
+        assert_eq!(meta.dimensions, Some((3, 2)));
+        assert_eq!(meta.size, png.len() as u64);
+        assert_eq!(meta.hash, blake3::hash(&png).to_hex().to_string());
+
+        let note = dir.join("intro.md");
+        fs::write(&note, "# intro\n").unwrap();
+        let meta = FileMeta::read(&note, None);
+        assert_eq!(meta.mime, "text/markdown");
+        assert_eq!(meta.dimensions, None);
+
+        // 大小与修改时间未变时沿用清单中的哈希
+        let previous = AssetEntry {
+            raw_path: "intro.md".into(),
+            size: meta.size,
+            mtime_ns: meta.modified_ns,
+            hash: "cached".into(),
+            row: String::new(),
+        };
+        assert_eq!(FileMeta::read(&note, Some(&previous)).hash, "cached");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
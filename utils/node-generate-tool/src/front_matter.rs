@@ -0,0 +1,209 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::visibility::Visibility;
+
+/// 节点目录下的排版文件名（对应 node-generate-tool.toml 的 `layout_files`）
+pub const LAYOUT_FILE: &str = "layout.md";
+
+/// `layout.md` 头部的作品信息
+///
+/// 支持两种写法：以 `---` 包围的 YAML，或以 `+++` 包围的 TOML。
+/// 未列出的字段会被忽略；序列化时省略空值，写入 CSV 的 `metadata` 列（JSON）。
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrontMatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    /// 允许写成数字或带货币符号的文本（如 `1200`、`"¥1200"`、`"面议"`）
+    #[serde(
+        deserialize_with = "string_or_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub price: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<i64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// `public` / `unlisted` / `hidden`；单独写入 CSV 的 `visibility` 列，不进入 metadata
+    #[serde(skip_serializing)]
+    pub visibility: Option<Visibility>,
+}
+
+impl FrontMatter {
+    /// 解析 markdown 文本开头的 front matter；没有 front matter 时返回默认值
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let Some((delimiter, rest)) = ["---", "+++"]
+            .into_iter()
+            .find_map(|d| strip_delimiter_line(text, d).map(|rest| (d, rest)))
+        else {
+            return Ok(Self::default());
+        };
+
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == delimiter {
+                let block = &rest[..offset];
+                if block.trim().is_empty() {
+                    return Ok(Self::default());
+                }
+                return if delimiter == "---" {
+                    serde_yaml::from_str(block).context("YAML front matter 格式错误")
+                } else {
+                    toml::from_str(block).context("TOML front matter 格式错误")
+                };
+            }
+            offset += line.len();
+        }
+
+        bail!("front matter 缺少结束分隔符 {delimiter}")
+    }
+
+    /// 读取排版文件（如 `layout.md`）的 front matter；格式错误时给出提示并按空值处理
+    pub fn from_layout(layout: &Path) -> Self {
+        let Ok(text) = fs::read_to_string(layout) else {
+            return Self::default();
+        };
+        match Self::parse(&text) {
+            Ok(front_matter) => front_matter,
+            Err(err) => {
+                eprintln!("忽略无效的 front matter: {}: {err:#}", layout.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// 节点自身声明的可见性；`hidden: true` 等同于 `visibility: hidden`
+    pub fn visibility(&self) -> Visibility {
+        let declared = self.visibility.unwrap_or_default();
+        if self.hidden {
+            declared.max(Visibility::Hidden)
+        } else {
+            declared
+        }
+    }
+
+    /// 序列化为写入 CSV / JSONB 的 JSON 文本
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// 若首行恰为分隔符，返回其后的内容
+fn strip_delimiter_line<'a>(text: &'a str, delimiter: &str) -> Option<&'a str> {
+    let (first, rest) = text.split_once('\n')?;
+    (first.trim_end() == delimiter).then_some(rest)
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Text(String),
+        Integer(i64),
+        Float(f64),
+    }
+
+    Ok(
+        Option::<Raw>::deserialize(deserializer)?.map(|raw| match raw {
+            Raw::Text(text) => text,
+            Raw::Integer(n) => n.to_string(),
+            Raw::Float(n) => n.to_string(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_yaml_front_matter() {
+        let text = "---\ntitle: 反复之书\nmaterial: 麻线, 手工纸\nyear: 2023\nprice: 1200\ntags: [book, handmade]\norder: 2\n---\n\n# 正文\n";
+        let fm = FrontMatter::parse(text).unwrap();
+        assert_eq!(fm.title.as_deref(), Some("反复之书"));
+        assert_eq!(fm.material.as_deref(), Some("麻线, 手工纸"));
+        assert_eq!(fm.year, Some(2023));
+        assert_eq!(fm.price.as_deref(), Some("1200"));
+        assert_eq!(fm.tags, ["book", "handmade"]);
+        assert_eq!(fm.order, Some(2));
+        assert!(!fm.hidden);
+    }
+
+    #[test]
+    fn parses_toml_front_matter() {
+        let text = "+++\ntitle = \"Painting\"\nprice = \"¥800\"\nhidden = true\n+++\nbody";
+        let fm = FrontMatter::parse(text).unwrap();
+        assert_eq!(fm.title.as_deref(), Some("Painting"));
+        assert_eq!(fm.price.as_deref(), Some("¥800"));
+        assert!(fm.hidden);
+        assert_eq!(fm.visibility(), Visibility::Hidden);
+    }
+
+    #[test]
+    fn parses_visibility_without_serializing_it() {
+        let fm = FrontMatter::parse(
+            "---
+visibility: unlisted
+---
+",
+        )
+        .unwrap();
+        assert_eq!(fm.visibility(), Visibility::Unlisted);
+        assert_eq!(fm.to_json(), "{}");
+        assert!(
+            FrontMatter::parse(
+                "---
+visibility: secret
+---
+"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn missing_or_empty_front_matter_is_default() {
+        for text in [
+            "# 只有正文\n",
+            "",
+            "---\n---\nbody",
+            "----\nnot: front matter\n",
+        ] {
+            assert_eq!(
+                FrontMatter::parse(text).unwrap(),
+                FrontMatter::default(),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unterminated_or_malformed_blocks() {
+        assert!(FrontMatter::parse("---\ntitle: x\n").is_err());
+        assert!(FrontMatter::parse("---\nyear: [1\n---\n").is_err());
+        assert!(FrontMatter::parse("+++\ntitle = \n+++\n").is_err());
+    }
+
+    #[test]
+    fn serializes_only_present_fields() {
+        let fm = FrontMatter {
+            title: Some("A \"quoted\" title".into()),
+            ..Default::default()
+        };
+        assert_eq!(fm.to_json(), r#"{"title":"A \"quoted\" title"}"#);
+        assert_eq!(FrontMatter::default().to_json(), "{}");
+    }
+}
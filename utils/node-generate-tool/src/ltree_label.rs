@@ -0,0 +1,316 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+
+use crate::util::sanitize_ltree_label;
+
+/// 哈希后缀的长度（十六进制字符数）
+const HASH_SUFFIX_LEN: usize = 8;
+
+/// ltree 单个标签的最大长度，与后端 `node_path::MAX_LABEL_LEN` 一致
+pub const MAX_LABEL_LEN: usize = 255;
+
+/// 标签与旧规则（[`sanitize_ltree_label`]）不同的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameReason {
+    /// 名称含非 ASCII 字符，旧规则会把它们全部替换为 `_`
+    NonAscii,
+    /// 名称含字母、数字、下划线以外的 ASCII 字符（如 `.`、`-`、空格）
+    InvalidChars,
+    /// 名称本身合法，但超过 [`MAX_LABEL_LEN`]
+    TooLong,
+}
+
+impl RenameReason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            RenameReason::NonAscii => "含非 ASCII 字符",
+            RenameReason::InvalidChars => "含字母、数字、下划线以外的字符",
+            RenameReason::TooLong => "超过标签长度上限",
+        }
+    }
+}
+
+/// 标签被改写的一条记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedLabel {
+    /// 相对于根目录的原始路径
+    pub raw_path: String,
+    /// 按旧规则得到的标签
+    pub legacy: String,
+    pub label: String,
+    pub reason: RenameReason,
+}
+
+/// 把单个文件或目录名编码为 ltree 标签。
+///
+/// - 只含 ASCII 字母、数字和下划线的名称原样使用；
+/// - 其余名称保留其中可读的 ASCII 部分（非法字符视为分隔），并追加哈希后缀；
+///   可读部分会被截短，使整个标签不超过 [`MAX_LABEL_LEN`]。
+///
+/// 结果只取决于名称本身：哈希取自完整的原始名称，不随同级条目的增删或扫描顺序变化，
+/// `a-b` 与 `a b` 等清理后相同的名称也得到不同的标签；
+/// 哈希后缀仍可能撞上其它名称，由 [`LabelResolver`] 检查。
+/// 原始名称保存在 `raw_filename` / `raw_path` 中，用于展示和反查。
+pub fn encode_label(name: &str) -> (String, Option<RenameReason>) {
+    let legacy = sanitize_ltree_label(name);
+    let reason = if legacy != name {
+        if name.is_ascii() {
+            RenameReason::InvalidChars
+        } else {
+            RenameReason::NonAscii
+        }
+    } else if name.len() > MAX_LABEL_LEN {
+        RenameReason::TooLong
+    } else {
+        return (legacy, None);
+    };
+
+    let mut readable = legacy
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    // 可读部分只含 ASCII，按字节截短即可
+    readable.truncate(MAX_LABEL_LEN - 1 - HASH_SUFFIX_LEN);
+    let readable = readable.trim_end_matches('_');
+    let hash = blake3::hash(name.as_bytes()).to_hex();
+    let hash = &hash[..HASH_SUFFIX_LEN];
+    let label = if readable.is_empty() {
+        hash.to_string()
+    } else {
+        format!("{readable}_{hash}")
+    };
+    (label, Some(reason))
+}
+
+/// 把相对路径编码为 ltree 路径，记录标签与旧规则不同的条目，
+/// 并检查不同条目是否得到了相同的 ltree 路径
+#[derive(Debug, Default)]
+pub struct LabelResolver {
+    renamed: BTreeMap<PathBuf, RenamedLabel>,
+    /// 已分配的 ltree 路径及其对应的相对路径
+    owners: HashMap<String, PathBuf>,
+    /// 被多个条目共用的 ltree 路径及这些条目的原始路径
+    collisions: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl LabelResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 相对路径对应的 ltree 路径；含非 UTF-8 名称或路径为空时返回 `None`
+    pub fn ltree(&mut self, rel: &Path) -> Option<String> {
+        let mut rel_prefix = PathBuf::new();
+        let mut parts = Vec::new();
+        for comp in rel.components() {
+            let Component::Normal(name) = comp else {
+                continue;
+            };
+            let name_str = name.to_str()?;
+            if name_str.is_empty() {
+                continue;
+            }
+            rel_prefix.push(name);
+            let (label, reason) = encode_label(name_str);
+            if let Some(reason) = reason {
+                self.renamed
+                    .entry(rel_prefix.clone())
+                    .or_insert_with(|| RenamedLabel {
+                        raw_path: rel_prefix.to_string_lossy().replace('\\', "/"),
+                        legacy: sanitize_ltree_label(name_str),
+                        label: label.clone(),
+                        reason,
+                    });
+            }
+            parts.push(label);
+
+            let ltree = parts.join(".");
+            let owner = self
+                .owners
+                .entry(ltree.clone())
+                .or_insert_with(|| rel_prefix.clone());
+            if *owner != rel_prefix {
+                let raw_paths = self.collisions.entry(ltree).or_default();
+                for path in [&*owner, &rel_prefix] {
+                    raw_paths.insert(path.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        Some(parts.join("."))
+    }
+
+    /// 已解析的路径中有不同条目得到相同 ltree 路径时返回错误，列出冲突的条目。
+    /// 导入时它们会违反主键约束或被合并为同一条记录，需要重命名其中之一
+    pub fn ensure_unique(&self) -> Result<()> {
+        if self.collisions.is_empty() {
+            return Ok(());
+        }
+        let details = self
+            .collisions
+            .iter()
+            .map(|(ltree, raw_paths)| {
+                let raw_paths: Vec<&str> = raw_paths.iter().map(String::as_str).collect();
+                format!("  {ltree}: {}", raw_paths.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        bail!("以下条目的 ltree 路径相同，请重命名其中之一:\n{details}")
+    }
+
+    /// 已解析的路径中标签被改写的条目，按路径排序
+    pub fn renamed(&self) -> Vec<RenamedLabel> {
+        self.renamed.values().cloned().collect()
+    }
+}
+
+/// 打印标签改写报告（输出到标准错误，不影响 CSV 输出）
+pub fn print_label_report(renamed: &[RenamedLabel]) {
+    if renamed.is_empty() {
+        return;
+    }
+    eprintln!(
+        "以下 {} 个目录或文件的 ltree 标签与旧规则不同:",
+        renamed.len()
+    );
+    for entry in renamed {
+        eprintln!(
+            "  {}: {} -> {}（{}）",
+            entry.raw_path,
+            entry.legacy,
+            entry.label,
+            entry.reason.describe()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_names_keep_their_labels() {
+        assert_eq!(
+            encode_label("1_OnceAndOnceAgain"),
+            ("1_OnceAndOnceAgain".to_string(), None)
+        );
+        assert_eq!(encode_label("a_b"), ("a_b".to_string(), None));
+    }
+
+    #[test]
+    fn other_ascii_names_always_get_hash_suffix() {
+        let (cover, reason) = encode_label("cover.jpg");
+        assert_eq!(reason, Some(RenameReason::InvalidChars));
+        assert!(cover.starts_with("cover_jpg_"), "{cover}");
+        assert_eq!(cover.len(), "cover_jpg_".len() + HASH_SUFFIX_LEN);
+
+        // 清理后相同的名称得到不同的标签，且都不会占用原样合法的 `a_b`
+        let (hyphen, _) = encode_label("a-b");
+        let (space, _) = encode_label("a b");
+        assert!(hyphen.starts_with("a_b_") && space.starts_with("a_b_"));
+        assert_ne!(hyphen, space);
+        assert_ne!(hyphen, "a_b");
+    }
+
+    #[test]
+    fn unicode_names_get_distinct_stable_labels() {
+        let (works, reason) = encode_label("作品");
+        let (articles, _) = encode_label("文章");
+        assert_eq!(reason, Some(RenameReason::NonAscii));
+        assert_ne!(works, articles);
+        assert_eq!(works.len(), HASH_SUFFIX_LEN);
+        assert!(works.chars().all(|c| c.is_ascii_hexdigit()));
+
+        let (mixed, _) = encode_label("2024 作品集.v2");
+        assert!(mixed.starts_with("2024_v2_"), "{mixed}");
+        let (accented, _) = encode_label("café");
+        assert!(accented.starts_with("caf_"), "{accented}");
+        assert_ne!(encode_label("🎨").0, encode_label("🖌").0);
+    }
+
+    #[test]
+    fn long_names_are_truncated_to_fit_a_label() {
+        let long_valid = "a".repeat(MAX_LABEL_LEN + 10);
+        let (label, reason) = encode_label(&long_valid);
+        assert_eq!(reason, Some(RenameReason::TooLong));
+        assert_eq!(label.len(), MAX_LABEL_LEN);
+        assert!(label.starts_with("aaa"));
+        assert_ne!(label, encode_label(&"a".repeat(MAX_LABEL_LEN + 11)).0);
+        assert_eq!(
+            encode_label(&"a".repeat(MAX_LABEL_LEN)),
+            ("a".repeat(MAX_LABEL_LEN), None)
+        );
+
+        // 文件系统允许 255 字节的名称，加上哈希后缀后同样不能超出
+        let long_file = format!("{}.png", "b".repeat(251));
+        let (label, reason) = encode_label(&long_file);
+        assert_eq!(reason, Some(RenameReason::InvalidChars));
+        assert!(label.len() <= MAX_LABEL_LEN, "{}", label.len());
+        let (label, _) = encode_label(&format!("{}作品", "c_".repeat(130)));
+        assert!(
+            label.len() <= MAX_LABEL_LEN && !label.contains("__"),
+            "{label}"
+        );
+    }
+
+    #[test]
+    fn resolver_reports_names_that_share_a_label() {
+        let encoded = encode_label("foo-").0;
+        let mut resolver = LabelResolver::new();
+        assert_eq!(
+            resolver.ltree(Path::new("x/foo-")).unwrap(),
+            format!("x.{encoded}")
+        );
+        // 原样合法、恰好与编码结果相同的名称
+        resolver
+            .ltree(Path::new(&format!("x/{encoded}/y")))
+            .unwrap();
+        resolver.ltree(Path::new("x/foo-/z")).unwrap();
+        resolver.ltree(Path::new("x/foo-")).unwrap();
+
+        let err = resolver.ensure_unique().unwrap_err().to_string();
+        assert!(
+            err.contains(&format!("x.{encoded}: x/foo-, x/{encoded}")),
+            "{err}"
+        );
+        assert!(!err.contains("/y") && !err.contains("/z"), "{err}");
+
+        let mut distinct = LabelResolver::new();
+        distinct.ltree(Path::new("a-b/c")).unwrap();
+        distinct.ltree(Path::new("a b/c")).unwrap();
+        distinct.ensure_unique().unwrap();
+    }
+
+    #[test]
+    fn resolver_labels_depend_only_on_each_name() {
+        let mut resolver = LabelResolver::new();
+        let hyphen = resolver.ltree(Path::new("a-b")).unwrap();
+        assert_eq!(resolver.ltree(Path::new("a_b")).unwrap(), "a_b");
+        // 同级是否存在其它条目不影响结果
+        assert_eq!(
+            LabelResolver::new().ltree(Path::new("a-b")).unwrap(),
+            hyphen
+        );
+
+        let nested = resolver.ltree(Path::new("作品/文章")).unwrap();
+        let top = resolver.ltree(Path::new("文章")).unwrap();
+        assert_eq!(nested, format!("{}.{top}", encode_label("作品").0));
+
+        let renamed = resolver.renamed();
+        let paths: Vec<&str> = renamed
+            .iter()
+            .map(|entry| entry.raw_path.as_str())
+            .collect();
+        assert_eq!(paths, ["a-b", "作品", "作品/文章", "文章"]);
+        assert_eq!(renamed[0].legacy, "a_b");
+        assert_eq!(renamed[0].reason, RenameReason::InvalidChars);
+        assert_eq!(renamed[1].reason, RenameReason::NonAscii);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::node_scan::NodeRow;
+use crate::visual_scan::AssetRow;
+
+/// 清单格式版本，结构变化时递增；版本不符的旧清单按不存在处理（即全量导入）
+const MANIFEST_VERSION: u32 = 1;
+
+/// 上一次导入时的扫描快照，键为 ltree 路径。
+///
+/// 节点只记录行内容的哈希；资源额外记录文件大小、修改时间与文件内容哈希，
+/// 下一次扫描时大小与修改时间都未变化的文件沿用旧哈希，不再重新读取。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub nodes: BTreeMap<String, NodeEntry>,
+    pub assets: BTreeMap<String, AssetEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeEntry {
+    pub raw_path: String,
+    /// 整行（含 front matter、排序键与可见性）的哈希
+    pub row: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetEntry {
+    pub raw_path: String,
+    pub size: u64,
+    /// 修改时间，自 Unix 纪元起的纳秒数
+    pub mtime_ns: u64,
+    /// 文件内容的 BLAKE3 哈希
+    pub hash: String,
+    /// 整行的哈希
+    pub row: String,
+}
+
+impl Manifest {
+    /// 读取清单；文件不存在或版本不符时返回 `None`
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("无法读取清单: {}", path.display()))?;
+        let manifest: Manifest = serde_json::from_str(&text)
+            .with_context(|| format!("清单格式错误: {}", path.display()))?;
+        if manifest.version != MANIFEST_VERSION {
+            eprintln!(
+                "清单版本 {} 与当前版本 {MANIFEST_VERSION} 不符，按全量处理: {}",
+                manifest.version,
+                path.display()
+            );
+            return Ok(None);
+        }
+        Ok(Some(manifest))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = serde_json::to_string_pretty(self).context("无法序列化清单")?;
+        // 先写临时文件再改名，避免中途失败留下半个清单
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, text).with_context(|| format!("无法写入清单: {}", temp.display()))?;
+        fs::rename(&temp, path).with_context(|| format!("无法写入清单: {}", path.display()))
+    }
+
+    /// 由本次扫描结果生成清单
+    pub fn build(nodes: &[NodeRow], assets: &[AssetRow]) -> Self {
+        let nodes = nodes
+            .iter()
+            .map(|row| {
+                let entry = NodeEntry {
+                    raw_path: row.raw_path.clone(),
+                    row: hash_text(&row.csv_line()),
+                };
+                (row.path.clone(), entry)
+            })
+            .collect();
+        let asset_entries = assets
+            .iter()
+            .map(|row| {
+                let entry = AssetEntry {
+                    raw_path: row.raw_path.clone(),
+                    size: row.file_size,
+                    mtime_ns: row.modified_ns,
+                    hash: row.content_hash.clone(),
+                    row: hash_text(&row.csv_line()),
+                };
+                (row.file_path.clone(), entry)
+            })
+            .collect();
+
+        Self {
+            version: MANIFEST_VERSION,
+            nodes,
+            assets: asset_entries,
+        }
+    }
+
+    /// 清单内容的指纹，取决于各行（即写入数据库的全部列，含 `modified_time`）与文件内容的哈希；
+    /// 只用于跳过重复哈希的 `mtime_ns` 不写入数据库，不参与指纹。
+    ///
+    /// 导入时写入数据库的 `import_state` 表；下一次增量导入前与旧清单的指纹比较，
+    /// 不一致说明数据库已不是这份清单对应的状态（被其它方式改写或来自另一份清单），改为全量导入
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.version.to_le_bytes());
+        for (path, entry) in &self.nodes {
+            hasher.update(path.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(entry.row.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.update(b"\x1e");
+        for (path, entry) in &self.assets {
+            hasher.update(path.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(entry.hash.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(entry.row.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.finalize().to_hex().to_string()
+    }
+}
+
+fn hash_text(text: &str) -> String {
+    blake3::hash(text.as_bytes()).to_hex().to_string()
+}
+
+/// 清单文件不应放在资源根目录中（该目录由资源服务直接对外提供）
+pub fn ensure_outside_root(manifest: &Path, root: &Path) -> Result<()> {
+    let parent = manifest
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let parent = fs::canonicalize(parent)
+        .with_context(|| format!("清单所在目录不存在: {}", parent.display()))?;
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    if parent.starts_with(&root) {
+        bail!("清单文件不能位于资源根目录内: {}", manifest.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visibility::Visibility;
+
+    fn asset(content_hash: &str, modified_time: &str, modified_ns: u64) -> AssetRow {
+        AssetRow {
+            file_path: "a.visual_assets.cover_jpg".into(),
+            raw_path: "a/visual_assets/cover.jpg".into(),
+            raw_filename: "cover.jpg".into(),
+            content: String::new(),
+            sort_key: "1cover.jpg".into(),
+            visibility: Visibility::Public,
+            file_size: 5,
+            file_type: "image/jpeg".into(),
+            modified_time: modified_time.into(),
+            content_hash: content_hash.into(),
+            width: None,
+            height: None,
+            duration_ms: None,
+            kind: "image".into(),
+            modified_ns,
+        }
+    }
+
+    #[test]
+    fn fingerprint_follows_database_columns_not_manifest_mtime() {
+        const JAN: &str = "2024-01-01T00:00:00Z";
+        let base = Manifest::build(&[], &[asset("first", JAN, 1)]);
+        // mtime_ns 只存在于清单中
+        assert_eq!(
+            base.fingerprint(),
+            Manifest::build(&[], &[asset("first", JAN, 2)]).fingerprint()
+        );
+        // modified_time 会写入数据库，变化后数据库不再对应这份清单
+        assert_ne!(
+            base.fingerprint(),
+            Manifest::build(&[], &[asset("first", "2024-02-01T00:00:00Z", 1)]).fingerprint()
+        );
+        assert_ne!(
+            base.fingerprint(),
+            Manifest::build(&[], &[asset("second", JAN, 1)]).fingerprint()
+        );
+        assert_ne!(base.fingerprint(), Manifest::default().fingerprint());
+    }
+}
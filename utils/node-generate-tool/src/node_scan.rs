@@ -1,95 +1,216 @@
 use std::{
+    collections::HashMap,
     fs::{File, read_dir},
     io::{BufWriter, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
 use pathdiff::diff_paths;
+use serde::Serialize;
+
+use crate::conventions::Conventions;
+use crate::front_matter::FrontMatter;
+use crate::ltree_label::{LabelResolver, RenamedLabel, print_label_report};
+use crate::ordering::{OrderFile, sort_key};
+use crate::util::{build_walker, escape_csv_field, same_path};
+use crate::visibility::{Visibility, VisibilityResolver, write_nginx_deny_list};
+
+/// node.csv 的列，同时也是导入 `directory_nodes` 时的列顺序
+pub const NODE_CSV_COLUMNS: &str =
+    "path,has_subnodes,raw_path,raw_filename,has_layout,metadata,sort_key,visibility,layout_path";
+
+/// node.csv 的一行，即 `directory_nodes` 的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeRow {
+    pub path: String,
+    pub has_subnodes: bool,
+    pub raw_path: String,
+    pub raw_filename: String,
+    pub has_layout: bool,
+    /// front matter 的 JSON 文本
+    pub metadata: String,
+    pub sort_key: String,
+    pub visibility: Visibility,
+    /// 排版文件相对于根目录的路径，没有排版文件时为 `None`
+    pub layout_path: Option<String>,
+}
 
-use crate::util::{build_walker, cleanup_temp_ignore, escape_csv_field, path_to_ltree, same_path};
+impl NodeRow {
+    /// 按 [`NODE_CSV_COLUMNS`] 的顺序输出 CSV 行（不含换行）
+    pub fn csv_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.path,
+            self.has_subnodes,
+            escape_csv_field(&self.raw_path),
+            escape_csv_field(&self.raw_filename),
+            self.has_layout,
+            escape_csv_field(&self.metadata),
+            escape_csv_field(&self.sort_key),
+            self.visibility.as_str(),
+            escape_csv_field(self.layout_path.as_deref().unwrap_or(""))
+        )
+    }
+}
 
-pub fn run_node_scan(root: &Path, output_path: &Path, ignore_file: &str) -> Result<()> {
+/// 节点扫描结果
+#[derive(Debug, Default)]
+pub struct NodeScan {
+    pub rows: Vec<NodeRow>,
+    /// 最上层隐藏节点的原始路径（其后代由同一条前缀规则覆盖），用于生成屏蔽规则
+    pub hidden_roots: Vec<String>,
+    /// ltree 标签与旧规则不同的目录
+    pub renamed: Vec<RenamedLabel>,
+}
+
+pub fn run_node_scan(
+    root: &Path,
+    output_path: &Path,
+    ignore_file: &str,
+    deny_list: Option<&Path>,
+) -> Result<()> {
+    let scan = scan_nodes(root, ignore_file)?;
+    print_label_report(&scan.renamed);
     let mut writer = BufWriter::new(
         File::create(output_path)
             .with_context(|| format!("无法创建输出文件: {}", output_path.display()))?,
     );
-    writeln!(writer, "path,has_subnodes,raw_path,raw_filename")
-        .with_context(|| "写入 CSV 表头失败")?;
-
-    let (walker, temp_ignore_path, _) = build_walker(root, ignore_file, false)?;
-    let result: Result<()> = (|| {
-        for dent in walker {
-            let dent = match dent {
-                Ok(d) => d,
-                Err(err) => {
-                    eprintln!("跳过不可读条目: {err}");
-                    continue;
-                }
-            };
-
-            let Some(ft) = dent.file_type() else { continue };
-            if !ft.is_dir() {
-                continue;
-            }
+    write_node_csv(&scan.rows, &mut writer)?;
+    writer
+        .flush()
+        .with_context(|| format!("写入输出文件失败: {}", output_path.display()))?;
 
-            let dir_path = dent.path();
-            if same_path(dir_path, root) {
+    println!("已生成: {}", output_path.display());
+    if let Some(deny_list) = deny_list {
+        write_nginx_deny_list(deny_list, &scan.hidden_roots)?;
+        println!(
+            "已生成隐藏节点屏蔽规则（{} 条）: {}",
+            scan.hidden_roots.len(),
+            deny_list.display()
+        );
+    }
+    Ok(())
+}
+
+/// 把节点记录写成 node.csv（含表头）
+pub fn write_node_csv(rows: &[NodeRow], writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, "{NODE_CSV_COLUMNS}").with_context(|| "写入 CSV 表头失败")?;
+    for row in rows {
+        writeln!(writer, "{}", row.csv_line()).with_context(|| "写入 CSV 失败")?;
+    }
+    Ok(())
+}
+
+/// 扫描根目录下的所有节点目录
+pub fn scan_nodes(root: &Path, ignore_file: &str) -> Result<NodeScan> {
+    let conventions = Conventions::load(root)?;
+    let (walker, _) = build_walker(root, ignore_file, None)?;
+    // 每个父目录的 order 文件只读取一次
+    let mut order_files: HashMap<PathBuf, OrderFile> = HashMap::new();
+    let mut visibility = VisibilityResolver::new(root, &conventions);
+    let mut labels = LabelResolver::new();
+    let mut scan = NodeScan::default();
+    for dent in walker {
+        let dent = match dent {
+            Ok(d) => d,
+            Err(err) => {
+                eprintln!("跳过不可读条目: {err}");
                 continue;
             }
+        };
 
-            let rel = match diff_paths(dir_path, root) {
-                Some(r) => r,
-                None => continue,
-            };
-
-            let has_subnodes = check_has_subnodes(dir_path);
-
-            if let Some(path_str) = path_to_ltree(&rel) {
-                let raw_path = rel.to_string_lossy().replace('\\', "/");
-                let raw_filename = dir_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("")
-                    .to_string();
-
-                let raw_path_escaped = escape_csv_field(&raw_path);
-                let raw_filename_escaped = escape_csv_field(&raw_filename);
-
-                writeln!(
-                    writer,
-                    "{},{},{},{}",
-                    path_str, has_subnodes, raw_path_escaped, raw_filename_escaped
-                )
-                .with_context(|| "写入 CSV 失败")?;
-            }
+        let Some(ft) = dent.file_type() else { continue };
+        if !ft.is_dir() {
+            continue;
         }
-        Ok(())
-    })();
-    cleanup_temp_ignore(temp_ignore_path);
-    result?;
 
-    writer.flush().ok();
+        let dir_path = dent.path();
+        if same_path(dir_path, root) {
+            continue;
+        }
 
-    println!("已生成: {}", output_path.display());
-    Ok(())
+        let rel = match diff_paths(dir_path, root) {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let node_visibility = visibility.resolve(dir_path);
+        let has_subnodes = check_has_subnodes(dir_path, &conventions, &mut visibility);
+        let layout = conventions.layout_file(dir_path);
+        let metadata = layout
+            .as_deref()
+            .map(FrontMatter::from_layout)
+            .unwrap_or_default();
+
+        if let Some(path_str) = labels.ltree(&rel) {
+            let raw_path = rel.to_string_lossy().replace('\\', "/");
+            let raw_filename = dir_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+
+            if node_visibility == Visibility::Hidden
+                && dir_path
+                    .parent()
+                    .is_none_or(|parent| visibility.resolve(parent) != Visibility::Hidden)
+            {
+                scan.hidden_roots.push(raw_path.clone());
+            }
+
+            // front matter 的 order 优先于父目录 order 文件中的位置
+            let explicit_order = metadata.order.or_else(|| {
+                let parent = dir_path.parent()?;
+                order_files
+                    .entry(parent.to_path_buf())
+                    .or_insert_with(|| OrderFile::load(parent))
+                    .position(&raw_filename)
+            });
+            let sort_key = sort_key(explicit_order, &raw_filename);
+
+            scan.rows.push(NodeRow {
+                path: path_str,
+                has_subnodes,
+                raw_path,
+                raw_filename,
+                has_layout: layout.is_some(),
+                metadata: metadata.to_json(),
+                sort_key,
+                visibility: node_visibility,
+                layout_path: layout
+                    .as_deref()
+                    .and_then(|layout| diff_paths(layout, root))
+                    .map(|rel| rel.to_string_lossy().replace('\\', "/")),
+            });
+        }
+    }
+    labels.ensure_unique()?;
+    scan.renamed = labels.renamed();
+
+    Ok(scan)
 }
 
-fn check_has_subnodes(dir: &Path) -> bool {
+/// 是否有会在该节点下列出的子节点（资源目录与排除目录不算；
+/// 按可见性规则，隐藏或不公开的子节点也不计入）
+fn check_has_subnodes(
+    dir: &Path,
+    conventions: &Conventions,
+    visibility: &mut VisibilityResolver,
+) -> bool {
     let Ok(entries) = read_dir(dir) else {
         return false;
     };
-
-    let excluded_dirs: &[&str] = &["visual_assets", "project_archive"];
+    let context = visibility.resolve(dir);
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if path.is_dir() {
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if !excluded_dirs.contains(&name) {
-                    return true;
-                }
-            }
+        if path.is_dir()
+            && let Some(name) = path.file_name().and_then(|n| n.to_str())
+            && !conventions.is_structural_dir(name)
+            && visibility.resolve(&path).listed_in(context)
+        {
+            return true;
         }
     }
 
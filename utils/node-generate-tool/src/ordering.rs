@@ -0,0 +1,145 @@
+use std::{collections::HashMap, fs, path::Path};
+
+/// 目录内的显式排序文件：每行一个条目名（节点目录中写子目录名，
+/// `visual_assets` 中写文件名），空行和 `#` 开头的行会被忽略
+pub const ORDER_FILE: &str = "order";
+
+/// `order` 文件中各条目的位置（从 1 开始）
+#[derive(Debug, Default)]
+pub struct OrderFile {
+    positions: HashMap<String, i64>,
+}
+
+impl OrderFile {
+    /// 读取目录下的 `order` 文件；不存在或不可读时返回空表
+    pub fn load(dir: &Path) -> Self {
+        let Ok(text) = fs::read_to_string(dir.join(ORDER_FILE)) else {
+            return Self::default();
+        };
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let positions = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .zip(1..)
+            .map(|(name, position)| (name.to_string(), position))
+            .collect();
+        Self { positions }
+    }
+
+    pub fn position(&self, name: &str) -> Option<i64> {
+        self.positions.get(name).copied()
+    }
+}
+
+/// 生成写入 CSV 的排序键，按字节序（`COLLATE "C"`）比较即为展示顺序：
+/// 显式序号（front matter 的 `order` 或 `order` 文件中的位置）排在前面，
+/// 其余条目按原始名称自然排序（数字按数值比较、不区分大小写）。
+pub fn sort_key(explicit: Option<i64>, raw_filename: &str) -> String {
+    match explicit {
+        // 翻转符号位，使 i64 的大小关系与补零后的十进制字符串一致
+        Some(order) => format!("0{:020}", (order as u64) ^ (1 << 63)),
+        None => format!("1{}", natural_key(raw_filename)),
+    }
+}
+
+/// 自然排序键（backend 的 `?sort=name` 使用同一规则，测试向量见 `natural_key_cases.txt`）：
+/// 字母转小写，连续数字去掉前导零后以“位数(3 位) + 数字”表示
+pub fn natural_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len() + 8);
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut digits = String::from(c);
+            while let Some(&next) = chars.peek() {
+                if !next.is_ascii_digit() {
+                    break;
+                }
+                digits.push(next);
+                chars.next();
+            }
+            let trimmed = digits.trim_start_matches('0');
+            let trimmed = if trimmed.is_empty() { "0" } else { trimmed };
+            key.push_str(&format!("{:03}{}", trimmed.len(), trimmed));
+        } else {
+            key.extend(c.to_lowercase());
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        names.sort_by_key(|name| sort_key(None, name));
+        names
+    }
+
+    #[test]
+    fn natural_order_compares_numbers_by_value() {
+        assert_eq!(
+            sorted(&[
+                "10_Later",
+                "2_Writing",
+                "1_OnceAndOnceAgain",
+                "img10.jpg",
+                "IMG9.jpg"
+            ]),
+            [
+                "1_OnceAndOnceAgain",
+                "2_Writing",
+                "10_Later",
+                "IMG9.jpg",
+                "img10.jpg"
+            ]
+        );
+        assert_eq!(natural_key("a007"), natural_key("A7"));
+    }
+
+    #[test]
+    fn explicit_order_comes_first_and_handles_negatives() {
+        let mut keys = vec![
+            sort_key(None, "0_first_by_name"),
+            sort_key(Some(10), "x"),
+            sort_key(Some(-1), "y"),
+            sort_key(Some(2), "z"),
+        ];
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                sort_key(Some(-1), "y"),
+                sort_key(Some(2), "z"),
+                sort_key(Some(10), "x"),
+                sort_key(None, "0_first_by_name"),
+            ]
+        );
+    }
+
+    #[test]
+    fn order_file_skips_blank_and_comment_lines() {
+        let order = OrderFile::parse("# 展示顺序\npainting\n\n  handmadeBook  \n");
+        assert_eq!(order.position("painting"), Some(1));
+        assert_eq!(order.position("handmadeBook"), Some(2));
+        assert_eq!(order.position("missing"), None);
+    }
+
+    #[test]
+    fn natural_key_matches_shared_vectors() {
+        let cases = include_str!("natural_key_cases.txt");
+        for line in cases.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (input, expected) = line.split_once('\t').expect("测试向量应以制表符分隔");
+            assert_eq!(natural_key(input), expected, "输入: {input}");
+        }
+    }
+}
@@ -0,0 +1,76 @@
+-- directory_nodes / file_nodes / image_derivatives / import_state 表结构；可重复执行，旧库会补齐缺失的列。
+-- node-generate-tool 的 import 与 database/scripts/import_csv.sh 都执行本文件。
+CREATE EXTENSION IF NOT EXISTS ltree;
+
+CREATE TABLE IF NOT EXISTS directory_nodes (
+    path ltree PRIMARY KEY,
+    has_subnodes BOOLEAN NOT NULL,
+    raw_path TEXT NOT NULL,
+    raw_filename TEXT NOT NULL,
+    has_layout BOOLEAN NOT NULL DEFAULT false,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    sort_key TEXT NOT NULL DEFAULT '',
+    visibility TEXT NOT NULL DEFAULT 'public'
+        CHECK (visibility IN ('public', 'unlisted', 'hidden')),
+    layout_path TEXT
+);
+ALTER TABLE directory_nodes ADD COLUMN IF NOT EXISTS has_layout BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE directory_nodes ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE directory_nodes ADD COLUMN IF NOT EXISTS sort_key TEXT NOT NULL DEFAULT '';
+ALTER TABLE directory_nodes ADD COLUMN IF NOT EXISTS visibility TEXT NOT NULL DEFAULT 'public'
+    CHECK (visibility IN ('public', 'unlisted', 'hidden'));
+ALTER TABLE directory_nodes ADD COLUMN IF NOT EXISTS layout_path TEXT;
+
+CREATE TABLE IF NOT EXISTS file_nodes (
+    file_path ltree PRIMARY KEY,
+    raw_path TEXT NOT NULL,
+    raw_filename TEXT NOT NULL,
+    content TEXT NOT NULL DEFAULT '',
+    sort_key TEXT NOT NULL DEFAULT '',
+    visibility TEXT NOT NULL DEFAULT 'public'
+        CHECK (visibility IN ('public', 'unlisted', 'hidden')),
+    file_size BIGINT NOT NULL DEFAULT 0,
+    file_type TEXT NOT NULL DEFAULT '',
+    modified_time TIMESTAMPTZ,
+    content_hash TEXT NOT NULL DEFAULT '',
+    width INTEGER,
+    height INTEGER,
+    duration_ms BIGINT,
+    kind TEXT NOT NULL DEFAULT ''
+);
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS content TEXT NOT NULL DEFAULT '';
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS sort_key TEXT NOT NULL DEFAULT '';
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS visibility TEXT NOT NULL DEFAULT 'public'
+    CHECK (visibility IN ('public', 'unlisted', 'hidden'));
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS file_size BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS file_type TEXT NOT NULL DEFAULT '';
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS modified_time TIMESTAMPTZ;
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS content_hash TEXT NOT NULL DEFAULT '';
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS width INTEGER;
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS height INTEGER;
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS duration_ms BIGINT;
+ALTER TABLE file_nodes ADD COLUMN IF NOT EXISTS kind TEXT NOT NULL DEFAULT '';
+
+-- 图片衍生图（thumbnails 子命令生成）；按 content_hash 与 file_nodes 关联，
+-- 原图替换后旧记录自然失效，重新导入 file_nodes 也不会丢失衍生图
+CREATE TABLE IF NOT EXISTS image_derivatives (
+    file_path ltree NOT NULL,
+    content_hash TEXT NOT NULL,
+    format TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (file_path, format, width)
+);
+
+CREATE INDEX IF NOT EXISTS idx_directory_nodes_search ON directory_nodes
+    USING GIN (to_tsvector('simple', raw_filename || ' ' || raw_path));
+CREATE INDEX IF NOT EXISTS idx_file_nodes_search ON file_nodes
+    USING GIN (to_tsvector('simple', raw_filename || ' ' || raw_path || ' ' || content));
+
+-- 最近一次由 node-generate-tool 写入后，数据库内容对应的清单指纹（单行）；
+-- 增量导入前与本地旧清单的指纹比较，不一致时改为全量导入。import_csv.sh 导入时清空
+CREATE TABLE IF NOT EXISTS import_state (
+    id BOOLEAN PRIMARY KEY DEFAULT true CHECK (id),
+    manifest_fingerprint TEXT NOT NULL
+);
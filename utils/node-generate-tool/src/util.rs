@@ -4,7 +4,12 @@ use std::{
 };
 
 use anyhow::{Context, Result};
-use ignore::{Walk, WalkBuilder};
+use ignore::{
+    Walk, WalkBuilder,
+    gitignore::{Gitignore, GitignoreBuilder},
+};
+
+use crate::conventions::Conventions;
 
 pub fn canonicalize_path(p: &Path) -> Result<PathBuf> {
     let abs = if p.is_absolute() {
@@ -15,19 +20,40 @@ pub fn canonicalize_path(p: &Path) -> Result<PathBuf> {
     Ok(abs)
 }
 
+/// `include_assets` 为资源相关模式使用的约定，其资源目录会被强制纳入（覆盖 ignore 规则）。
+///
+/// 合并后的规则只在内存中生效，不会在根目录写入任何文件；同时返回不含强制纳入条目的合并规则。
 pub fn build_walker(
     root: &Path,
     ignore_file: &str,
-    include_visual: bool,
-) -> Result<(Walk, Option<PathBuf>, Option<String>)> {
+    include_assets: Option<&Conventions>,
+) -> Result<(Walk, Option<String>)> {
+    let merged_rules = merged_ignore_rules(root, ignore_file);
+    let matcher = compile_rules(root, &merged_rules, include_assets)?;
+
     let mut builder = WalkBuilder::new(root);
     builder
         .hidden(false)
         .git_ignore(false)
         .git_global(false)
         .git_exclude(false)
-        .follow_links(false);
+        .follow_links(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.depth() == 0 || !matcher.matched(entry.path(), is_dir).is_ignore()
+        });
 
+    let base_rules = if merged_rules.trim().is_empty() {
+        None
+    } else {
+        Some(merged_rules)
+    };
+
+    Ok((builder.build(), base_rules))
+}
+
+/// 依次读取程序目录、可执行文件目录与根目录下的 ignore 文件并合并
+fn merged_ignore_rules(root: &Path, ignore_file: &str) -> String {
     let mut ignore_files: Vec<PathBuf> = Vec::new();
 
     let program_dir: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -44,17 +70,17 @@ pub fn build_walker(
     let mut merged_rules = String::new();
     let mut loaded_count = 0;
     for path in &ignore_files {
-        if path.exists() {
-            if let Ok(content) = fs::read_to_string(path) {
-                eprintln!("已加载 ignore 文件: {}", path.display());
-                if !merged_rules.is_empty() {
-                    merged_rules.push('\n');
-                }
-                merged_rules.push_str(&format!("# From: {}\n", path.display()));
-                merged_rules.push_str(&content);
+        if path.exists()
+            && let Ok(content) = fs::read_to_string(path)
+        {
+            eprintln!("已加载 ignore 文件: {}", path.display());
+            if !merged_rules.is_empty() {
                 merged_rules.push('\n');
-                loaded_count += 1;
             }
+            merged_rules.push_str(&format!("# From: {}\n", path.display()));
+            merged_rules.push_str(&content);
+            merged_rules.push('\n');
+            loaded_count += 1;
         }
     }
 
@@ -63,40 +89,39 @@ pub fn build_walker(
     } else {
         eprintln!("共加载 {} 个 ignore 文件", loaded_count);
     }
+    merged_rules
+}
 
-    let base_rules = if merged_rules.trim().is_empty() {
-        None
-    } else {
-        Some(merged_rules.clone())
-    };
-
-    if include_visual {
-        merged_rules.push_str("\n# visual modes: force include visual_assets\n");
-        merged_rules.push_str("!visual_assets/\n");
-        merged_rules.push_str("!**/visual_assets/\n");
-        merged_rules.push_str("!**/visual_assets/**\n");
-    }
-
-    let temp_ignore_path = if !merged_rules.is_empty() {
-        let temp_path = root.join(format!(".{}_merged", ignore_file));
-        fs::write(&temp_path, merged_rules)
-            .with_context(|| format!("无法创建临时 ignore 文件: {}", temp_path.display()))?;
-
-        builder.add_custom_ignore_filename(&format!(".{}_merged", ignore_file));
-        Some(temp_path)
-    } else {
-        None
-    };
-
-    Ok((builder.build(), temp_ignore_path, base_rules))
+/// 与 visual 模式的 [`build_walker`] 相同的过滤规则，用于判断单个路径是否被忽略
+pub fn build_ignore_matcher(
+    root: &Path,
+    ignore_file: &str,
+    conventions: &Conventions,
+) -> Result<Gitignore> {
+    compile_rules(
+        root,
+        &merged_ignore_rules(root, ignore_file),
+        Some(conventions),
+    )
 }
 
-pub fn cleanup_temp_ignore(temp_path: Option<PathBuf>) {
-    if let Some(path) = temp_path {
-        if let Err(err) = fs::remove_file(&path) {
-            eprintln!("清理临时 ignore 文件失败: {} ({err})", path.display());
-        }
+/// 把合并后的规则（及可选的资源目录强制纳入规则）编译为相对于根目录的匹配器
+fn compile_rules(
+    root: &Path,
+    rules: &str,
+    include_assets: Option<&Conventions>,
+) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    let include_rules = include_assets.map(Conventions::include_rules);
+    for line in rules
+        .lines()
+        .chain(include_rules.iter().flat_map(|r| r.lines()))
+    {
+        builder
+            .add_line(None, line)
+            .with_context(|| format!("ignore 规则无效: {line}"))?;
     }
+    builder.build().context("无法构建 ignore 规则")
 }
 
 pub fn resolve_copy_destination(path: &Path) -> Result<PathBuf> {
@@ -163,25 +188,6 @@ fn normalize_components(p: &Path) -> Vec<String> {
         .collect()
 }
 
-pub fn path_to_ltree(rel: &Path) -> Option<String> {
-    let mut parts = Vec::new();
-    for comp in rel.components() {
-        if let Component::Normal(os) = comp {
-            let s = os.to_str()?;
-            if !s.is_empty() {
-                let cleaned = sanitize_ltree_label(s);
-                if !cleaned.is_empty() {
-                    parts.push(cleaned);
-                }
-            }
-        }
-    }
-    if parts.is_empty() {
-        return None;
-    }
-    Some(parts.join("."))
-}
-
 pub fn sanitize_ltree_label(label: &str) -> String {
     label
         .chars()
@@ -202,3 +208,80 @@ pub fn escape_csv_field(field: &str) -> String {
         field.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 根目录下所有条目（相对路径），用于确认遍历没有写入任何文件
+    fn snapshot(root: &Path) -> Vec<PathBuf> {
+        fn list(dir: &Path, out: &mut Vec<PathBuf>) {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    list(&path, out);
+                }
+                out.push(path);
+            }
+        }
+        let mut entries = Vec::new();
+        list(root, &mut entries);
+        entries.sort();
+        entries
+    }
+
+    fn walk(root: &Path, conventions: Option<&Conventions>) -> Vec<PathBuf> {
+        let (walker, _) = build_walker(root, "fileignore", conventions).unwrap();
+        let mut paths: Vec<PathBuf> = walker
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_path_buf()
+            })
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn walker_applies_rules_in_memory_without_touching_root() {
+        let root = std::env::temp_dir().join(format!("ngt-util-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("drafts")).unwrap();
+        fs::create_dir_all(root.join("series/visual_assets")).unwrap();
+        fs::write(root.join("drafts/notes.md"), "").unwrap();
+        fs::write(root.join("series/visual_assets/cover.jpg"), "").unwrap();
+        fs::write(root.join("series/upload.tmp"), "").unwrap();
+        fs::write(root.join("fileignore"), "drafts/\nvisual_assets/\n*.tmp\n").unwrap();
+        let before = snapshot(&root);
+
+        let conventions = Conventions::default();
+        let (_, base_rules) = build_walker(&root, "fileignore", Some(&conventions)).unwrap();
+        let base_rules = base_rules.unwrap();
+        assert!(base_rules.contains("drafts/\n"));
+        assert!(!base_rules.contains("!**/visual_assets/"));
+
+        // visual 模式强制纳入 visual_assets，其余规则照常生效
+        assert_eq!(
+            walk(&root, Some(&conventions)),
+            [
+                "fileignore",
+                "series",
+                "series/visual_assets",
+                "series/visual_assets/cover.jpg"
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(
+            walk(&root, None),
+            ["fileignore", "series"].map(PathBuf::from)
+        );
+        assert_eq!(snapshot(&root), before);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
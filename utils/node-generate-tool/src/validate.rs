@@ -0,0 +1,590 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, read_dir},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use pathdiff::diff_paths;
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Serialize;
+
+use crate::conventions::Conventions;
+use crate::front_matter::FrontMatter;
+use crate::ltree_label::LabelResolver;
+use crate::ordering::ORDER_FILE;
+use crate::util::{build_walker, same_path};
+
+/// 矢量图没有固定的像素尺寸，不检查大小与尺寸
+const VECTOR_EXTENSIONS: [&str; 1] = ["svg"];
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+/// 问题级别：错误会使命令以非零状态退出，警告只提示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 一条校验问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    /// 稳定的问题代码，如 `broken_link`，便于脚本过滤
+    pub code: &'static str,
+    /// 相对于根目录的路径（根目录本身为 `.`）
+    pub path: String,
+    pub message: String,
+}
+
+/// 校验阈值
+#[derive(Debug, Clone)]
+pub struct ValidateOptions {
+    /// 图片文件大小上限（字节）
+    pub max_image_bytes: u64,
+    /// 图片宽或高的上限（像素）
+    pub max_image_dimension: u32,
+}
+
+/// 校验结果，问题按路径与代码排序
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    fn new(mut issues: Vec<Issue>) -> Self {
+        issues.sort_by(|a, b| {
+            (&a.path, a.severity, a.code, &a.message)
+                .cmp(&(&b.path, b.severity, b.code, &b.message))
+        });
+        let errors = issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .count();
+        Self {
+            errors,
+            warnings: issues.len() - errors,
+            issues,
+        }
+    }
+}
+
+/// 人类可读的报告：每个问题一行，最后一行为计数
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            let level = match issue.severity {
+                Severity::Error => "错误",
+                Severity::Warning => "警告",
+            };
+            writeln!(
+                f,
+                "{level} [{}] {}: {}",
+                issue.code, issue.path, issue.message
+            )?;
+        }
+        writeln!(
+            f,
+            "校验完成：{} 个错误，{} 个警告",
+            self.errors, self.warnings
+        )
+    }
+}
+
+/// 校验资源目录并输出报告；有错误时返回 `Err`，使进程以非零状态退出
+pub fn run_validate(
+    root: &Path,
+    ignore_file: &str,
+    options: &ValidateOptions,
+    format: ReportFormat,
+) -> Result<()> {
+    let report = validate(root, ignore_file, options)?;
+    match format {
+        ReportFormat::Text => print!("{report}"),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    if report.errors > 0 {
+        bail!("资源目录校验未通过：{} 个错误", report.errors);
+    }
+    Ok(())
+}
+
+/// 按与扫描相同的 ignore 规则遍历根目录，收集所有问题
+pub fn validate(root: &Path, ignore_file: &str, options: &ValidateOptions) -> Result<Report> {
+    let conventions = Conventions::load(root)?;
+    let (walker, _) = build_walker(root, ignore_file, Some(&conventions))?;
+    let mut checker = Checker {
+        root,
+        options,
+        conventions: &conventions,
+        labels: LabelResolver::new(),
+        owners: HashMap::new(),
+        issues: Vec::new(),
+    };
+    for dent in walker {
+        let dent = match dent {
+            Ok(d) => d,
+            Err(err) => {
+                eprintln!("跳过不可读条目: {err}");
+                continue;
+            }
+        };
+        if dent.file_type().is_some_and(|ft| ft.is_dir()) && !same_path(dent.path(), root) {
+            checker.check_dir(dent.path());
+        }
+    }
+
+    Ok(Report::new(checker.issues))
+}
+
+struct Checker<'a> {
+    root: &'a Path,
+    options: &'a ValidateOptions,
+    conventions: &'a Conventions,
+    labels: LabelResolver,
+    /// 已分配的 ltree 路径及其原始路径，用于发现重复
+    owners: HashMap<String, String>,
+    issues: Vec<Issue>,
+}
+
+impl Checker<'_> {
+    fn push(&mut self, severity: Severity, code: &'static str, path: &Path, message: String) {
+        let path = diff_paths(path, self.root)
+            .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+            .filter(|rel| !rel.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        self.issues.push(Issue {
+            severity,
+            code,
+            path,
+            message,
+        });
+    }
+
+    fn check_dir(&mut self, dir: &Path) {
+        let Some(rel) = diff_paths(dir, self.root) else {
+            return;
+        };
+        let Some(name) = dir.file_name().and_then(|name| name.to_str()) else {
+            self.push(
+                Severity::Warning,
+                "invalid_name",
+                dir,
+                "目录名不是有效的 UTF-8，扫描时会被跳过".into(),
+            );
+            return;
+        };
+        self.check_placement(dir, &rel, name);
+        self.claim_label(dir, &rel);
+
+        if self.conventions.is_asset_dir(name) {
+            self.check_visual_assets(dir);
+        } else if !self.inside_structural_dir(&rel)
+            && let Some(layout) = self.conventions.layout_file(dir)
+        {
+            // 节点目录：检查排版文件
+            if let Ok(text) = fs::read_to_string(&layout) {
+                if let Err(err) = FrontMatter::parse(&text) {
+                    self.push(
+                        Severity::Error,
+                        "invalid_front_matter",
+                        &layout,
+                        format!("front matter 无法解析: {err:#}"),
+                    );
+                }
+                self.check_links(&layout, &text);
+            }
+        }
+    }
+
+    /// 路径中是否有资源目录或排除目录
+    fn inside_structural_dir(&self, rel: &Path) -> bool {
+        rel.components().any(|comp| {
+            matches!(comp, Component::Normal(name)
+                if name.to_str().is_some_and(|name| self.conventions.is_structural_dir(name)))
+        })
+    }
+
+    /// 资源目录与排除目录（如 visual_assets、project_archive）都必须是某个节点的直接子目录，
+    /// 且不能互相嵌套
+    fn check_placement(&mut self, dir: &Path, rel: &Path, name: &str) {
+        if !self.conventions.is_structural_dir(name) {
+            return;
+        }
+        let parent_rel = rel.parent().unwrap_or(Path::new(""));
+        if parent_rel.as_os_str().is_empty() {
+            self.push(
+                Severity::Error,
+                "misplaced_folder",
+                dir,
+                format!("{name} 位于根目录，不属于任何节点"),
+            );
+        } else if self.inside_structural_dir(parent_rel) {
+            self.push(
+                Severity::Error,
+                "misplaced_folder",
+                dir,
+                format!("{name} 位于资源目录或排除目录之内"),
+            );
+        }
+    }
+
+    /// 记录条目的 ltree 路径；与已有条目重复时报错（导入时主键冲突）
+    fn claim_label(&mut self, path: &Path, rel: &Path) {
+        let Some(ltree) = self.labels.ltree(rel) else {
+            return;
+        };
+        let raw_path = rel.to_string_lossy().replace('\\', "/");
+        if let Some(other) = self.owners.insert(ltree.clone(), raw_path) {
+            self.push(
+                Severity::Error,
+                "duplicate_path",
+                path,
+                format!("与 {other} 的 ltree 路径同为 {ltree}"),
+            );
+        }
+    }
+
+    fn check_visual_assets(&mut self, dir: &Path) {
+        let Ok(entries) = read_dir(dir) else {
+            self.push(Severity::Error, "unreadable", dir, "无法读取目录".into());
+            return;
+        };
+        let mut files = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.push(
+                    Severity::Warning,
+                    "nested_directory",
+                    &path,
+                    "资源目录只扫描直接文件，子目录中的内容不会出现在网站上".into(),
+                );
+                continue;
+            }
+            if entry.file_name() == ORDER_FILE {
+                continue;
+            }
+            files += 1;
+            if let Some(rel) = diff_paths(&path, self.root) {
+                self.claim_label(&path, &rel);
+            }
+            self.check_asset(&path);
+        }
+        if files == 0 {
+            self.push(
+                Severity::Warning,
+                "empty_visual_assets",
+                dir,
+                "资源目录中没有文件".into(),
+            );
+        }
+    }
+
+    fn check_asset(&mut self, path: &Path) {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let kind = self.conventions.kind_of(path);
+        if kind.is_none() {
+            let shown = if extension.is_empty() {
+                "无扩展名".to_string()
+            } else {
+                format!(".{extension}")
+            };
+            self.push(
+                Severity::Warning,
+                "unsupported_extension",
+                path,
+                format!("{shown} 文件无法在网站上预览"),
+            );
+        }
+        if kind == Some("markdown")
+            && let Ok(text) = fs::read_to_string(path)
+        {
+            self.check_links(path, &text);
+        }
+        if kind == Some("image") && !VECTOR_EXTENSIONS.contains(&extension.as_str()) {
+            self.check_image(path);
+        }
+    }
+
+    fn check_image(&mut self, path: &Path) {
+        let size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        if size > self.options.max_image_bytes {
+            self.push(
+                Severity::Warning,
+                "oversized_image",
+                path,
+                format!(
+                    "文件大小 {:.1} MB 超过上限 {:.1} MB",
+                    size as f64 / 1_048_576.0,
+                    self.options.max_image_bytes as f64 / 1_048_576.0
+                ),
+            );
+        }
+        match imagesize::size(path) {
+            Ok(dimensions) => {
+                let limit = self.options.max_image_dimension as usize;
+                if dimensions.width > limit || dimensions.height > limit {
+                    self.push(
+                        Severity::Warning,
+                        "oversized_image",
+                        path,
+                        format!(
+                            "尺寸 {}×{} 超过上限 {limit} 像素",
+                            dimensions.width, dimensions.height
+                        ),
+                    );
+                }
+            }
+            Err(_) => self.push(
+                Severity::Warning,
+                "unreadable_image",
+                path,
+                "无法识别图片格式，文件可能已损坏或扩展名有误".into(),
+            ),
+        }
+    }
+
+    /// 检查 markdown 中指向本地文件的相对链接与图片
+    fn check_links(&mut self, file: &Path, text: &str) {
+        let base = file.parent().unwrap_or(self.root);
+        // 与前端一致：只写文件名的链接找不到时，再到所属节点的资源目录中按文件名匹配
+        let node_dir = match base.file_name().and_then(|name| name.to_str()) {
+            Some(name) if self.conventions.is_asset_dir(name) => base.parent().unwrap_or(base),
+            _ => base,
+        };
+        for target in local_targets(text) {
+            let Some(resolved) = resolve_relative(base, &target) else {
+                continue;
+            };
+            let in_asset_dir = || {
+                !target.contains('/')
+                    && self
+                        .conventions
+                        .asset_dirs
+                        .iter()
+                        .any(|dir| node_dir.join(dir).join(&target).is_file())
+            };
+            if !resolved.starts_with(self.root) {
+                self.push(
+                    Severity::Error,
+                    "link_outside_root",
+                    file,
+                    format!("链接指向根目录之外: {target}"),
+                );
+            } else if !resolved.exists() && !in_asset_dir() {
+                self.push(
+                    Severity::Error,
+                    "broken_link",
+                    file,
+                    format!("链接目标不存在: {target}"),
+                );
+            }
+        }
+    }
+}
+
+/// markdown 中链接与图片的本地目标（已去掉锚点与查询参数并解码 `%XX`）；
+/// 外部 URL、站内绝对路径与纯锚点不在其列
+fn local_targets(text: &str) -> Vec<String> {
+    Parser::new(text)
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                Some(dest_url.into_string())
+            }
+            _ => None,
+        })
+        .filter(|dest| !dest.starts_with('/') && !has_scheme(dest))
+        .filter_map(|dest| {
+            let path = dest.split(['#', '?']).next().unwrap_or_default();
+            (!path.is_empty()).then(|| percent_decode(path))
+        })
+        .collect()
+}
+
+/// 是否以 URL scheme 开头（如 `https:`、`mailto:`）
+fn has_scheme(dest: &str) -> bool {
+    dest.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 按字面拼接并规整 `.` / `..`，不访问文件系统；越过 `base` 的最顶层时返回 `None`
+fn resolve_relative(base: &Path, target: &str) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for comp in base.join(target).components() {
+        match comp {
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    Some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_local_link_targets_only() {
+        let text = "![photo](my%20photo.jpg) [next](../b/visual_assets/b.md#top) \
+                    [site](https://example.com) [mail](mailto:a@b.c) [abs](/resource/x.png) \
+                    [anchor](#intro) ![q](img.png?v=1)";
+        assert_eq!(
+            local_targets(text),
+            ["my photo.jpg", "../b/visual_assets/b.md", "img.png"]
+        );
+        assert_eq!(
+            resolve_relative(Path::new("/r/a"), "../b/./c.md"),
+            Some(PathBuf::from("/r/b/c.md"))
+        );
+    }
+
+    #[test]
+    fn reports_problems_with_paths_and_severity() {
+        let root = std::env::temp_dir().join(format!("ngt-validate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in [
+            "a/visual_assets/sub",
+            "a/project_archive",
+            "b/visual_assets",
+            "visual_assets",
+            "c/visual_assets/project_archive",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(
+            root.join("a/visual_assets/intro.md"),
+            "![ok](cover.png) ![missing](gone.jpg) [out](../../../etc/passwd)",
+        )
+        .unwrap();
+        fs::write(root.join("a/visual_assets/cover.png"), b"not a png").unwrap();
+        fs::write(root.join("a/visual_assets/data.xyz"), b"?").unwrap();
+        fs::write(root.join("a/layout.md"), "---\nyear: [\n---\n").unwrap();
+        fs::write(root.join("c/visual_assets/note.md"), "[b](../../b/)").unwrap();
+
+        let options = ValidateOptions {
+            max_image_bytes: 1,
+            max_image_dimension: 8000,
+        };
+        let report = validate(&root, "fileignore", &options).unwrap();
+        let found: Vec<(Severity, &str, &str)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.code, issue.path.as_str()))
+            .collect();
+        use Severity::{Error, Warning};
+        assert_eq!(
+            found,
+            [
+                (Error, "invalid_front_matter", "a/layout.md"),
+                (Warning, "oversized_image", "a/visual_assets/cover.png"),
+                (Warning, "unreadable_image", "a/visual_assets/cover.png"),
+                (Warning, "unsupported_extension", "a/visual_assets/data.xyz"),
+                (Error, "broken_link", "a/visual_assets/intro.md"),
+                (Error, "link_outside_root", "a/visual_assets/intro.md"),
+                (Warning, "nested_directory", "a/visual_assets/sub"),
+                (Warning, "empty_visual_assets", "b/visual_assets"),
+                (Error, "misplaced_folder", "c/visual_assets/project_archive"),
+                (
+                    Warning,
+                    "nested_directory",
+                    "c/visual_assets/project_archive"
+                ),
+                (Error, "misplaced_folder", "visual_assets"),
+                (Warning, "empty_visual_assets", "visual_assets"),
+            ]
+        );
+        assert_eq!((report.errors, report.warnings), (5, 7));
+        assert!(
+            report
+                .to_string()
+                .ends_with("校验完成：5 个错误，7 个警告\n")
+        );
+        assert!(run_validate(&root, "fileignore", &options, ReportFormat::Json).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn bare_file_names_in_layout_resolve_to_visual_assets() {
+        let root = std::env::temp_dir().join(format!("ngt-validate-layout-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/visual_assets")).unwrap();
+        fs::write(root.join("a/visual_assets/cover.jpg"), b"").unwrap();
+        fs::write(root.join("a/visual_assets/notes.md"), "![](cover.jpg)").unwrap();
+        fs::write(
+            root.join("a/layout.md"),
+            "![](cover.jpg) ![](visual_assets/cover.jpg) ![](gone.jpg) ![](sub/cover.jpg)",
+        )
+        .unwrap();
+
+        let options = ValidateOptions {
+            max_image_bytes: u64::MAX,
+            max_image_dimension: u32::MAX,
+        };
+        let report = validate(&root, "fileignore", &options).unwrap();
+        let links: Vec<(&str, &str)> = report
+            .issues
+            .iter()
+            .filter(|issue| issue.code == "broken_link")
+            .map(|issue| (issue.path.as_str(), issue.message.as_str()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("a/layout.md", "链接目标不存在: gone.jpg"),
+                ("a/layout.md", "链接目标不存在: sub/cover.jpg"),
+            ]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,443 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// 视频的时长与画面尺寸，只解析容器头部，不解码画面
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VideoMeta {
+    pub duration_ms: Option<u64>,
+    /// 显示尺寸（宽, 高），已按旋转矩阵交换宽高
+    pub dimensions: Option<(u32, u32)>,
+}
+
+/// moov / Info 等头部元素的大小上限，超过时视为损坏
+const MAX_HEADER_LEN: u64 = 64 * 1024 * 1024;
+
+/// 按 MIME 类型解析 MP4 / MOV（ISO BMFF）或 WebM / MKV（EBML）的头部；
+/// 其它格式或解析失败时返回 `None`
+pub fn probe(path: &Path, mime: &str) -> Option<VideoMeta> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let meta = match mime {
+        "video/mp4" | "video/quicktime" | "video/x-m4v" | "video/3gpp" => probe_mp4(&mut reader),
+        "video/webm" | "video/x-matroska" => probe_ebml(&mut reader),
+        _ => None,
+    }?;
+    (meta != VideoMeta::default()).then_some(meta)
+}
+
+// ---------------- ISO BMFF ----------------
+
+/// 依次读取顶层 box，找到 moov 后解析其中的 mvhd 与视频轨道的 tkhd
+fn probe_mp4<R: Read + Seek>(reader: &mut R) -> Option<VideoMeta> {
+    let end = reader.seek(SeekFrom::End(0)).ok()?;
+    reader.seek(SeekFrom::Start(0)).ok()?;
+    let mut offset = 0;
+    while offset < end {
+        let (kind, header_len, size) = read_box_header(reader, end - offset)?;
+        if &kind == b"moov" {
+            let len = size - header_len;
+            if len > MAX_HEADER_LEN {
+                return None;
+            }
+            let mut moov = vec![0; len as usize];
+            reader.read_exact(&mut moov).ok()?;
+            return Some(parse_moov(&moov));
+        }
+        offset += size;
+        reader.seek(SeekFrom::Start(offset)).ok()?;
+    }
+    None
+}
+
+/// 读取 box 头部，返回（类型, 头部长度, box 总长度）；`remaining` 为到文件末尾的长度
+fn read_box_header<R: Read>(reader: &mut R, remaining: u64) -> Option<([u8; 4], u64, u64)> {
+    let mut header = [0; 8];
+    reader.read_exact(&mut header).ok()?;
+    let kind = [header[4], header[5], header[6], header[7]];
+    let (header_len, size) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]])
+    {
+        0 => (8, remaining),
+        1 => {
+            let mut large = [0; 8];
+            reader.read_exact(&mut large).ok()?;
+            (16, u64::from_be_bytes(large))
+        }
+        size => (8, u64::from(size)),
+    };
+    (size >= header_len && size <= remaining).then_some((kind, header_len, size))
+}
+
+/// 内存中 box 序列的迭代器，产出（类型, 内容）
+fn boxes(mut data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let mut cursor = data;
+        let (kind, header_len, size) = read_box_header(&mut cursor, data.len() as u64)?;
+        let body = &data[header_len as usize..size as usize];
+        data = &data[size as usize..];
+        Some((kind, body))
+    })
+}
+
+fn parse_moov(moov: &[u8]) -> VideoMeta {
+    let mut meta = VideoMeta::default();
+    for (kind, body) in boxes(moov) {
+        match &kind {
+            b"mvhd" => meta.duration_ms = parse_mvhd(body),
+            b"trak" if meta.dimensions.is_none() && is_video_track(body) => {
+                meta.dimensions = boxes(body)
+                    .find(|(kind, _)| kind == b"tkhd")
+                    .and_then(|(_, tkhd)| parse_tkhd(tkhd));
+            }
+            _ => {}
+        }
+    }
+    meta
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// mvhd：version 0 为 32 位时间字段，version 1 为 64 位
+fn parse_mvhd(body: &[u8]) -> Option<u64> {
+    let (timescale, duration) = match body.first()? {
+        0 => (be_u32(body, 12)?, u64::from(be_u32(body, 16)?)),
+        1 => (be_u32(body, 20)?, be_u64(body, 24)?),
+        _ => return None,
+    };
+    // 全 1 表示时长未知
+    if timescale == 0 || duration == u64::from(u32::MAX) || duration == u64::MAX {
+        return None;
+    }
+    Some(duration.saturating_mul(1000) / u64::from(timescale))
+}
+
+/// trak/mdia/hdlr 的 handler_type 为 `vide` 时是视频轨道
+fn is_video_track(trak: &[u8]) -> bool {
+    boxes(trak)
+        .filter(|(kind, _)| kind == b"mdia")
+        .flat_map(|(_, mdia)| boxes(mdia))
+        .any(|(kind, hdlr)| &kind == b"hdlr" && hdlr.get(8..12) == Some(b"vide"))
+}
+
+/// tkhd：宽高为 16.16 定点数，位于变换矩阵之后；矩阵表示旋转 90° / 270° 时交换宽高
+fn parse_tkhd(body: &[u8]) -> Option<(u32, u32)> {
+    let matrix_at = match body.first()? {
+        0 => 40,
+        1 => 52,
+        _ => return None,
+    };
+    let width = be_u32(body, matrix_at + 36)? >> 16;
+    let height = be_u32(body, matrix_at + 40)? >> 16;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let (a, b) = (be_u32(body, matrix_at)?, be_u32(body, matrix_at + 4)?);
+    if a == 0 && b != 0 {
+        Some((height, width))
+    } else {
+        Some((width, height))
+    }
+}
+
+// ---------------- EBML (WebM / Matroska) ----------------
+
+const EBML_SEGMENT: u32 = 0x1853_8067;
+const EBML_INFO: u32 = 0x1549_A966;
+const EBML_TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const EBML_DURATION: u32 = 0x4489;
+const EBML_TRACKS: u32 = 0x1654_AE6B;
+const EBML_TRACK_ENTRY: u32 = 0xAE;
+const EBML_TRACK_TYPE: u32 = 0x83;
+const EBML_VIDEO: u32 = 0xE0;
+const EBML_PIXEL_WIDTH: u32 = 0xB0;
+const EBML_PIXEL_HEIGHT: u32 = 0xBA;
+const EBML_CLUSTER: u32 = 0x1F43_B675;
+
+/// 读取 EBML 变长整数；`keep_marker` 为真时保留长度标记位（元素 ID 的写法）。
+/// 返回（值, 字节数, 是否为“未知大小”）
+fn read_vint<R: Read>(reader: &mut R, keep_marker: bool) -> Option<(u64, u8, bool)> {
+    let mut first = [0; 1];
+    reader.read_exact(&mut first).ok()?;
+    let len = first[0].leading_zeros() as u8 + 1;
+    if len > 8 {
+        return None;
+    }
+    let marker = 0x80u8 >> (len - 1);
+    let mut value = u64::from(if keep_marker {
+        first[0]
+    } else {
+        first[0] & !marker
+    });
+    let mut all_ones = first[0] | !(marker | (marker - 1)) == 0xFF;
+    for _ in 1..len {
+        let mut byte = [0; 1];
+        reader.read_exact(&mut byte).ok()?;
+        all_ones &= byte[0] == 0xFF;
+        value = (value << 8) | u64::from(byte[0]);
+    }
+    Some((value, len, all_ones && !keep_marker))
+}
+
+/// 读取元素头部，返回（ID, 头部长度, 内容长度；未知大小时为 `None`）
+fn read_element_header<R: Read>(reader: &mut R) -> Option<(u32, u64, Option<u64>)> {
+    let (id, id_len, _) = read_vint(reader, true)?;
+    let (size, size_len, unknown) = read_vint(reader, false)?;
+    Some((
+        u32::try_from(id).ok()?,
+        u64::from(id_len + size_len),
+        (!unknown).then_some(size),
+    ))
+}
+
+fn probe_ebml<R: Read + Seek>(reader: &mut R) -> Option<VideoMeta> {
+    let end = reader.seek(SeekFrom::End(0)).ok()?;
+    reader.seek(SeekFrom::Start(0)).ok()?;
+    let mut offset = 0;
+    // 跳过 EBML 头，进入 Segment
+    while offset < end {
+        let (id, header_len, size) = read_element_header(reader)?;
+        offset += header_len;
+        if id == EBML_SEGMENT {
+            let segment_end = size.map_or(end, |size| (offset + size).min(end));
+            return Some(parse_segment(reader, offset, segment_end));
+        }
+        offset += size?;
+        reader.seek(SeekFrom::Start(offset)).ok()?;
+    }
+    None
+}
+
+/// 在 Segment 中读取 Info 与 Tracks；遇到第一个 Cluster（画面数据）即停止
+fn parse_segment<R: Read + Seek>(reader: &mut R, mut offset: u64, end: u64) -> VideoMeta {
+    let mut meta = VideoMeta::default();
+    while offset < end {
+        let Some((id, header_len, Some(size))) = read_element_header(reader) else {
+            break;
+        };
+        offset += header_len;
+        // 元素头部越过 Segment 末尾或内容超出剩余长度时视为损坏
+        let Some(remaining) = end.checked_sub(offset) else {
+            break;
+        };
+        if id == EBML_CLUSTER || size > remaining {
+            break;
+        }
+        if matches!(id, EBML_INFO | EBML_TRACKS) && size <= MAX_HEADER_LEN {
+            let mut body = vec![0; size as usize];
+            if reader.read_exact(&mut body).is_err() {
+                break;
+            }
+            if id == EBML_INFO {
+                meta.duration_ms = parse_info(&body);
+            } else {
+                meta.dimensions = parse_tracks(&body);
+            }
+        }
+        offset += size;
+        if reader.seek(SeekFrom::Start(offset)).is_err() {
+            break;
+        }
+    }
+    meta
+}
+
+/// 内存中 EBML 元素序列的迭代器，产出（ID, 内容）；未知大小的元素终止迭代
+fn elements(mut data: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    std::iter::from_fn(move || {
+        let mut cursor = data;
+        let (id, header_len, size) = read_element_header(&mut cursor)?;
+        let start = header_len as usize;
+        let body = data.get(start..start.checked_add(usize::try_from(size?).ok()?)?)?;
+        data = &data[start + body.len()..];
+        Some((id, body))
+    })
+}
+
+fn ebml_uint(body: &[u8]) -> Option<u64> {
+    (body.len() <= 8).then(|| {
+        body.iter()
+            .fold(0, |value, &byte| (value << 8) | u64::from(byte))
+    })
+}
+
+fn ebml_float(body: &[u8]) -> Option<f64> {
+    match body.len() {
+        4 => Some(f64::from(f32::from_be_bytes(body.try_into().ok()?))),
+        8 => Some(f64::from_be_bytes(body.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Info：Duration 以 TimestampScale（纳秒，默认 1 毫秒）为单位
+fn parse_info(info: &[u8]) -> Option<u64> {
+    let mut scale = 1_000_000;
+    let mut duration = None;
+    for (id, body) in elements(info) {
+        match id {
+            EBML_TIMESTAMP_SCALE => scale = ebml_uint(body)?,
+            EBML_DURATION => duration = ebml_float(body),
+            _ => {}
+        }
+    }
+    let ms = duration? * scale as f64 / 1_000_000.0;
+    (ms.is_finite() && ms >= 0.0).then_some(ms.round() as u64)
+}
+
+/// Tracks：第一个 TrackType 为 1（视频）的轨道的 PixelWidth / PixelHeight
+fn parse_tracks(tracks: &[u8]) -> Option<(u32, u32)> {
+    elements(tracks)
+        .filter(|(id, _)| *id == EBML_TRACK_ENTRY)
+        .find_map(|(_, entry)| {
+            let is_video = elements(entry)
+                .any(|(id, body)| id == EBML_TRACK_TYPE && ebml_uint(body) == Some(1));
+            if !is_video {
+                return None;
+            }
+            let (_, video) = elements(entry).find(|(id, _)| *id == EBML_VIDEO)?;
+            let mut size = (0, 0);
+            for (id, body) in elements(video) {
+                match id {
+                    EBML_PIXEL_WIDTH => size.0 = u32::try_from(ebml_uint(body)?).ok()?,
+                    EBML_PIXEL_HEIGHT => size.1 = u32::try_from(ebml_uint(body)?).ok()?,
+                    _ => {}
+                }
+            }
+            (size.0 > 0 && size.1 > 0).then_some(size)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn tkhd(width: u32, height: u32, rotated: bool) -> Vec<u8> {
+        let mut body = vec![0; 84];
+        let (a, b) = if rotated {
+            (0u32, 0x0001_0000u32)
+        } else {
+            (0x0001_0000, 0)
+        };
+        body[40..44].copy_from_slice(&a.to_be_bytes());
+        body[44..48].copy_from_slice(&b.to_be_bytes());
+        body[76..80].copy_from_slice(&(width << 16).to_be_bytes());
+        body[80..84].copy_from_slice(&(height << 16).to_be_bytes());
+        mp4_box(b"tkhd", &body)
+    }
+
+    fn trak(handler: &[u8; 4], tkhd: Vec<u8>) -> Vec<u8> {
+        let mut hdlr = vec![0; 8];
+        hdlr.extend_from_slice(handler);
+        hdlr.extend_from_slice(&[0; 12]);
+        let mdia = mp4_box(b"mdia", &mp4_box(b"hdlr", &hdlr));
+        mp4_box(b"trak", &[tkhd, mdia].concat())
+    }
+
+    #[test]
+    fn reads_mp4_duration_and_video_track_size() {
+        let mut mvhd = vec![0; 100];
+        mvhd[12..16].copy_from_slice(&600u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&(600u32 * 83 + 300).to_be_bytes());
+        let moov = mp4_box(
+            b"moov",
+            &[
+                mp4_box(b"mvhd", &mvhd),
+                // 音频轨道在前，不应被当作画面尺寸
+                trak(b"soun", tkhd(0, 0, false)),
+                trak(b"vide", tkhd(1920, 1080, true)),
+            ]
+            .concat(),
+        );
+        let moov_len = moov.len();
+        // moov 位于 mdat 之后（未做 faststart 的文件）
+        let file = [
+            mp4_box(b"ftyp", b"isom\0\0\0\0"),
+            mp4_box(b"mdat", &[0; 64]),
+            moov,
+        ]
+        .concat();
+
+        let meta = probe_mp4(&mut Cursor::new(file.clone())).unwrap();
+        assert_eq!(meta.duration_ms, Some(83_500));
+        assert_eq!(meta.dimensions, Some((1080, 1920)));
+        assert_eq!(probe_mp4(&mut Cursor::new(b"not a video".to_vec())), None);
+
+        // 截断的文件：moov 声明的长度超出文件末尾
+        let truncated = &file[..file.len() - 40];
+        assert_eq!(probe_mp4(&mut Cursor::new(truncated.to_vec())), None);
+        // 截断在 box 头部中间
+        let truncated = &file[..file.len() - moov_len + 4];
+        assert_eq!(probe_mp4(&mut Cursor::new(truncated.to_vec())), None);
+    }
+
+    fn element(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.push(0x80 | body.len() as u8);
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn reads_webm_duration_and_video_track_size() {
+        let info = element(
+            &[0x15, 0x49, 0xA9, 0x66],
+            &[
+                element(&[0x2A, 0xD7, 0xB1], &[0x0F, 0x42, 0x40]),
+                element(&[0x44, 0x89], &12_345.6f64.to_be_bytes()),
+            ]
+            .concat(),
+        );
+        let video = element(
+            &[0xE0],
+            &[
+                element(&[0xB0], &[0x02, 0x80]),
+                element(&[0xBA], &[0x01, 0xE0]),
+            ]
+            .concat(),
+        );
+        let tracks = element(
+            &[0x16, 0x54, 0xAE, 0x6B],
+            &[
+                element(&[0xAE], &element(&[0x83], &[2])),
+                element(&[0xAE], &[element(&[0x83], &[1]), video].concat()),
+            ]
+            .concat(),
+        );
+        // 未知大小的 Segment（流式写出的文件）
+        let mut file = element(&[0x1A, 0x45, 0xDF, 0xA3], &[]);
+        file.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0xFF]);
+        file.extend(info);
+        file.extend(tracks);
+        file.extend_from_slice(&[0x1F, 0x43, 0xB6, 0x75, 0xFF, 0, 0, 0]);
+
+        let meta = probe_ebml(&mut Cursor::new(file)).unwrap();
+        assert_eq!(meta.duration_ms, Some(12_346));
+        assert_eq!(meta.dimensions, Some((640, 480)));
+    }
+
+    #[test]
+    fn stops_at_element_header_past_segment_end() {
+        // Segment 只声明 2 字节，其后 Info 的头部越过 Segment 末尾
+        let mut file = element(&[0x1A, 0x45, 0xDF, 0xA3], &[]);
+        file.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x82]);
+        file.extend(element(&[0x15, 0x49, 0xA9, 0x66], &[0; 4]));
+
+        let meta = probe_ebml(&mut Cursor::new(file)).unwrap();
+        assert_eq!(meta, VideoMeta::default());
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::conventions::Conventions;
+use crate::front_matter::FrontMatter;
+use crate::util::same_path;
+
+/// 节点目录下的标记文件：存在即把该节点（及其全部后代）设为隐藏 / 仅凭链接访问
+pub const HIDDEN_MARKER: &str = ".hidden";
+pub const UNLISTED_MARKER: &str = ".unlisted";
+
+/// 节点可见性，顺序即严格程度，写入 CSV 的 `visibility` 列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// 正常出现在列表、子树与搜索中
+    #[default]
+    Public,
+    /// 不出现在列表与搜索中，知道链接即可访问
+    Unlisted,
+    /// 不对外提供：后端不返回，`copy visual` 也不复制其资源
+    Hidden,
+}
+
+impl Visibility {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Hidden => "hidden",
+        }
+    }
+
+    /// 在可见性为 `context` 的节点下是否会被列出，规则与后端一致
+    pub fn listed_in(self, context: Visibility) -> bool {
+        self == Visibility::Public || (self == context && context != Visibility::Hidden)
+    }
+
+    /// 节点自身声明的可见性：取标记文件与排版文件 front matter 中较严格的一项
+    fn declared(dir: &Path, layout: Option<PathBuf>) -> Self {
+        let marker = if dir.join(HIDDEN_MARKER).is_file() {
+            Visibility::Hidden
+        } else if dir.join(UNLISTED_MARKER).is_file() {
+            Visibility::Unlisted
+        } else {
+            Visibility::Public
+        };
+        // 排版文件读不出或 front matter 格式错误时无法确认作者的意图，按隐藏处理；
+        // 错误提示由节点扫描负责输出
+        let front_matter = layout.map_or(Visibility::Public, |layout| {
+            fs::read_to_string(layout)
+                .ok()
+                .and_then(|text| FrontMatter::parse(&text).ok())
+                .map_or(Visibility::Hidden, |front_matter| front_matter.visibility())
+        });
+        marker.max(front_matter)
+    }
+}
+
+/// 计算节点的实际可见性：自身声明与所有祖先中最严格的一项，结果按目录缓存
+#[derive(Debug)]
+pub struct VisibilityResolver {
+    root: PathBuf,
+    conventions: Conventions,
+    resolved: HashMap<PathBuf, Visibility>,
+}
+
+impl VisibilityResolver {
+    pub fn new(root: &Path, conventions: &Conventions) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            conventions: conventions.clone(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// `dir` 为节点目录；根目录及根目录之外的路径视为公开
+    pub fn resolve(&mut self, dir: &Path) -> Visibility {
+        if same_path(dir, &self.root) || !dir.starts_with(&self.root) {
+            return Visibility::Public;
+        }
+        if let Some(visibility) = self.resolved.get(dir) {
+            return *visibility;
+        }
+
+        let inherited = dir
+            .parent()
+            .map(|parent| self.resolve(parent))
+            .unwrap_or_default();
+        let layout = self.conventions.layout_file(dir);
+        let visibility = inherited.max(Visibility::declared(dir, layout));
+        self.resolved.insert(dir.to_path_buf(), visibility);
+        visibility
+    }
+}
+
+/// 生成资源服务（nginx）的屏蔽规则：隐藏节点目录下的所有文件返回 404。
+/// `raw_paths` 为相对于资源根目录的节点路径，只需列出最上层的隐藏节点。
+pub fn nginx_deny_rules(raw_paths: &[String]) -> String {
+    let mut rules =
+        String::from("# 由 node-generate-tool scan node --deny-list 生成，请勿手动修改\n");
+    for raw_path in raw_paths {
+        let location = format!("/resource/{}/", raw_path.trim_matches('/'))
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        rules.push_str(&format!("location ^~ \"{location}\" {{ return 404; }}\n"));
+    }
+    rules
+}
+
+/// 写出 [`nginx_deny_rules`]
+pub fn write_nginx_deny_list(path: &Path, raw_paths: &[String]) -> Result<()> {
+    fs::write(path, nginx_deny_rules(raw_paths))
+        .with_context(|| format!("无法写入屏蔽规则: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::front_matter::LAYOUT_FILE;
+
+    #[test]
+    fn visibility_propagates_to_descendants() {
+        let root = std::env::temp_dir().join(format!("ngt-visibility-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let series = root.join("series");
+        let draft = series.join("draft");
+        let preview = series.join("preview");
+        fs::create_dir_all(draft.join("sketch")).unwrap();
+        fs::create_dir_all(preview.join("study")).unwrap();
+        fs::write(draft.join(HIDDEN_MARKER), "").unwrap();
+        fs::write(
+            preview.join(LAYOUT_FILE),
+            "---\nvisibility: unlisted\n---\n",
+        )
+        .unwrap();
+        // 子节点声明为公开也不能放宽祖先的限制
+        fs::write(
+            preview.join("study").join(LAYOUT_FILE),
+            "---\nvisibility: public\n---\n",
+        )
+        .unwrap();
+
+        let mut resolver = VisibilityResolver::new(&root, &Conventions::default());
+        assert_eq!(resolver.resolve(&series), Visibility::Public);
+        assert_eq!(resolver.resolve(&draft.join("sketch")), Visibility::Hidden);
+        assert_eq!(resolver.resolve(&preview), Visibility::Unlisted);
+        assert_eq!(
+            resolver.resolve(&preview.join("study")),
+            Visibility::Unlisted
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn malformed_front_matter_hides_node() {
+        let root = std::env::temp_dir().join(format!("ngt-visibility-bad-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let book = root.join("book");
+        fs::create_dir_all(&book).unwrap();
+        fs::write(
+            book.join(LAYOUT_FILE),
+            "---\nhidden: true\nyear: 不是数字\n---\n",
+        )
+        .unwrap();
+
+        let mut resolver = VisibilityResolver::new(&root, &Conventions::default());
+        assert_eq!(resolver.resolve(&book), Visibility::Hidden);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn deny_rules_quote_raw_paths() {
+        let rules = nginx_deny_rules(&["a/my draft".into(), "b/\"q\"".into()]);
+        assert!(rules.contains("location ^~ \"/resource/a/my draft/\" { return 404; }\n"));
+        assert!(rules.contains("location ^~ \"/resource/b/\\\"q\\\"/\" { return 404; }\n"));
+        assert_eq!(nginx_deny_rules(&[]).lines().count(), 1);
+    }
+
+    #[test]
+    fn listing_rules_match_backend() {
+        use Visibility::*;
+        assert!(Public.listed_in(Public));
+        assert!(!Unlisted.listed_in(Public));
+        assert!(Unlisted.listed_in(Unlisted));
+        assert!(!Hidden.listed_in(Unlisted));
+    }
+}
@@ -3,7 +3,9 @@ use std::{fs, path::Path};
 use anyhow::{Context, Result};
 use pathdiff::diff_paths;
 
-use crate::util::{build_walker, cleanup_temp_ignore, copy_dir};
+use crate::conventions::Conventions;
+use crate::util::{build_walker, copy_dir};
+use crate::visibility::{Visibility, VisibilityResolver};
 
 pub fn run_copy_visual_assets(root: &Path, destination: &Path, ignore_file: &str) -> Result<()> {
     if !destination.exists() {
@@ -15,9 +17,11 @@ pub fn run_copy_visual_assets(root: &Path, destination: &Path, ignore_file: &str
     println!("目标目录: {}", destination.display());
     println!("开始查找 visual_assets 目录...\n");
 
-    let (walker, temp_ignore_path, consolidated_ignore_rules) =
-        build_walker(root, ignore_file, true)?;
+    let conventions = Conventions::load(root)?;
+    let (walker, consolidated_ignore_rules) = build_walker(root, ignore_file, Some(&conventions))?;
+    let mut visibility = VisibilityResolver::new(root, &conventions);
     let mut copied_count = 0usize;
+    let mut hidden_count = 0usize;
     let result: Result<()> = (|| {
         for dent in walker {
             let dent = match dent {
@@ -34,10 +38,11 @@ pub fn run_copy_visual_assets(root: &Path, destination: &Path, ignore_file: &str
             }
 
             let dir_path = dent.path();
-            if !matches!(
-                dir_path.file_name().and_then(|n| n.to_str()),
-                Some("visual_assets")
-            ) {
+            if !dir_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| conventions.is_asset_dir(name))
+            {
                 continue;
             }
 
@@ -46,6 +51,21 @@ pub fn run_copy_visual_assets(root: &Path, destination: &Path, ignore_file: &str
             };
             let target_path = destination.join(&rel);
 
+            // 隐藏节点的资源不复制，/resource 上也就无法访问
+            if dir_path
+                .parent()
+                .is_some_and(|node| visibility.resolve(node) == Visibility::Hidden)
+            {
+                println!("跳过隐藏节点: {}", rel.display());
+                if target_path.exists() {
+                    fs::remove_dir_all(&target_path).with_context(|| {
+                        format!("无法删除已复制的隐藏资源: {}", target_path.display())
+                    })?;
+                }
+                hidden_count += 1;
+                continue;
+            }
+
             println!("复制: {} -> {}", rel.display(), target_path.display());
             copy_dir(dir_path, &target_path).with_context(|| {
                 format!(
@@ -59,7 +79,6 @@ pub fn run_copy_visual_assets(root: &Path, destination: &Path, ignore_file: &str
         }
         Ok(())
     })();
-    cleanup_temp_ignore(temp_ignore_path);
     result?;
 
     if let Some(rules) = consolidated_ignore_rules {
@@ -78,6 +97,10 @@ pub fn run_copy_visual_assets(root: &Path, destination: &Path, ignore_file: &str
         println!("未发现可合并的 ignore 规则，跳过导出整理文件");
     }
 
+    if hidden_count > 0 {
+        println!("已跳过 {hidden_count} 个隐藏节点的 visual_assets 目录");
+    }
+
     if copied_count == 0 {
         println!("未找到任何 visual_assets 目录（或全部被 ignore 规则过滤）");
     } else {
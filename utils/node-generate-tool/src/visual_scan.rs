@@ -1,84 +1,262 @@
 use std::{
-    fs::{File, read_dir},
+    fs::{self, File, read_dir},
     io::{BufWriter, Write},
     path::Path,
 };
 
 use anyhow::{Context, Result};
 use pathdiff::diff_paths;
+use serde::Serialize;
 
-use crate::util::{build_walker, cleanup_temp_ignore, escape_csv_field, path_to_ltree};
+use crate::conventions::Conventions;
+use crate::file_meta::FileMeta;
+use crate::front_matter::FrontMatter;
+use crate::ltree_label::LabelResolver;
+use crate::manifest::Manifest;
+use crate::ordering::{ORDER_FILE, OrderFile, sort_key};
+use crate::util::{build_walker, escape_csv_field};
+use crate::visibility::{Visibility, VisibilityResolver};
+
+/// visual_assets.csv 的列，同时也是导入 `file_nodes` 时的列顺序
+pub const VISUAL_CSV_COLUMNS: &str = "file_path,raw_path,raw_filename,content,sort_key,visibility,\
+file_size,file_type,modified_time,content_hash,width,height,duration_ms,kind";
+
+/// visual_assets.csv 的一行，即 `file_nodes` 的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetRow {
+    pub file_path: String,
+    pub raw_path: String,
+    pub raw_filename: String,
+    pub content: String,
+    pub sort_key: String,
+    pub visibility: Visibility,
+    /// 文件大小（字节）
+    pub file_size: u64,
+    /// MIME 类型
+    pub file_type: String,
+    /// 修改时间（RFC 3339，UTC）
+    pub modified_time: String,
+    /// 文件内容的 BLAKE3 哈希
+    pub content_hash: String,
+    /// 图片或视频的像素尺寸，无法识别时为空
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// 视频时长（毫秒），非视频时为空
+    pub duration_ms: Option<u64>,
+    /// 按 [`Conventions::kinds`] 判断的资源类型，未列出的扩展名为 `other`
+    pub kind: String,
+    /// 修改时间的纳秒数（不写入 CSV），用于清单比较
+    #[serde(skip)]
+    pub modified_ns: u64,
+}
+
+impl AssetRow {
+    /// 按 [`VISUAL_CSV_COLUMNS`] 的顺序输出 CSV 行（不含换行）
+    pub fn csv_line(&self) -> String {
+        let optional = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.file_path,
+            escape_csv_field(&self.raw_path),
+            escape_csv_field(&self.raw_filename),
+            escape_csv_field(&self.content),
+            escape_csv_field(&self.sort_key),
+            self.visibility.as_str(),
+            self.file_size,
+            escape_csv_field(&self.file_type),
+            self.modified_time,
+            self.content_hash,
+            optional(self.width),
+            optional(self.height),
+            self.duration_ms.map(|v| v.to_string()).unwrap_or_default(),
+            escape_csv_field(&self.kind)
+        )
+    }
+}
 
 pub fn run_visual_scan(root: &Path, output_path: &Path, ignore_file: &str) -> Result<()> {
+    let rows = scan_assets(root, ignore_file, None)?;
     let mut writer = BufWriter::new(
         File::create(output_path)
             .with_context(|| format!("无法创建输出文件: {}", output_path.display()))?,
     );
-    writeln!(writer, "file_path,raw_path,raw_filename").with_context(|| "写入 CSV 表头失败")?;
-
-    let (walker, temp_ignore_path, _) = build_walker(root, ignore_file, true)?;
-    let result: Result<()> = (|| {
-        for dent in walker {
-            let dent = match dent {
-                Ok(d) => d,
-                Err(err) => {
-                    eprintln!("跳过不可读条目: {err}");
-                    continue;
-                }
-            };
+    write_visual_csv(&rows, &mut writer)?;
+    writer
+        .flush()
+        .with_context(|| format!("写入输出文件失败: {}", output_path.display()))?;
+
+    println!("已生成: {}", output_path.display());
+    Ok(())
+}
+
+/// 把资源记录写成 visual_assets.csv（含表头）
+pub fn write_visual_csv(rows: &[AssetRow], writer: &mut impl Write) -> Result<()> {
+    writeln!(writer, "{VISUAL_CSV_COLUMNS}").with_context(|| "写入 CSV 表头失败")?;
+    for row in rows {
+        writeln!(writer, "{}", row.csv_line()).with_context(|| "写入 CSV 失败")?;
+    }
+    Ok(())
+}
 
-            let Some(ft) = dent.file_type() else { continue };
-            if !ft.is_dir() {
+/// 扫描各节点资源目录下的直接文件；`previous` 为上一次的清单，
+/// 用于跳过未变化文件的哈希计算
+pub fn scan_assets(
+    root: &Path,
+    ignore_file: &str,
+    previous: Option<&Manifest>,
+) -> Result<Vec<AssetRow>> {
+    let conventions = Conventions::load(root)?;
+    let (walker, _) = build_walker(root, ignore_file, Some(&conventions))?;
+    let mut visibility = VisibilityResolver::new(root, &conventions);
+    let mut labels = LabelResolver::new();
+    let mut rows = Vec::new();
+    for dent in walker {
+        let dent = match dent {
+            Ok(d) => d,
+            Err(err) => {
+                eprintln!("跳过不可读条目: {err}");
                 continue;
             }
+        };
+
+        let Some(ft) = dent.file_type() else { continue };
+        if !ft.is_dir() {
+            continue;
+        }
+
+        let dir_path = dent.path();
+        if !dir_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| conventions.is_asset_dir(name))
+        {
+            continue;
+        }
 
-            let dir_path = dent.path();
-            if !matches!(
-                dir_path.file_name().and_then(|n| n.to_str()),
-                Some("visual_assets")
-            ) {
+        // 资源沿用所属节点的可见性
+        let node_visibility = dir_path
+            .parent()
+            .map(|node| visibility.resolve(node))
+            .unwrap_or(Visibility::Public);
+        let order_file = OrderFile::load(dir_path);
+        let Ok(entries) = read_dir(dir_path) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_path = entry.path();
+            if !file_path.is_file() || entry.file_name() == ORDER_FILE {
                 continue;
             }
 
-            if let Ok(entries) = read_dir(dir_path) {
-                for entry in entries.flatten() {
-                    let file_path = entry.path();
-                    if !file_path.is_file() {
-                        continue;
-                    }
-
-                    let Some(rel_file) = diff_paths(&file_path, root) else {
-                        continue;
-                    };
-
-                    if let Some(file_ltree) = path_to_ltree(&rel_file) {
-                        let raw_path = rel_file.to_string_lossy().replace('\\', "/");
-                        let raw_filename = file_path
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        let raw_path_escaped = escape_csv_field(&raw_path);
-                        let raw_filename_escaped = escape_csv_field(&raw_filename);
-
-                        writeln!(
-                            writer,
-                            "{},{},{}",
-                            file_ltree, raw_path_escaped, raw_filename_escaped
-                        )
-                        .with_context(|| "写入 CSV 失败")?;
-                    }
-                }
+            let Some(rel_file) = diff_paths(&file_path, root) else {
+                continue;
+            };
+
+            if let Some(file_ltree) = labels.ltree(&rel_file) {
+                let raw_path = rel_file.to_string_lossy().replace('\\', "/");
+                let raw_filename = file_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("")
+                    .to_string();
+
+                let kind = conventions.kind_of(&file_path).unwrap_or("other");
+                let content = read_text_content(&file_path, kind);
+                // markdown 可在 front matter 中写 order，优先于 order 文件
+                let explicit_order = FrontMatter::parse(&content)
+                    .ok()
+                    .and_then(|front_matter| front_matter.order)
+                    .or_else(|| order_file.position(&raw_filename));
+                let sort_key = sort_key(explicit_order, &raw_filename);
+                let meta = FileMeta::read(
+                    &file_path,
+                    previous.and_then(|manifest| manifest.assets.get(&file_ltree)),
+                );
+
+                rows.push(AssetRow {
+                    file_path: file_ltree,
+                    raw_path,
+                    raw_filename,
+                    content,
+                    sort_key,
+                    visibility: node_visibility,
+                    file_size: meta.size,
+                    file_type: meta.mime.clone(),
+                    modified_time: meta.modified_rfc3339(),
+                    content_hash: meta.hash,
+                    width: meta.dimensions.map(|(width, _)| width),
+                    height: meta.dimensions.map(|(_, height)| height),
+                    duration_ms: meta.duration_ms,
+                    kind: kind.to_string(),
+                    modified_ns: meta.modified_ns,
+                });
             }
         }
-        Ok(())
-    })();
-    cleanup_temp_ignore(temp_ignore_path);
-    result?;
+    }
+    labels.ensure_unique()?;
 
-    writer.flush().ok();
+    Ok(rows)
+}
 
-    println!("已生成: {}", output_path.display());
-    Ok(())
+/// 读取 markdown 文件（按配置的扩展名判断）的正文，写入 content 列供数据库全文检索；其它文件留空
+fn read_text_content(file_path: &Path, kind: &str) -> String {
+    if kind != "markdown" {
+        return String::new();
+    }
+
+    match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("无法读取 markdown 内容: {} ({err})", file_path.display());
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conventions::CONFIG_FILE;
+
+    #[test]
+    fn scans_configured_asset_dirs_and_kinds() {
+        let root = std::env::temp_dir().join(format!("ngt-visual-scan-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let media = root.join("work").join("media");
+        fs::create_dir_all(&media).unwrap();
+        fs::create_dir_all(root.join("work").join("visual_assets")).unwrap();
+        fs::write(
+            root.join(CONFIG_FILE),
+            "asset_dirs = [\"media\"]\n[kinds]\nmarkdown = [\"txt\"]\nimage = [\"png\"]\n",
+        )
+        .unwrap();
+        fs::write(media.join("notes.txt"), "正文").unwrap();
+        fs::write(media.join("intro.md"), "# 不按 markdown 读取").unwrap();
+        fs::write(media.join("cover.png"), "").unwrap();
+        fs::write(root.join("work").join("visual_assets").join("old.png"), "").unwrap();
+
+        let mut rows = scan_assets(&root, "fileignore", None).unwrap();
+        rows.sort_by(|a, b| a.raw_filename.cmp(&b.raw_filename));
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.raw_filename.as_str(),
+                    row.kind.as_str(),
+                    row.content.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("cover.png", "image", ""),
+                ("intro.md", "other", ""),
+                ("notes.txt", "markdown", "正文"),
+            ]
+        );
+        assert!(rows[0].csv_line().ends_with(",image"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,274 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::Gitignore;
+use notify_debouncer_full::{
+    DebounceEventResult, DebouncedEvent, new_debouncer,
+    notify::{EventKind, RecursiveMode},
+};
+use postgres::Client;
+
+use crate::change_set::ChangeSet;
+use crate::conventions::{CONFIG_FILE, Conventions};
+use crate::db_import::{ImportOutcome, connect, import_scan};
+use crate::manifest::Manifest;
+use crate::node_scan::{scan_nodes, write_node_csv};
+use crate::util::{build_ignore_matcher, same_path};
+use crate::visibility::write_nginx_deny_list;
+use crate::visual_scan::{scan_assets, write_visual_csv};
+
+const NODE_CSV: &str = "node.csv";
+const VISUAL_CSV: &str = "visual_assets.csv";
+
+/// 监听模式的写入目标
+#[derive(Debug, Clone)]
+pub enum WatchTarget {
+    /// 增量写入数据库（连接串）
+    Database(String),
+    /// 重写该目录下的 node.csv 与 visual_assets.csv
+    Csv(PathBuf),
+}
+
+/// 监听根目录，变化平息 `debounce` 之后重新扫描并把变化写入目标。
+///
+/// 启动时先同步一次（有清单时增量，否则全量）；之后每次同步都与上一次的结果比较，
+/// 逐条打印写入的变化。启动后的同步失败只打印错误，下次变化时重试。
+pub fn run_watch(
+    root: &Path,
+    ignore_file: &str,
+    target: WatchTarget,
+    deny_list: Option<&Path>,
+    manifest: Option<&Path>,
+    debounce: Duration,
+) -> Result<()> {
+    let mut outputs: Vec<PathBuf> = deny_list.into_iter().map(Path::to_path_buf).collect();
+    outputs.extend(manifest.map(Path::to_path_buf));
+    if let WatchTarget::Csv(dir) = &target {
+        outputs.push(dir.join(NODE_CSV));
+        outputs.push(dir.join(VISUAL_CSV));
+    }
+
+    let mut syncer = Syncer {
+        root,
+        ignore_file,
+        target,
+        deny_list,
+        manifest,
+        client: None,
+        previous: match manifest {
+            Some(path) => Manifest::load(path)?,
+            None => None,
+        },
+    };
+    println!("初始同步: {}", root.display());
+    syncer.run()?;
+
+    let mut filter = EventFilter::new(root, ignore_file, outputs)?;
+    let (sender, receiver) = mpsc::channel::<DebounceEventResult>();
+    let mut debouncer = new_debouncer(debounce, None, sender).context("无法创建文件监听")?;
+    debouncer
+        .watch(root, RecursiveMode::Recursive)
+        .with_context(|| format!("无法监听目录: {}", root.display()))?;
+    println!("开始监听: {}（Ctrl+C 退出）", root.display());
+
+    for result in receiver {
+        let events = match result {
+            Ok(events) => events,
+            Err(errors) => {
+                for err in errors {
+                    eprintln!("文件监听出错: {err}");
+                }
+                continue;
+            }
+        };
+
+        // ignore 文件或目录约定变化时先更新过滤规则，再决定是否需要同步
+        if changed_paths(&events).any(|path| {
+            path.file_name()
+                .is_some_and(|name| name == ignore_file || name == CONFIG_FILE)
+        }) {
+            filter = EventFilter::new(root, ignore_file, filter.outputs)?;
+        }
+        let changed = changed_paths(&events)
+            .filter(|path| filter.is_relevant(path))
+            .count();
+        if changed == 0 {
+            continue;
+        }
+
+        println!("检测到 {changed} 处文件变化，重新扫描...");
+        if let Err(err) = syncer.run() {
+            eprintln!("同步失败，将在下次变化时重试: {err:#}");
+        }
+    }
+    Ok(())
+}
+
+/// 事件涉及的路径；只读访问（扫描本身也会打开文件）不算变化
+fn changed_paths(events: &[DebouncedEvent]) -> impl Iterator<Item = &PathBuf> {
+    events
+        .iter()
+        .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+        .flat_map(|event| &event.paths)
+}
+
+/// 判断文件事件是否需要触发重新扫描
+struct EventFilter {
+    root: PathBuf,
+    /// 本工具自己写出的文件（CSV、清单、屏蔽规则）
+    outputs: Vec<PathBuf>,
+    matcher: Gitignore,
+}
+
+impl EventFilter {
+    fn new(root: &Path, ignore_file: &str, outputs: Vec<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            outputs,
+            matcher: build_ignore_matcher(root, ignore_file, &Conventions::load(root)?)?,
+        })
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        if !path.starts_with(&self.root)
+            || self.outputs.iter().any(|output| path.starts_with(output))
+        {
+            return false;
+        }
+        same_path(path, &self.root)
+            || !self
+                .matcher
+                .matched_path_or_any_parents(path, path.is_dir())
+                .is_ignore()
+    }
+}
+
+/// 同步状态：上一次写入的清单与复用的数据库连接
+struct Syncer<'a> {
+    root: &'a Path,
+    ignore_file: &'a str,
+    target: WatchTarget,
+    deny_list: Option<&'a Path>,
+    manifest: Option<&'a Path>,
+    client: Option<Client>,
+    previous: Option<Manifest>,
+}
+
+impl Syncer<'_> {
+    fn run(&mut self) -> Result<()> {
+        let scan = scan_nodes(self.root, self.ignore_file)?;
+        let assets = scan_assets(self.root, self.ignore_file, self.previous.as_ref())?;
+        let current = Manifest::build(&scan.rows, &assets);
+        let changes = self.previous.as_ref().map(|previous| {
+            (
+                ChangeSet::diff(previous, &current, &scan.rows, &assets),
+                previous.fingerprint(),
+            )
+        });
+        if changes
+            .as_ref()
+            .is_some_and(|(changes, _)| changes.is_empty())
+        {
+            println!("没有变化");
+            return Ok(());
+        }
+
+        match &self.target {
+            WatchTarget::Database(database_url) => {
+                let client = match &mut self.client {
+                    Some(client) if !client.is_closed() => client,
+                    slot => slot.insert(connect(database_url)?),
+                };
+                let base = changes
+                    .as_ref()
+                    .map(|(changes, fingerprint)| (changes, fingerprint.as_str()));
+                let outcome =
+                    import_scan(client, &scan.rows, &assets, base, &current.fingerprint());
+                if outcome.is_err() {
+                    // 连接可能已不可用，下次重新连接
+                    self.client = None;
+                }
+                match outcome? {
+                    ImportOutcome::Incremental => {
+                        if let Some((changes, _)) = &changes {
+                            print!("{changes}");
+                        }
+                    }
+                    ImportOutcome::Full { nodes, assets } => {
+                        println!("已全量导入：{nodes} 个节点，{assets} 个资源文件");
+                    }
+                }
+            }
+            WatchTarget::Csv(dir) => {
+                write_csv_file(&dir.join(NODE_CSV), |writer| {
+                    write_node_csv(&scan.rows, writer)
+                })?;
+                write_csv_file(&dir.join(VISUAL_CSV), |writer| {
+                    write_visual_csv(&assets, writer)
+                })?;
+                println!("已重写 CSV: {}", dir.display());
+                if let Some((changes, _)) = &changes {
+                    print!("{changes}");
+                }
+            }
+        }
+
+        if let Some(deny_list) = self.deny_list {
+            write_nginx_deny_list(deny_list, &scan.hidden_roots)?;
+        }
+        if let Some(path) = self.manifest {
+            current.save(path)?;
+        }
+        self.previous = Some(current);
+        Ok(())
+    }
+}
+
+fn write_csv_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    let mut writer = BufWriter::new(
+        File::create(path).with_context(|| format!("无法创建输出文件: {}", path.display()))?,
+    );
+    write(&mut writer)?;
+    writer
+        .flush()
+        .with_context(|| format!("写入输出文件失败: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn filters_ignored_paths_and_own_outputs() {
+        let root = std::env::temp_dir().join(format!("ngt-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("drafts")).unwrap();
+        fs::create_dir_all(root.join("series/visual_assets")).unwrap();
+        fs::write(root.join("fileignore"), "drafts/\nvisual_assets/\n*.tmp\n").unwrap();
+
+        let output = root.join("exports");
+        let filter = EventFilter::new(&root, "fileignore", vec![output.clone()]).unwrap();
+        assert!(filter.is_relevant(&root.join("series")));
+        assert!(filter.is_relevant(&root.join("series/layout.md")));
+        // visual_assets 与 visual 扫描一样强制纳入
+        assert!(filter.is_relevant(&root.join("series/visual_assets/cover.jpg")));
+        assert!(filter.is_relevant(&root.join("fileignore")));
+        assert!(!filter.is_relevant(&root.join("drafts/notes.md")));
+        assert!(!filter.is_relevant(&root.join("series/upload.tmp")));
+        assert!(!filter.is_relevant(&output.join("node.csv")));
+        assert!(!filter.is_relevant(Path::new("/elsewhere/file.md")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}